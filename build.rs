@@ -0,0 +1,46 @@
+// Rust language amplification derive library providing multiple generic trait
+// implementations, type wrappers, derive macros and other language enhancements
+//
+// Written in 2019-2020 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the MIT License
+// along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+use std::env;
+use std::process::Command;
+
+/// `core::error::Error` is stable since Rust 1.81; the same compiler builds
+/// this proc macro and the code it generates.
+const CORE_ERROR_MINOR: u32 = 81;
+
+fn main() {
+    println!("cargo:rustc-check-cfg=cfg(core_error)");
+    println!("cargo:rustc-check-cfg=cfg(nightly)");
+
+    let rustc = env::var_os("RUSTC").unwrap_or_else(|| "rustc".into());
+    let version = Command::new(rustc)
+        .arg("--version")
+        .output()
+        .ok()
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .unwrap_or_default();
+    let minor = version
+        .split('.')
+        .nth(1)
+        .and_then(|minor| minor.parse::<u32>().ok());
+    if minor.map_or(false, |minor| minor >= CORE_ERROR_MINOR) {
+        println!("cargo:rustc-cfg=core_error");
+    }
+    // Examples using unstable library features are compiled only by nightly
+    // (or locally built) compilers
+    if version.contains("-nightly") || version.contains("-dev") {
+        println!("cargo:rustc-cfg=nightly");
+    }
+}
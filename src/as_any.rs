@@ -16,16 +16,42 @@
 use proc_macro2::TokenStream as TokenStream2;
 use syn::{DeriveInput, Result};
 
+use crate::util::CrateSettings;
+
 pub(crate) fn inner(input: DeriveInput) -> Result<TokenStream2> {
     let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
     let ident_name = &input.ident;
+    let amplify_crate = CrateSettings::with(&input.attrs)?.amplify;
 
     Ok(quote! {
         #[automatically_derived]
-        impl #impl_generics ::amplify::AsAny for #ident_name #ty_generics #where_clause {
+        impl #impl_generics #amplify_crate::AsAny for #ident_name #ty_generics #where_clause {
            fn as_any(&self) -> &dyn ::core::any::Any {
                 self as &dyn ::core::any::Any
             }
         }
     })
 }
+
+pub(crate) fn inner_mut(input: DeriveInput) -> Result<TokenStream2> {
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let ident_name = &input.ident;
+    let settings = CrateSettings::with(&input.attrs)?;
+    let amplify_crate = &settings.amplify;
+    let alloc_crate = settings.alloc_crate();
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics #amplify_crate::AsAnyMut for #ident_name #ty_generics #where_clause {
+            fn as_any_mut(&mut self) -> &mut dyn ::core::any::Any {
+                self as &mut dyn ::core::any::Any
+            }
+
+            fn into_any(
+                self: #alloc_crate::boxed::Box<Self>,
+            ) -> #alloc_crate::boxed::Box<dyn ::core::any::Any> {
+                self
+            }
+        }
+    })
+}
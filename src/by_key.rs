@@ -0,0 +1,96 @@
+// Rust language amplification derive library providing multiple generic trait
+// implementations, type wrappers, derive macros and other language enhancements
+//
+// Written in 2019-2020 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the MIT License
+// along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+use amplify_syn::{SelectedField, mentions_params};
+use proc_macro2::TokenStream as TokenStream2;
+use syn::{DeriveInput, Generics, Result, Type, TypeParamBound};
+
+use crate::wrapper::select_field;
+
+pub(crate) fn inner_cmp(mut input: DeriveInput) -> Result<TokenStream2> {
+    let SelectedField {
+        member: field,
+        ty: key,
+        ..
+    } = select_field(&input, "`CmpByKey`", "key")?;
+    bound_key(&mut input.generics, &key, parse_quote! { ::core::cmp::Ord });
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let ident_name = &input.ident;
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics ::core::cmp::PartialEq for #ident_name #ty_generics #where_clause {
+            #[inline]
+            fn eq(&self, other: &Self) -> bool {
+                self.#field == other.#field
+            }
+        }
+
+        #[automatically_derived]
+        impl #impl_generics ::core::cmp::Eq for #ident_name #ty_generics #where_clause {}
+
+        #[automatically_derived]
+        impl #impl_generics ::core::cmp::PartialOrd for #ident_name #ty_generics #where_clause {
+            #[inline]
+            fn partial_cmp(&self, other: &Self) -> Option<::core::cmp::Ordering> {
+                Some(::core::cmp::Ord::cmp(self, other))
+            }
+        }
+
+        #[automatically_derived]
+        impl #impl_generics ::core::cmp::Ord for #ident_name #ty_generics #where_clause {
+            #[inline]
+            fn cmp(&self, other: &Self) -> ::core::cmp::Ordering {
+                ::core::cmp::Ord::cmp(&self.#field, &other.#field)
+            }
+        }
+    })
+}
+
+pub(crate) fn inner_hash(mut input: DeriveInput) -> Result<TokenStream2> {
+    let SelectedField {
+        member: field,
+        ty: key,
+        ..
+    } = select_field(&input, "`HashByKey`", "key")?;
+    bound_key(&mut input.generics, &key, parse_quote! { ::core::hash::Hash });
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let ident_name = &input.ident;
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics ::core::hash::Hash for #ident_name #ty_generics #where_clause {
+            #[inline]
+            fn hash<H: ::core::hash::Hasher>(&self, state: &mut H) {
+                ::core::hash::Hash::hash(&self.#field, state)
+            }
+        }
+    })
+}
+
+/// Requires the key type to implement the given trait, if the type depends on
+/// the generic parameters.
+fn bound_key(generics: &mut Generics, key: &Type, bound: TypeParamBound) {
+    let params = generics
+        .type_params()
+        .map(|param| param.ident.clone())
+        .collect::<Vec<_>>();
+    let params = params.iter().collect::<Vec<_>>();
+    if mentions_params(quote! { #key }, &params) {
+        amplify_syn::add_bounds(generics, [(key.clone(), bound)]);
+    }
+}
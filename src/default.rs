@@ -0,0 +1,141 @@
+// Rust language amplification derive library providing multiple generic trait
+// implementations, type wrappers, derive macros and other language enhancements
+//
+// Written in 2019-2020 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the MIT License
+// along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+use amplify_syn::mentions_params;
+use proc_macro2::TokenStream as TokenStream2;
+use syn::spanned::Spanned;
+use syn::{
+    Attribute, Data, DataEnum, DataStruct, DeriveInput, Error, Expr, Fields, Meta, Result, Type,
+};
+
+use crate::util::field_value;
+
+const NAME: &str = "default";
+const EXAMPLE: &str = r#"#[default(42)] or #[default("literal")]"#;
+
+pub(crate) fn inner(mut input: DeriveInput) -> Result<TokenStream2> {
+    let (construct, types) = match input.data {
+        Data::Struct(DataStruct { ref fields, .. }) => {
+            let (init, types) = fields_default(fields)?;
+            (quote! { Self #init }, types)
+        }
+        Data::Enum(DataEnum { ref variants, .. }) => {
+            let mut defaults = variants
+                .iter()
+                .filter(|variant| variant.attrs.iter().any(|attr| attr.path().is_ident(NAME)));
+            let variant = defaults.next().ok_or_else(|| {
+                Error::new_spanned(
+                    &input.ident,
+                    "`SmartDefault` requires one of the enum variants to be marked with \
+                     `#[default]` attribute",
+                )
+            })?;
+            if let Some(other) = defaults.next() {
+                return Err(Error::new_spanned(
+                    &other.ident,
+                    "only one enum variant can be marked with `#[default]` attribute",
+                ));
+            }
+            for attr in variant
+                .attrs
+                .iter()
+                .filter(|attr| attr.path().is_ident(NAME))
+            {
+                if !matches!(attr.meta, Meta::Path(_)) {
+                    return Err(attr_err!(attr, "enum variants take no arguments"));
+                }
+            }
+            let (init, types) = fields_default(&variant.fields)?;
+            let ident = &variant.ident;
+            (quote! { Self::#ident #init }, types)
+        }
+        Data::Union(_) => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "Deriving `SmartDefault` is not supported in unions",
+            ));
+        }
+    };
+
+    // Only the types of the fields defaulting to `Default::default()` must
+    // implement `Default`
+    let params = input
+        .generics
+        .type_params()
+        .map(|param| param.ident.clone())
+        .collect::<Vec<_>>();
+    let params = params.iter().collect::<Vec<_>>();
+    let bounds = types
+        .into_iter()
+        .filter(|ty| mentions_params(quote! { #ty }, &params))
+        .collect::<Vec<_>>();
+    if !bounds.is_empty() {
+        let where_clause = input.generics.make_where_clause();
+        for ty in bounds {
+            where_clause
+                .predicates
+                .push(parse_quote! { #ty: ::core::default::Default });
+        }
+    }
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let ident_name = &input.ident;
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics ::core::default::Default for #ident_name #ty_generics #where_clause {
+            fn default() -> Self {
+                #construct
+            }
+        }
+    })
+}
+
+/// Constructs initializer for the fields and returns types of the fields
+/// which values are constructed with `Default::default()`.
+fn fields_default(fields: &Fields) -> Result<(TokenStream2, Vec<Type>)> {
+    let mut types = vec![];
+    let mut values = Vec::with_capacity(fields.len());
+    for field in fields {
+        let value = match field.attrs.iter().find(|attr| attr.path().is_ident(NAME)) {
+            Some(attr) => field_default(attr)?,
+            None => {
+                types.push(field.ty.clone());
+                quote! { ::core::default::Default::default() }
+            }
+        };
+        values.push(match field.ident {
+            Some(ref ident) => quote! { #ident: #value },
+            None => value,
+        });
+    }
+    Ok(match fields {
+        Fields::Named(_) => (quote! { { #( #values ),* } }, types),
+        Fields::Unnamed(_) => (quote! { ( #( #values ),* ) }, types),
+        Fields::Unit => (quote! {}, types),
+    })
+}
+
+/// Parses `#[default(expr)]` field attribute. String literals are converted
+/// into the field type, such that they can be used for `String` fields.
+fn field_default(attr: &Attribute) -> Result<TokenStream2> {
+    if let Meta::Path(_) = attr.meta {
+        return Err(attr_err!(attr, "fields require default value expression"));
+    }
+    let expr = attr
+        .parse_args::<Expr>()
+        .map_err(|_| attr_err!(attr, "expected single expression"))?;
+    Ok(field_value(&expr))
+}
@@ -14,16 +14,33 @@
 // along with this software.
 // If not, see <https://opensource.org/licenses/MIT>.
 
-use proc_macro2::{Span, TokenStream as TokenStream2};
+use amplify_syn::{FormatPiece, FormatString, Warnings};
+use proc_macro2::{Group, Literal, Span, TokenStream as TokenStream2, TokenTree};
+use syn::parse::ParseStream;
 use syn::spanned::Spanned;
 use syn::{
-    Attribute, Data, DataEnum, DataStruct, DataUnion, DeriveInput, Error, Fields, Ident, Index,
-    Lit, LitStr, Meta, MetaNameValue, NestedMeta, Path, Result,
+    Attribute, Data, DataEnum, DataStruct, DataUnion, DeriveInput, Error, Expr, ExprLit, Field,
+    Fields, Generics, Ident, Index, Lit, LitStr, Member, Meta, MetaList, MetaNameValue, Path,
+    Result, Token, Type, TypeParamBound, Variant, WherePredicate,
 };
 
+use crate::util::{CrateSettings, NestedArg, nested_args};
+
 const NAME: &str = "display";
-const EXAMPLE: &str = r#"#[display("format {} string" | Trait | Type::function)]"#;
-const FIELD_EXAMPLE: &str = r#"#[display(separator = "...")]"#;
+const DEBUG: &str = "debug";
+const DISCRIMINANT: &str = "discriminant";
+const VARIANT: &str = "{variant}";
+const VARIANT_ALIAS: &str = "{_variant_}";
+const WARN_UNUSED: &str = "warn_unused";
+const FMT_ALL: &str = "fmt_all";
+const STATIC_STR: &str = "static_str";
+const BOUND: &str = "bound";
+const CATALOG: &str = "catalog";
+/// Flags which may be given to `display` attribute in addition to the
+/// formatting arguments.
+const FLAGS: [&str; 3] = [WARN_UNUSED, FMT_ALL, STATIC_STR];
+const EXAMPLE: &str = r#"#[display("format {} string" [, args...] | Trait | Type::function | with = path::to::function)]"#;
+const FIELD_EXAMPLE: &str = r#"#[display(separator = "..." | join = "..." | none = "..." | truncate = 8 | skip | with = path::to::function)]"#;
 
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
 enum FormattingTrait {
@@ -38,6 +55,18 @@ enum FormattingTrait {
 }
 
 impl FormattingTrait {
+    /// Traits which are forwarded by field adapters (`join`, `none`) to the
+    /// field values, such that format specs like `{field:02x}` apply to them.
+    const FORWARDED: [FormattingTrait; 7] = [
+        FormattingTrait::Debug,
+        FormattingTrait::Octal,
+        FormattingTrait::Binary,
+        FormattingTrait::LowerHex,
+        FormattingTrait::UpperHex,
+        FormattingTrait::LowerExp,
+        FormattingTrait::UpperExp,
+    ];
+
     pub fn from_path(path: &Path, span: Span) -> Result<Option<Self>> {
         path.segments.first().map_or(
             Err(attr_err!(span, NAME, "must contain at least one identifier", EXAMPLE)),
@@ -57,6 +86,24 @@ impl FormattingTrait {
         )
     }
 
+    /// Detects formatting trait out of the format specification of a
+    /// placeholder; returns `None` for `Display`.
+    pub fn from_spec(spec: &str) -> Option<Self> {
+        if spec.ends_with('?') {
+            return Some(FormattingTrait::Debug);
+        }
+        Some(match spec.chars().last()? {
+            'o' => FormattingTrait::Octal,
+            'b' => FormattingTrait::Binary,
+            'p' => FormattingTrait::Pointer,
+            'x' => FormattingTrait::LowerHex,
+            'X' => FormattingTrait::UpperHex,
+            'e' => FormattingTrait::LowerExp,
+            'E' => FormattingTrait::UpperExp,
+            _ => return None,
+        })
+    }
+
     pub fn to_fmt(self, alt: bool) -> TokenStream2 {
         let mut fmt = match self {
             FormattingTrait::Debug => "{:?}",
@@ -75,6 +122,19 @@ impl FormattingTrait {
         quote! { #fmt }
     }
 
+    pub fn path(self) -> TokenStream2 {
+        match self {
+            FormattingTrait::Debug => quote! { ::core::fmt::Debug },
+            FormattingTrait::Octal => quote! { ::core::fmt::Octal },
+            FormattingTrait::Binary => quote! { ::core::fmt::Binary },
+            FormattingTrait::Pointer => quote! { ::core::fmt::Pointer },
+            FormattingTrait::LowerHex => quote! { ::core::fmt::LowerHex },
+            FormattingTrait::UpperHex => quote! { ::core::fmt::UpperHex },
+            FormattingTrait::LowerExp => quote! { ::core::fmt::LowerExp },
+            FormattingTrait::UpperExp => quote! { ::core::fmt::UpperExp },
+        }
+    }
+
     pub fn into_token_stream2(self, span: Span) -> TokenStream2 {
         match self {
             FormattingTrait::Debug => quote_spanned! { span =>
@@ -105,15 +165,357 @@ impl FormattingTrait {
     }
 }
 
+/// Text encoding used to display the bytes of a single-field structure.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+enum Encoding {
+    Base32,
+    Base58,
+    Base64,
+}
+
+impl Encoding {
+    pub fn from_path(path: &Path) -> Result<Option<Self>> {
+        let encoding = match path.get_ident().map(Ident::to_string).as_deref() {
+            Some("base32") => Encoding::Base32,
+            Some("base58") => Encoding::Base58,
+            Some("base64") => Encoding::Base64,
+            _ => return Ok(None),
+        };
+        let enabled = match encoding {
+            Encoding::Base32 => cfg!(feature = "base32"),
+            Encoding::Base58 => cfg!(feature = "base58"),
+            Encoding::Base64 => cfg!(feature = "base64"),
+        };
+        if !enabled {
+            let msg = format!(
+                "displaying with `{0}` requires `{0}` feature of `amplify_derive`",
+                path.get_ident().expect("checked above")
+            );
+            return Err(attr_err!(path.span(), msg));
+        }
+        Ok(Some(encoding))
+    }
+
+    /// Expression encoding `bytes` slice into a `String`.
+    fn encode(self, bytes: TokenStream2, span: Span) -> TokenStream2 {
+        match self {
+            Encoding::Base32 => quote_spanned! { span =>
+                ::data_encoding::BASE32_NOPAD.encode(#bytes)
+            },
+            Encoding::Base58 => quote_spanned! { span => ::bs58::encode(#bytes).into_string() },
+            Encoding::Base64 => quote_spanned! { span => ::data_encoding::BASE64.encode(#bytes) },
+        }
+    }
+
+    /// Expression decoding `s` string into `Option<Vec<u8>>`.
+    fn decode(self, span: Span) -> TokenStream2 {
+        match self {
+            Encoding::Base32 => quote_spanned! { span =>
+                ::data_encoding::BASE32_NOPAD.decode(s.as_bytes()).ok()
+            },
+            Encoding::Base58 => quote_spanned! { span => ::bs58::decode(s).into_vec().ok() },
+            Encoding::Base64 => quote_spanned! { span =>
+                ::data_encoding::BASE64.decode(s.as_bytes()).ok()
+            },
+        }
+    }
+
+    pub fn into_token_stream2(self, fields: &Fields, span: Span) -> TokenStream2 {
+        let member = match single_member(fields) {
+            Some(member) => member,
+            None => return Error::new(span, ENCODING_ERR).to_compile_error(),
+        };
+        let encoded = self.encode(
+            quote_spanned! { span => ::core::convert::AsRef::<[u8]>::as_ref(&self.#member) },
+            span,
+        );
+        quote_spanned! { span => f.pad(&#encoded) }
+    }
+
+    /// Statement parsing the structure out of the encoded string `s`.
+    fn parse(self, fields: &Fields, span: Span) -> Result<TokenStream2> {
+        let member = single_member(fields).ok_or_else(|| Error::new(span, ENCODING_ERR))?;
+        let decoded = self.decode(span);
+        Ok(quote_spanned! { span =>
+            if let Some(Ok(value)) = #decoded.map(::core::convert::TryFrom::try_from) {
+                return Ok(Self { #member: value });
+            }
+        })
+    }
+}
+
+const ENCODING_ERR: &str = "displaying with `base32`, `base58` or `base64` is supported only for \
+                            structures with a single field";
+
+/// Returns member of the only field of a structure.
+fn single_member(fields: &Fields) -> Option<Member> {
+    if fields.len() != 1 {
+        return None;
+    }
+    field_member(fields, "_0").or_else(|| {
+        fields
+            .iter()
+            .next()
+            .and_then(|field| field.ident.clone())
+            .map(Member::Named)
+    })
+}
+
+/// Serialization format used to display the value with `serde`.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+enum Serialization {
+    Json,
+    JsonPretty,
+    Yaml,
+    Toml,
+}
+
+impl Serialization {
+    pub fn from_path(path: &Path) -> Result<Option<Self>> {
+        let (format, feature) = match path.get_ident().map(Ident::to_string).as_deref() {
+            Some("json") => (Serialization::Json, cfg!(feature = "serde_json")),
+            Some("json_pretty") => (Serialization::JsonPretty, cfg!(feature = "serde_json")),
+            Some("yaml") => (Serialization::Yaml, cfg!(feature = "serde_yaml")),
+            Some("toml") => (Serialization::Toml, cfg!(feature = "toml")),
+            _ => return Ok(None),
+        };
+        if !feature {
+            let msg = format!(
+                "displaying with `{}` requires `{}` feature of `amplify_derive`",
+                path.get_ident().expect("checked above"),
+                format.feature()
+            );
+            return Err(attr_err!(path.span(), msg));
+        }
+        Ok(Some(format))
+    }
+
+    pub fn feature(self) -> &'static str {
+        match self {
+            Serialization::Json | Serialization::JsonPretty => "serde_json",
+            Serialization::Yaml => "serde_yaml",
+            Serialization::Toml => "toml",
+        }
+    }
+
+    pub fn into_token_stream2(self, span: Span) -> TokenStream2 {
+        let serializer = match self {
+            Serialization::Json => quote_spanned! { span => ::serde_json::to_string },
+            Serialization::JsonPretty => quote_spanned! { span => ::serde_json::to_string_pretty },
+            Serialization::Yaml => quote_spanned! { span => ::serde_yaml::to_string },
+            Serialization::Toml => quote_spanned! { span => ::toml::to_string },
+        };
+        quote_spanned! { span =>
+            match #serializer(self) {
+                Ok(s) => f.pad(&s),
+                Err(_) => Err(::core::fmt::Error),
+            }
+        }
+    }
+}
+
+/// Case transformation applied to the identifier of an enum variant.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub(crate) enum Case {
+    Lower,
+    Upper,
+    Snake,
+    Kebab,
+    Camel,
+    Pascal,
+    ScreamingSnake,
+    ScreamingKebab,
+}
+
+impl Case {
+    const ALL: [Case; 8] = [
+        Case::Lower,
+        Case::Upper,
+        Case::Snake,
+        Case::Kebab,
+        Case::Camel,
+        Case::Pascal,
+        Case::ScreamingSnake,
+        Case::ScreamingKebab,
+    ];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "lowercase" => Case::Lower,
+            "uppercase" | "UPPERCASE" => Case::Upper,
+            "snake_case" => Case::Snake,
+            "kebab-case" => Case::Kebab,
+            "camelCase" => Case::Camel,
+            "PascalCase" => Case::Pascal,
+            "SCREAMING_SNAKE_CASE" => Case::ScreamingSnake,
+            "SCREAMING-KEBAB-CASE" => Case::ScreamingKebab,
+            _ => return None,
+        })
+    }
+
+    pub fn from_path(path: &Path) -> Option<Self> {
+        path.get_ident()
+            .and_then(|ident| Self::from_name(&ident.to_string()))
+    }
+
+    /// Placeholder which can be used inside format strings and is replaced
+    /// with the transformed variant identifier.
+    pub fn placeholder(self) -> &'static str {
+        match self {
+            Case::Lower => "{lowercase}",
+            Case::Upper => "{UPPERCASE}",
+            Case::Snake => "{snake_case}",
+            Case::Kebab => "{kebab-case}",
+            Case::Camel => "{camelCase}",
+            Case::Pascal => "{PascalCase}",
+            Case::ScreamingSnake => "{SCREAMING_SNAKE_CASE}",
+            Case::ScreamingKebab => "{SCREAMING-KEBAB-CASE}",
+        }
+    }
+
+    pub fn apply(self, ident: &str) -> String {
+        let words = split_words(ident);
+        let capitalize = |word: &String| {
+            let mut chars = word.chars();
+            chars.next().map_or_else(String::new, |first| {
+                first
+                    .to_uppercase()
+                    .chain(chars.flat_map(char::to_lowercase))
+                    .collect()
+            })
+        };
+        match self {
+            Case::Lower => ident.to_lowercase(),
+            Case::Upper => ident.to_uppercase(),
+            Case::Snake => words.join("_").to_lowercase(),
+            Case::Kebab => words.join("-").to_lowercase(),
+            Case::Camel => words
+                .iter()
+                .enumerate()
+                .map(|(i, word)| {
+                    if i == 0 {
+                        word.to_lowercase()
+                    } else {
+                        capitalize(word)
+                    }
+                })
+                .collect(),
+            Case::Pascal => words.iter().map(capitalize).collect(),
+            Case::ScreamingSnake => words.join("_").to_uppercase(),
+            Case::ScreamingKebab => words.join("-").to_uppercase(),
+        }
+    }
+}
+
+/// Splits identifier into words on underscores and case boundaries, keeping
+/// acronyms together (`HTTPServer` gives `HTTP` and `Server`).
+fn split_words(ident: &str) -> Vec<String> {
+    let chars = ident.chars().collect::<Vec<_>>();
+    let mut words = Vec::<String>::new();
+    let mut word = String::new();
+    for (i, c) in chars.iter().copied().enumerate() {
+        if c == '_' || c == '-' {
+            if !word.is_empty() {
+                words.push(word.split_off(0));
+            }
+            continue;
+        }
+        let boundary = c.is_uppercase() &&
+            i > 0 &&
+            (!chars[i - 1].is_uppercase() ||
+                chars
+                    .get(i + 1)
+                    .map(|next| next.is_lowercase())
+                    .unwrap_or_default());
+        if boundary && !word.is_empty() {
+            words.push(word.split_off(0));
+        }
+        word.push(c);
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    words
+}
+
+/// Detects hyphenated case names like `#[display(kebab-case)]`, which can't
+/// be parsed as a meta item.
+fn hyphenated_case(attr: &Attribute) -> Option<Case> {
+    attr.parse_args_with(|input: ParseStream| {
+        let mut name = input.parse::<Ident>()?.to_string();
+        while !input.is_empty() {
+            input.parse::<Token![-]>()?;
+            name.push('-');
+            name.push_str(&input.parse::<Ident>()?.to_string());
+        }
+        Ok(name)
+    })
+    .ok()
+    .filter(|name| name.contains('-'))
+    .and_then(|name| Case::from_name(&name))
+}
+
+/// Detects `#[display(with = path::to::function)]`, which can't be parsed as
+/// a meta item since its value is not a literal.
+fn with_function(attr: &Attribute) -> Option<Path> {
+    attr.parse_args_with(|input: ParseStream| {
+        let key = input.parse::<Ident>()?;
+        if key != "with" {
+            return Err(Error::new(key.span(), "expected `with`"));
+        }
+        input.parse::<Token![=]>()?;
+        input.parse::<Path>()
+    })
+    .ok()
+}
+
+/// Detects `format!`-like `#[display("{} ({})", expr, expr)]` form, which
+/// can't be parsed as a meta item since its arguments are expressions.
+fn format_args(attr: &Attribute) -> Option<(LitStr, Vec<Expr>)> {
+    attr.parse_args_with(|input: ParseStream| {
+        let format = input.parse::<LitStr>()?;
+        input.parse::<Token![,]>()?;
+        let args = syn::punctuated::Punctuated::<Expr, Token![,]>::parse_terminated(input)?;
+        Ok((format, args.into_iter().collect::<Vec<_>>()))
+    })
+    .ok()
+    .filter(|(_, args)| {
+        // `alt = "..."` is an assignment expression, but it is not an argument
+        !args.is_empty() &&
+            !args.iter().any(|arg| match arg {
+                Expr::Path(path) => {
+                    FLAGS.iter().any(|flag| path.path.is_ident(flag)) ||
+                        Case::from_path(&path.path).is_some()
+                }
+                Expr::Assign(assign) => {
+                    if let Expr::Path(path) = &*assign.left {
+                        path.path.is_ident("alt") ||
+                            path.path.is_ident(BOUND) ||
+                            path.path.is_ident(CATALOG)
+                    } else {
+                        false
+                    }
+                }
+                _ => false,
+            })
+    })
+}
+
 #[derive(Clone)]
 enum Technique {
     FromTrait(FormattingTrait),
     FromMethod(Path),
+    WithFunction(Path),
+    Serialized(Serialization),
+    Encoded(Encoding),
     WithFormat(LitStr, Option<LitStr>),
-    DocComments(String),
+    WithArgs(LitStr, Vec<Expr>),
+    /// Doc comments text; the flag indicates that only the first line of the
+    /// comment must be used.
+    DocComments(String, bool),
     Inner,
-    Lowercase(String),
-    Uppercase(String),
+    Case(Case, String),
+    RenameAll(Case),
 }
 
 impl Technique {
@@ -121,47 +523,85 @@ impl Technique {
         attrs: impl IntoIterator<Item = &'a Attribute> + Clone,
         span: Span,
     ) -> Result<Option<Self>> {
-        let mut res = match attrs
+        let attr = attrs
             .clone()
             .into_iter()
-            .find(|attr| attr.path.is_ident(NAME))
-            .map(|attr| attr.parse_meta())
-            .map_or(Ok(None), |r| r.map(Some))?
-        {
+            .find(|attr| attr.path().is_ident(NAME));
+        if let Some(case) = attr.and_then(hyphenated_case) {
+            return Ok(Some(Technique::Case(case, String::new())));
+        }
+        if let Some(path) = attr.and_then(with_function) {
+            return Ok(Some(Technique::WithFunction(path)));
+        }
+        if let Some((format, args)) = attr.and_then(format_args) {
+            return Ok(Some(Technique::WithArgs(format, args)));
+        }
+        let mut res = match attr.map(|attr| &attr.meta) {
             Some(Meta::List(list)) => {
-                if list.nested.len() > 2 {
+                let args = nested_args(list)?;
+                let nested = args
+                    .iter()
+                    .filter(|nested| {
+                        is_flag(nested).is_none() && !is_bound(nested) && !is_catalog(nested)
+                    })
+                    .collect::<Vec<_>>();
+                if nested.len() > 2 {
                     return Err(attr_err!(span, "too many arguments"));
                 }
-                let mut iter = list.nested.iter();
+                let mut iter = nested.into_iter();
                 let mut res = match iter.next() {
-                    Some(NestedMeta::Lit(Lit::Str(format))) => {
+                    Some(NestedArg::Lit(Lit::Str(format))) => {
                         Some(Technique::WithFormat(format.clone(), None))
                     }
-                    Some(NestedMeta::Meta(Meta::Path(path)))
+                    Some(NestedArg::Meta(Meta::Path(path)))
                         if path.is_ident("doc_comments") || path.is_ident("docs") =>
                     {
-                        Some(Technique::DocComments(String::new()))
+                        Some(Technique::DocComments(String::new(), false))
                     }
-                    Some(NestedMeta::Meta(Meta::Path(path))) if path.is_ident("inner") => {
+                    Some(NestedArg::Meta(Meta::Path(path))) if path.is_ident("inner") => {
                         Some(Technique::Inner)
                     }
-                    Some(NestedMeta::Meta(Meta::Path(path))) if path.is_ident("lowercase") => {
-                        Some(Technique::Lowercase(String::new()))
+                    Some(NestedArg::Meta(Meta::NameValue(MetaNameValue {
+                        path,
+                        value:
+                            Expr::Lit(ExprLit {
+                                lit: Lit::Str(case),
+                                ..
+                            }),
+                        ..
+                    }))) if path.is_ident("rename_all") => {
+                        Some(Technique::RenameAll(Case::from_name(&case.value()).ok_or_else(
+                            || attr_err!(case.span(), "unknown case in `rename_all`"),
+                        )?))
+                    }
+                    Some(NestedArg::Meta(Meta::Path(path)))
+                        if Serialization::from_path(path)?.is_some() =>
+                    {
+                        Serialization::from_path(path)?.map(Technique::Serialized)
+                    }
+                    Some(NestedArg::Meta(Meta::Path(path)))
+                        if Encoding::from_path(path)?.is_some() =>
+                    {
+                        Encoding::from_path(path)?.map(Technique::Encoded)
                     }
-                    Some(NestedMeta::Meta(Meta::Path(path))) if path.is_ident("uppercase") => {
-                        Some(Technique::Uppercase(String::new()))
+                    Some(NestedArg::Meta(Meta::Path(path))) if Case::from_path(path).is_some() => {
+                        Case::from_path(path).map(|case| Technique::Case(case, String::new()))
                     }
-                    Some(NestedMeta::Meta(Meta::Path(path))) => Some(
+                    Some(NestedArg::Meta(Meta::Path(path))) => Some(
                         FormattingTrait::from_path(path, list.span())?
                             .map_or(Technique::FromMethod(path.clone()), Technique::FromTrait),
                     ),
                     Some(_) => return Err(attr_err!(span, "argument must be a string literal")),
+                    None if !args.is_empty() => None,
                     None => return Err(attr_err!(span, "argument is required")),
                 };
                 res = match iter.next() {
-                    Some(NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                    Some(NestedArg::Meta(Meta::NameValue(MetaNameValue {
                         path,
-                        lit: Lit::Str(alt),
+                        value:
+                            Expr::Lit(ExprLit {
+                                lit: Lit::Str(alt), ..
+                            }),
                         ..
                     }))) if Some("alt".to_string()) == path.get_ident().map(Ident::to_string) => {
                         if iter.count() > 0 {
@@ -180,15 +620,47 @@ impl Technique {
                             }
                         }
                     }
+                    Some(NestedArg::Meta(Meta::Path(path))) if path.is_ident("first_line") => {
+                        match res {
+                            Some(Technique::DocComments(doc, _)) => {
+                                Some(Technique::DocComments(doc, true))
+                            }
+                            _ => {
+                                return Err(attr_err!(
+                                    span,
+                                    "`first_line` can be given only together with `doc_comments`"
+                                ));
+                            }
+                        }
+                    }
+                    Some(NestedArg::Meta(Meta::Path(path))) if Case::from_path(path).is_some() => {
+                        match (res, Case::from_path(path)) {
+                            (Some(mut technique @ Technique::WithFormat(..)), Some(case)) => {
+                                technique.set_variant_case(case);
+                                Some(technique)
+                            }
+                            _ => {
+                                return Err(attr_err!(
+                                    span,
+                                    "case of the variant name can be given only if the first \
+                                     argument is a format string"
+                                ));
+                            }
+                        }
+                    }
                     None => res,
                     _ => return Err(attr_err!(span, "unrecognizable second argument")),
                 };
                 res
             }
             Some(Meta::NameValue(MetaNameValue {
-                lit: Lit::Str(format),
+                value:
+                    Expr::Lit(ExprLit {
+                        lit: Lit::Str(format),
+                        ..
+                    }),
                 ..
-            })) => Some(Technique::WithFormat(format, None)),
+            })) => Some(Technique::WithFormat(format.clone(), None)),
             Some(_) => return Err(attr_err!(span, "argument must be a string literal")),
             None => None,
         };
@@ -206,7 +678,12 @@ impl Technique {
     pub fn to_fmt(&self, alt: bool) -> TokenStream2 {
         match self {
             Technique::FromTrait(fmt) => fmt.to_fmt(alt),
-            Technique::FromMethod(_) => quote! { "{}" },
+            Technique::FromMethod(_) |
+            Technique::WithFunction(_) |
+            Technique::Serialized(_) |
+            Technique::Encoded(_) => {
+                quote! { "{}" }
+            }
             Technique::WithFormat(fmt, fmt_alt) => {
                 if alt && fmt_alt.is_some() {
                     let alt = fmt_alt
@@ -217,7 +694,8 @@ impl Technique {
                     quote! {#fmt}
                 }
             }
-            Technique::DocComments(doc) => quote! { #doc },
+            Technique::WithArgs(fmt, _) => quote! { #fmt },
+            Technique::DocComments(doc, _) => quote! { #doc },
             Technique::Inner => {
                 if alt {
                     quote! { "{_0:#}" }
@@ -225,8 +703,8 @@ impl Technique {
                     quote! { "{_0}" }
                 }
             }
-            Technique::Lowercase(fields_fmt) => quote! { #fields_fmt },
-            Technique::Uppercase(fields_fmt) => quote! { #fields_fmt },
+            Technique::Case(_, fields_fmt) => quote! { #fields_fmt },
+            Technique::RenameAll(_) => unreachable!("`rename_all` is resolved by enum derivation"),
         }
     }
 
@@ -234,9 +712,20 @@ impl Technique {
     pub fn into_token_stream2(self, fields: &Fields, span: Span, alt: bool) -> TokenStream2 {
         match self {
             Technique::FromTrait(fmt) => fmt.into_token_stream2(span),
+            Technique::Serialized(format) => format.into_token_stream2(span),
+            Technique::Encoded(encoding) => encoding.into_token_stream2(fields, span),
+            Technique::WithFunction(path) => quote_spanned! { span =>
+                #path(self, f)
+            },
             Technique::FromMethod(path) => quote_spanned! { span =>
                 ::core::fmt::Display::fmt(&#path(self), f)
             },
+            Technique::WithArgs(fmt, args) => {
+                let idents = format_args_fields(fields, &fmt.value());
+                quote_spanned! { span =>
+                    write!(f, #fmt, #( #args, )* #( #idents = &self.#idents, )*)
+                }
+            }
             Technique::WithFormat(fmt, fmt_alt) => {
                 let format = if alt && fmt_alt.is_some() {
                     let alt = fmt_alt.expect("we just checked that there are data");
@@ -246,7 +735,7 @@ impl Technique {
                 };
                 Self::impl_format(fields, &format, span)
             }
-            Technique::DocComments(doc) => {
+            Technique::DocComments(doc, _) => {
                 let format = quote_spanned! { span => #doc };
                 Self::impl_format(fields, &format, span)
             }
@@ -258,14 +747,11 @@ impl Technique {
                 };
                 Self::impl_format(fields, &format, span)
             }
-            Technique::Lowercase(fields_fmt) => {
-                let format = quote_spanned! { span => #fields_fmt };
-                Self::impl_format(fields, &format, span)
-            }
-            Technique::Uppercase(fields_fmt) => {
+            Technique::Case(_, fields_fmt) => {
                 let format = quote_spanned! { span => #fields_fmt };
                 Self::impl_format(fields, &format, span)
             }
+            Technique::RenameAll(_) => unreachable!("`rename_all` is resolved by enum derivation"),
         }
     }
 
@@ -298,36 +784,27 @@ impl Technique {
             }
             Fields::Unit => {
                 quote_spanned! { span =>
-                    f.write_str(#format)
+                    f.pad(#format)
                 }
             }
         }
     }
 
-    fn apply_docs<'a>(&mut self, attrs: impl IntoIterator<Item = &'a Attribute> + Clone) {
-        if let Technique::DocComments(ref mut doc) = self {
-            for attr in attrs.into_iter().filter(|attr| attr.path.is_ident("doc")) {
-                if let Ok(Meta::NameValue(MetaNameValue {
-                    lit: Lit::Str(s), ..
-                })) = attr.parse_meta()
-                {
-                    let fragment = s.value().trim().replace("\\n", "\n");
-                    if fragment.is_empty() || fragment == "\n" {
-                        doc.push('\n');
-                    } else {
-                        doc.push_str(&fragment);
-                        doc.push(' ');
-                    }
-                }
-            }
-            *doc = doc.trim().replace(" \n", "\n");
+    fn apply_docs<'a>(&mut self, attrs: impl IntoIterator<Item = &'a Attribute>) {
+        if let Technique::DocComments(ref mut doc, first_line) = self {
+            *doc = if *first_line {
+                amplify_syn::doc_first_line(attrs)
+                    .map(|line| line.replace("\\n", "\n").trim().to_owned())
+                    .unwrap_or_default()
+            } else {
+                amplify_syn::doc_text(attrs)
+            };
         }
     }
 
     fn apply_case(&mut self, type_str: &str, fields: &Fields) {
         let (type_str_cased, fields_fmt) = match self {
-            Technique::Lowercase(ref mut f) => (type_str.to_lowercase(), f),
-            Technique::Uppercase(ref mut f) => (type_str.to_uppercase(), f),
+            Technique::Case(case, ref mut f) => (case.apply(type_str), f),
             _ => unreachable!(),
         };
         *fields_fmt = match fields {
@@ -355,18 +832,183 @@ impl Technique {
         };
     }
 
+    /// Replaces `{variant}` placeholder (and its `{_variant_}` alias) with the
+    /// placeholder of the given case, so the variant name gets transformed
+    /// with that case regardless of `rename_all`.
+    fn set_variant_case(&mut self, case: Case) {
+        let replace = |lit: &LitStr| {
+            let value = replace_placeholders(&lit.value(), |placeholder| {
+                if placeholder == VARIANT || placeholder == VARIANT_ALIAS {
+                    Some(case.placeholder().to_owned())
+                } else {
+                    None
+                }
+            });
+            LitStr::new(&value, lit.span())
+        };
+        if let Technique::WithFormat(fmt, alt) = self {
+            *fmt = replace(fmt);
+            if let Some(alt) = alt {
+                *alt = replace(alt);
+            }
+        }
+    }
+
+    /// Replaces case placeholders like `{snake_case}` in format strings with
+    /// the transformed variant identifier, and `{variant}` placeholder (or its
+    /// `{_variant_}` alias) with the variant name (renamed according to
+    /// `rename_all`, if any).
+    fn apply_case_placeholders(&mut self, type_str: &str, rename_all: Option<Case>) {
+        let replace = |s: &str| -> String {
+            let variant =
+                rename_all.map_or_else(|| type_str.to_owned(), |case| case.apply(type_str));
+            replace_placeholders(s, |placeholder| {
+                if placeholder == VARIANT || placeholder == VARIANT_ALIAS {
+                    return Some(variant.clone());
+                }
+                Case::ALL
+                    .iter()
+                    .find(|case| case.placeholder() == placeholder)
+                    .map(|case| case.apply(type_str))
+            })
+        };
+
+        match self {
+            Technique::WithFormat(fmt, alt) => {
+                *fmt = LitStr::new(&replace(&fmt.value()), fmt.span());
+                if let Some(alt) = alt {
+                    *alt = LitStr::new(&replace(&alt.value()), alt.span());
+                }
+            }
+            Technique::DocComments(doc, _) => *doc = replace(doc),
+            _ => {}
+        }
+    }
+
+    /// Detects formats consisting of a single field placeholder (with the
+    /// alternative format being absent or just setting the alternate flag) and
+    /// returns that field. Formatting of such field can be delegated directly
+    /// to its `Display` implementation, preserving width, fill, alignment and
+    /// precision set by the caller.
+    fn delegated_field(&self, fields: &Fields) -> Option<Member> {
+        let (fmt, alt) = match self {
+            Technique::Inner => ("{_0}".to_owned(), None),
+            Technique::WithFormat(fmt, alt) => (fmt.value(), alt.as_ref().map(LitStr::value)),
+            Technique::DocComments(doc, _) => (doc.clone(), None),
+            _ => return None,
+        };
+        let name = fmt.strip_prefix('{')?.strip_suffix('}')?;
+        if name.contains(['{', '}', ':']) {
+            return None;
+        }
+        if let Some(alt) = alt {
+            if alt != fmt && alt != format!("{{{}:#}}", name) {
+                return None;
+            }
+        }
+        match fields {
+            Fields::Named(fields) if name == "_0" && fields.named.len() == 1 => {
+                fields.named[0].ident.clone().map(Member::Named)
+            }
+            Fields::Named(fields) => fields
+                .named
+                .iter()
+                .filter(|field| !field.attrs.iter().any(|attr| attr.path().is_ident(NAME)))
+                .find_map(|field| field.ident.clone().filter(|ident| ident == name))
+                .map(Member::Named),
+            Fields::Unnamed(fields) => name
+                .strip_prefix('_')?
+                .parse::<usize>()
+                .ok()
+                .filter(|index| *index < fields.unnamed.len())
+                .map(|index| Member::Unnamed(Index::from(index))),
+            Fields::Unit => None,
+        }
+    }
+
+    /// Produces code displaying the value of the field returned by
+    /// [`Technique::delegated_field`]. The alternate flag is passed to the
+    /// field only with `inner` technique or if the alternative format is
+    /// `{field:#}`; otherwise the field is displayed without the flag, like
+    /// the format string does.
+    fn delegate(&self, value: TokenStream2) -> TokenStream2 {
+        let alternate = match self {
+            Technique::Inner => true,
+            Technique::WithFormat(fmt, Some(alt)) => fmt.value() != alt.value(),
+            _ => false,
+        };
+        if alternate {
+            quote! { ::core::fmt::Display::fmt(#value, f) }
+        } else {
+            quote! {
+                if f.alternate() {
+                    write!(f, "{}", #value)
+                } else {
+                    ::core::fmt::Display::fmt(#value, f)
+                }
+            }
+        }
+    }
+
+    /// Detects whether format strings contain any placeholders, which must be
+    /// resolved individually for each of enum variants.
+    fn has_placeholders(&self) -> bool {
+        let contains = |s: &str| {
+            FormatString::parse(s).map_or(true, |fmt| fmt.placeholders().next().is_some())
+        };
+        match self {
+            Technique::WithFormat(fmt, alt) => {
+                contains(&fmt.value()) ||
+                    alt.as_ref().map(|alt| contains(&alt.value())) == Some(true)
+            }
+            Technique::DocComments(doc, _) => contains(doc),
+            _ => false,
+        }
+    }
+
+    /// Replaces positional placeholders like `{}` or `{:#x}` with the names
+    /// of the displayed fields, taken in the order of their declaration.
+    fn renumber_positional(&mut self, fields: &Fields) {
+        let names = displayed_fields(fields);
+        let renumber = |s: &str| -> String {
+            let fmt = match FormatString::parse(s) {
+                Ok(fmt) => fmt,
+                Err(_) => return s.to_owned(),
+            };
+            fmt.rewrite(|placeholder| {
+                let name = names.get(placeholder.position?)?;
+                Some(match placeholder.spec {
+                    Some(ref spec) => format!("{{{}:{}}}", name, spec),
+                    None => format!("{{{}}}", name),
+                })
+            })
+        };
+
+        match self {
+            Technique::WithFormat(fmt, alt) => {
+                *fmt = LitStr::new(&renumber(&fmt.value()), fmt.span());
+                if let Some(alt) = alt {
+                    *alt = LitStr::new(&renumber(&alt.value()), alt.span());
+                }
+            }
+            Technique::DocComments(doc, _) => *doc = renumber(doc),
+            _ => {}
+        }
+    }
+
     fn fix_fmt(&mut self) {
+        // Positional placeholders reference fields by their `_N` names
         fn fix(s: &str) -> String {
-            s.replace("{0", "{_0")
-                .replace("{1", "{_1")
-                .replace("{2", "{_2")
-                .replace("{3", "{_3")
-                .replace("{4", "{_4")
-                .replace("{5", "{_5")
-                .replace("{6", "{_6")
-                .replace("{7", "{_7")
-                .replace("{8", "{_8")
-                .replace("{9", "{_9")
+            let fmt = match FormatString::parse(s) {
+                Ok(fmt) => fmt,
+                Err(_) => return s.to_owned(),
+            };
+            fmt.rewrite(|placeholder| {
+                if !placeholder.arg.starts_with(|c: char| c.is_ascii_digit()) {
+                    return None;
+                }
+                Some(fmt.source()[placeholder.range.clone()].replacen('{', "{_", 1))
+            })
         }
 
         if let Technique::WithFormat(fmt, x) = self {
@@ -381,177 +1023,1032 @@ impl Technique {
                 Some(LitStr::new(&fix(&fmt.value()), Span::call_site())),
             );
         }
-        if let Technique::DocComments(fmt) = self {
-            *self = Technique::DocComments(fix(fmt))
+        if let Technique::DocComments(fmt, first_line) = self {
+            *self = Technique::DocComments(fix(fmt), *first_line)
         }
     }
 }
 
-fn has_formatters(ident: impl ToString, s: &str) -> bool {
-    let m1 = format!("{}{}:", '{', ident.to_string());
-    let m2 = format!("{}{}{}", '{', ident.to_string(), '}');
-    s.contains(&m1) || s.contains(&m2)
+/// Detects flag arguments of `display` attribute, like `warn_unused`.
+fn is_flag(nested: &NestedArg) -> Option<&'static str> {
+    if let NestedArg::Meta(Meta::Path(path)) = nested {
+        FLAGS.iter().copied().find(|flag| path.is_ident(flag))
+    } else {
+        None
+    }
 }
 
-pub(crate) fn inner(input: DeriveInput) -> Result<TokenStream2> {
-    match input.data {
-        Data::Struct(ref data) => inner_struct(&input, data),
-        Data::Enum(ref data) => inner_enum(&input, data),
-        Data::Union(ref data) => inner_union(&input, data),
+/// Detects `bound = "..."` argument of `display` attribute.
+fn is_bound(nested: &NestedArg) -> bool {
+    if let NestedArg::Meta(Meta::NameValue(MetaNameValue { path, .. })) = nested {
+        path.is_ident(BOUND)
+    } else {
+        false
     }
 }
 
-fn inner_struct(input: &DeriveInput, data: &DataStruct) -> Result<TokenStream2> {
-    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
-    let ident_name = &input.ident;
-
-    let technique = Technique::from_attrs(&input.attrs, input.span())?.ok_or_else(|| {
-        Error::new(
-            input.span(),
-            format!("Deriving `Display`: required attribute `{}` is missing.\n{}", NAME, EXAMPLE),
-        )
-    })?;
-
-    let tokens_fmt = technique.to_fmt(false);
-    let tokens_alt = technique.to_fmt(true);
-    let str_fmt = tokens_fmt.to_string();
-    let str_alt = tokens_alt.to_string();
+/// Detects `catalog = path` argument of `display` attribute.
+fn is_catalog(nested: &NestedArg) -> bool {
+    if let NestedArg::Meta(Meta::NameValue(MetaNameValue { path, .. })) = nested {
+        path.is_ident(CATALOG)
+    } else {
+        false
+    }
+}
 
-    let display = match (&data.fields, &technique) {
-        (_, Technique::FromTrait(_)) | (_, Technique::FromMethod(_)) => technique
-            .clone()
-            .into_token_stream2(&data.fields, input.span(), false),
-        (Fields::Named(fields), Technique::Inner) => {
-            if fields.named.len() != 1 {
-                return Err(attr_err!(
-                    fields.span(),
-                    "display(inner) requires only single field in the structure"
-                ));
+/// Parses path to the message catalog function given by `catalog = path`
+/// argument of `display` attribute, if any.
+fn catalog_path(attrs: &[Attribute]) -> Result<Option<Path>> {
+    let list = match attrs.iter().find(|attr| attr.path().is_ident(NAME)) {
+        Some(attr) if format_args(attr).is_none() && with_function(attr).is_none() => {
+            match attr.meta {
+                Meta::List(ref list) => match nested_args(list) {
+                    Ok(list) => list,
+                    Err(_) => return Ok(None),
+                },
+                _ => return Ok(None),
             }
-            let field = fields
-                .named
-                .first()
-                .expect("we just checked that there is a single field")
-                .ident
-                .as_ref()
-                .expect("named fields always have ident with the name");
-            quote_spanned! { field.span() =>
-                write!(f, #tokens_fmt, _0 = self.#field)
+        }
+        _ => return Ok(None),
+    };
+    for nested in &list {
+        match nested {
+            NestedArg::Meta(Meta::NameValue(MetaNameValue {
+                path,
+                value: Expr::Path(catalog),
+                ..
+            })) if path.is_ident(CATALOG) => return Ok(Some(catalog.path.clone())),
+            nested if is_catalog(nested) => {
+                return Err(attr_err!(nested.span(), "`catalog` must be a path to a function"));
             }
+            _ => {}
         }
-        (Fields::Named(fields), _) => {
-            let idents = fields
-                .named
-                .iter()
-                .filter_map(|field| format_field(field, &str_fmt).transpose())
-                .collect::<Result<Vec<_>>>()?;
-            if str_fmt == str_alt {
-                quote_spanned! { fields.span() =>
-                    write!(f, #tokens_fmt, #( #idents, )*)
+    }
+    Ok(None)
+}
+
+/// Generates lookup of the message in the catalog given by `catalog = path`
+/// argument of type-level attribute. The catalog function is called with the
+/// type name (or `Type::Variant` for enums) as a key and the names and values
+/// of the fields not marked with `#[display(skip)]`; if it returns a message,
+/// the message is displayed instead of the default representation.
+fn catalog_lookup(input: &DeriveInput) -> Result<TokenStream2> {
+    let catalog = match catalog_path(&input.attrs)? {
+        Some(catalog) => catalog,
+        None => return Ok(TokenStream2::new()),
+    };
+    // Fields are bound to `_N` names, since their own names may clash
+    let args = |fields: &Fields| {
+        let mut members = vec![];
+        let mut names = vec![];
+        let mut bindings = vec![];
+        for (index, field) in fields
+            .iter()
+            .enumerate()
+            .filter(|(_, field)| !is_skipped(field))
+        {
+            match &field.ident {
+                Some(ident) => {
+                    members.push(Member::Named(ident.clone()));
+                    names.push(ident.to_string().trim_start_matches("r#").to_owned());
                 }
-            } else {
-                let idents_alt = fields
-                    .named
-                    .iter()
-                    .filter_map(|field| format_field(field, &str_alt).transpose())
-                    .collect::<Result<Vec<_>>>()?;
-                if str_fmt != str_alt {
-                    quote_spanned! { fields.span() =>
-                        if !f.alternate() {
-                            write!(f, #tokens_fmt, #( #idents, )*)
-                        } else {
-                            write!(f, #tokens_alt, #( #idents_alt, )*)
-                        }
-                    }
-                } else {
-                    quote_spanned! { fields.span() =>
-                        write!(f, #tokens_fmt, #( #idents = self.#idents, )*)
-                    }
+                None => {
+                    members.push(Member::Unnamed(Index::from(index)));
+                    names.push(index.to_string());
                 }
             }
+            bindings.push(Ident::new(&format!("_{}", index), field.span()));
         }
-        (Fields::Unnamed(fields), _) => {
-            let f = (0..fields.unnamed.len()).map(Index::from);
-            let idents = f
-                .clone()
-                .filter(|ident| has_formatters(format!("_{}", ident.index), &str_fmt));
-            let nums = idents
-                .clone()
-                .map(|ident| Ident::new(&format!("_{}", ident.index), fields.span()))
+        (members, names, bindings)
+    };
+    let ident_name = &input.ident;
+    let message = match input.data {
+        Data::Struct(ref data) => {
+            let key = ident_name.to_string();
+            let (members, names, bindings) = args(&data.fields);
+            quote! {
+                {
+                    let Self { #( #members: #bindings, )* .. } = self;
+                    #catalog(#key, &[#( (#names, #bindings as &dyn ::core::fmt::Display), )*])
+                }
+            }
+        }
+        Data::Enum(ref data) => {
+            let arms = data.variants.iter().map(|v| {
+                let type_name = &v.ident;
+                let key = format!("{}::{}", ident_name, type_name);
+                let (members, names, bindings) = args(&v.fields);
+                quote_spanned! { v.span() =>
+                    Self::#type_name { #( #members: #bindings, )* .. } => #catalog(
+                        #key,
+                        &[#( (#names, #bindings as &dyn ::core::fmt::Display), )*],
+                    ),
+                }
+            });
+            quote! {
+                match self {
+                    #( #arms )*
+                }
+            }
+        }
+        Data::Union(_) => {
+            let key = ident_name.to_string();
+            quote! { #catalog(#key, &[]) }
+        }
+    };
+    Ok(quote! {
+        if let ::core::option::Option::Some(message) = #message {
+            return ::core::fmt::Display::fmt(&message, f);
+        }
+    })
+}
+
+/// Parses predicates given by `bound = "..."` argument of type-level `display`
+/// attribute, if any.
+fn bound_predicates(attrs: &[Attribute]) -> Result<Option<Vec<WherePredicate>>> {
+    let list = match attrs.iter().find(|attr| attr.path().is_ident(NAME)) {
+        Some(attr) if format_args(attr).is_none() && with_function(attr).is_none() => {
+            match attr.meta {
+                Meta::List(ref list) => match nested_args(list) {
+                    Ok(list) => list,
+                    Err(_) => return Ok(None),
+                },
+                _ => return Ok(None),
+            }
+        }
+        _ => return Ok(None),
+    };
+    for nested in &list {
+        match nested {
+            NestedArg::Meta(Meta::NameValue(MetaNameValue {
+                path,
+                value:
+                    Expr::Lit(ExprLit {
+                        lit: Lit::Str(bound),
+                        ..
+                    }),
+                ..
+            })) if path.is_ident(BOUND) => {
+                let predicates = bound.parse_with(
+                    syn::punctuated::Punctuated::<WherePredicate, Token![,]>::parse_terminated,
+                )?;
+                return Ok(Some(predicates.into_iter().collect()));
+            }
+            nested if is_bound(nested) => {
+                return Err(attr_err!(nested.span(), "`bound` must be a string literal"));
+            }
+            _ => {}
+        }
+    }
+    Ok(None)
+}
+
+/// Returns names and format specifications of placeholders referencing fields,
+/// skipping accessors.
+fn placeholder_specs(fmt: &str) -> Vec<(String, String)> {
+    FormatString::parse(fmt)
+        .map(|fmt| {
+            fmt.placeholders()
+                .filter(|placeholder| !placeholder.arg.contains('.'))
+                .map(|placeholder| {
+                    (placeholder.arg.clone(), placeholder.spec.clone().unwrap_or_default())
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Returns generics of the type extended with the bounds required by the field
+/// placeholders of the format strings. Only fields which types depend on the
+/// generic type parameters get bounded, with the formatting trait following
+/// the format specification of the placeholder. If the type-level attribute
+/// has `bound = "..."` argument, its predicates are used instead.
+fn bounded_generics<'a>(
+    input: &DeriveInput,
+    formats: impl IntoIterator<Item = (Option<&'a Technique>, &'a Fields)>,
+) -> Result<Generics> {
+    let mut bounds = Vec::<(Type, TypeParamBound)>::new();
+    for (technique, fields) in formats {
+        let strings = match technique {
+            Some(Technique::WithFormat(fmt, alt)) => {
+                let mut strings = vec![fmt.value()];
+                strings.extend(alt.as_ref().map(LitStr::value));
+                strings
+            }
+            Some(Technique::WithArgs(fmt, _)) => vec![fmt.value()],
+            Some(technique) => technique.format_str().into_iter().collect(),
+            None => vec![],
+        };
+        let names = placeholder_names(fields);
+        for (name, spec) in strings.iter().flat_map(|fmt| placeholder_specs(fmt)) {
+            // Single field is referenced as `_0` by `inner` technique
+            let field = fields
+                .iter()
+                .zip(&names)
+                .find(|(_, field_name)| **field_name == name)
+                .map(|(field, _)| field)
+                .or_else(|| {
+                    fields
+                        .iter()
+                        .next()
+                        .filter(|_| name == "_0" && fields.len() == 1)
+                });
+            let ty = match field {
+                Some(field) if !field.attrs.iter().any(|attr| attr.path().is_ident(NAME)) => {
+                    &field.ty
+                }
+                _ => continue,
+            };
+            let tr = FormattingTrait::from_spec(&spec)
+                .map_or_else(|| quote! { ::core::fmt::Display }, FormattingTrait::path);
+            bounds.push((ty.clone(), parse_quote! { #tr }));
+        }
+    }
+    Ok(amplify_syn::bounded_generics(&input.generics, bound_predicates(&input.attrs)?, bounds))
+}
+
+/// Detects whether type-level `display` attribute has the given flag.
+fn has_flag(attrs: &[Attribute], flag: &str) -> bool {
+    attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident(NAME))
+        .filter_map(|attr| match attr.meta {
+            Meta::List(ref list) => nested_args(list).ok(),
+            _ => None,
+        })
+        .any(|list| list.iter().any(|nested| is_flag(nested) == Some(flag)))
+}
+
+/// Detects fields marked with `#[display(skip)]`.
+fn is_skipped(field: &Field) -> bool {
+    field
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident(NAME))
+        .any(|attr| match attr.meta {
+            Meta::List(ref list) => match nested_args(list) {
+                Ok(list) if list.len() == 1 => {
+                    matches!(list.first(), Some(NestedArg::Meta(Meta::Path(path))) if path.is_ident("skip"))
+                }
+                _ => false,
+            },
+            _ => false,
+        })
+}
+
+/// Returns placeholder names of fields which may be displayed, i.e. all fields
+/// not marked with `#[display(skip)]`, in the order of their declaration.
+fn displayed_fields(fields: &Fields) -> Vec<String> {
+    fields
+        .iter()
+        .zip(placeholder_names(fields))
+        .filter(|(field, _)| !is_skipped(field))
+        .map(|(_, name)| name)
+        .collect()
+}
+
+/// Returns placeholder names of all fields.
+fn placeholder_names(fields: &Fields) -> Vec<String> {
+    fields
+        .iter()
+        .enumerate()
+        .map(|(index, field)| {
+            field
+                .ident
+                .as_ref()
+                .map_or_else(|| format!("_{}", index), Ident::to_string)
+        })
+        .collect()
+}
+
+/// Checks that placeholders in the format strings given by `display`
+/// attribute reference only displayed fields. Errors point to the unknown
+/// placeholder inside the string literal, if the compiler supports this.
+///
+/// If the attribute has `warn_unused` argument, returns code producing
+/// compiler warnings for the displayed fields which are not used by any of
+/// the format strings.
+fn check_placeholders(
+    attrs: &[Attribute],
+    fields: &Fields,
+    variant: Option<&Ident>,
+) -> Result<TokenStream2> {
+    let attr = match attrs.iter().find(|attr| attr.path().is_ident(NAME)) {
+        // Placeholders of format strings with arguments reference the arguments
+        Some(attr) if format_args(attr).is_none() => attr,
+        _ => return Ok(TokenStream2::new()),
+    };
+    let (lits, warn_unused) = match attr.meta {
+        Meta::List(ref list) => {
+            let list = match nested_args(list) {
+                Ok(list) => list,
+                Err(_) => return Ok(TokenStream2::new()),
+            };
+            let warn_unused = list
+                .iter()
+                .any(|nested| is_flag(nested) == Some(WARN_UNUSED));
+            let lits = list
+                .into_iter()
+                .filter_map(|nested| match nested {
+                    NestedArg::Lit(Lit::Str(lit)) => Some(lit),
+                    NestedArg::Meta(Meta::NameValue(MetaNameValue {
+                        path,
+                        value:
+                            Expr::Lit(ExprLit {
+                                lit: Lit::Str(lit), ..
+                            }),
+                        ..
+                    })) if path.is_ident("alt") => Some(lit),
+                    _ => None,
+                })
                 .collect::<Vec<_>>();
-            let idents = idents.collect::<Vec<_>>();
-            if str_fmt == str_alt {
-                quote_spanned! { fields.span() =>
-                    write!(f, #tokens_fmt, #( #nums = self.#idents, )*)
+            (lits, warn_unused)
+        }
+        Meta::NameValue(MetaNameValue {
+            value:
+                Expr::Lit(ExprLit {
+                    lit: Lit::Str(ref lit),
+                    ..
+                }),
+            ..
+        }) => (vec![lit.clone()], false),
+        _ => return Ok(TokenStream2::new()),
+    };
+
+    let displayed = displayed_fields(fields);
+    let known = |name: &str| {
+        let name = name.split('.').next().unwrap_or_default();
+        displayed.iter().any(|field| {
+            field == name || name.parse::<usize>().is_ok() && field.strip_prefix('_') == Some(name)
+        }) || variant.is_some() &&
+            (name == DISCRIMINANT || is_variant_placeholder(&format!("{{{}}}", name)))
+    };
+    let mut used = Vec::new();
+    for lit in &lits {
+        for placeholder in FormatString::parse_lit(lit)?.placeholders() {
+            let name = placeholder.arg.as_str();
+            let root = name.split('.').next().unwrap_or_default();
+            let error = if let Some(position) = placeholder.position {
+                if let Some(field) = displayed.get(position) {
+                    used.push(field.clone());
+                    continue;
                 }
+                "format string has more positional placeholders than there are displayed fields"
+                    .to_owned()
+            } else if known(name) {
+                used.push(match root.parse::<usize>() {
+                    Ok(index) => format!("_{}", index),
+                    Err(_) => root.to_owned(),
+                });
+                continue;
+            } else if fields.iter().enumerate().any(|(index, field)| {
+                is_skipped(field) &&
+                    field
+                        .ident
+                        .as_ref()
+                        .map_or_else(|| index.to_string(), Ident::to_string) ==
+                        name.split('.').next().unwrap_or_default()
+            }) {
+                format!("field `{}` is marked with `#[display(skip)]` and can't be displayed", name)
             } else {
-                let idents_alt =
-                    f.filter(|ident| has_formatters(format!("_{}", ident.index), &str_alt));
-                let nums_alt = idents_alt
+                format!("unknown field `{}` in the format string", name)
+            };
+            let error = match variant {
+                Some(variant) => format!("{} of `{}` variant", error, variant),
+                None => error,
+            };
+            return Err(attr_err!(placeholder.span(lit), error));
+        }
+    }
+
+    if !warn_unused {
+        return Ok(TokenStream2::new());
+    }
+    let mut warnings = Warnings::new();
+    for (field, name) in fields
+        .iter()
+        .zip(placeholder_names(fields))
+        .filter(|(field, name)| !is_skipped(field) && !used.contains(name))
+    {
+        let name = name
+            .strip_prefix('_')
+            .filter(|index| index.parse::<usize>().is_ok())
+            .unwrap_or(&name);
+        warnings.warn(field, format!("field `{}` is not used in the display format", name));
+    }
+    Ok(quote! { #warnings })
+}
+
+/// Replaces placeholders of the format string (given with braces) for which `f`
+/// returns a value, keeping escaped braces like `{{snake_case}}` intact.
+/// Strings which are not valid format strings are returned unchanged.
+fn replace_placeholders(fmt: &str, f: impl Fn(&str) -> Option<String>) -> String {
+    match FormatString::parse(fmt) {
+        Ok(parsed) => parsed.rewrite(|placeholder| f(&fmt[placeholder.range.clone()])),
+        Err(_) => fmt.to_owned(),
+    }
+}
+
+/// Checks whether the placeholder (including braces) is replaced with the name
+/// of enum variant.
+fn is_variant_placeholder(placeholder: &str) -> bool {
+    placeholder == VARIANT ||
+        placeholder == VARIANT_ALIAS ||
+        Case::ALL
+            .iter()
+            .any(|case| case.placeholder() == placeholder)
+}
+
+/// Ensures that `rename_all` is not used outside of enum-level attribute.
+fn no_rename_all(technique: Option<Technique>, span: Span) -> Result<Option<Technique>> {
+    if let Some(Technique::RenameAll(_)) = technique {
+        return Err(attr_err!(span, "`rename_all` may be used only with enums"));
+    }
+    Ok(technique)
+}
+
+/// Returns member of the structure or variant referenced in format strings
+/// either by its name or by `_`-prefixed index.
+fn field_member(fields: &Fields, name: &str) -> Option<Member> {
+    match fields {
+        Fields::Named(fields) => fields
+            .named
+            .iter()
+            .find_map(|field| field.ident.clone().filter(|ident| ident == name))
+            .map(Member::Named),
+        Fields::Unnamed(fields) => name
+            .strip_prefix('_')?
+            .parse::<usize>()
+            .ok()
+            .filter(|index| *index < fields.unnamed.len())
+            .map(|index| Member::Unnamed(Index::from(index))),
+        Fields::Unit => None,
+    }
+}
+
+/// Replaces accessors like `{outer.inner}` or `{_0.len()}` in the format
+/// string with generated argument names. Returns the new format string and
+/// the list of arguments, which are built out of the field expressions
+/// provided by `root`.
+fn expand_accessors(
+    fmt: &TokenStream2,
+    root: impl Fn(&str) -> Option<TokenStream2>,
+) -> Result<(TokenStream2, Vec<TokenStream2>)> {
+    let lit = syn::parse2::<LitStr>(fmt.clone())?;
+    let span = lit.span();
+    let fmt = FormatString::parse_lit(&lit)?;
+    let mut args = Vec::new();
+    let mut error = None;
+    let res = fmt.rewrite(|placeholder| {
+        let name = placeholder.arg.as_str();
+        let (root_name, path) = name.split_once('.')?;
+        let expr = root(root_name)
+            .ok_or_else(|| {
+                let msg = format!("unknown field `{}` in the accessor `{}`", root_name, name);
+                attr_err!(span, msg)
+            })
+            .and_then(|expr| accessor_expr(expr, path, name, span));
+        match expr {
+            Ok(expr) => {
+                let arg = Ident::new(&format!("__accessor{}", args.len()), span);
+                args.push(quote_spanned! { span => #arg = #expr });
+                Some(match placeholder.spec {
+                    Some(ref spec) => format!("{{{}:{}}}", arg, spec),
+                    None => format!("{{{}}}", arg),
+                })
+            }
+            Err(err) => {
+                error.get_or_insert(err);
+                None
+            }
+        }
+    });
+    if let Some(err) = error {
+        return Err(err);
+    }
+    let lit = LitStr::new(&res, span);
+    Ok((quote! { #lit }, args))
+}
+
+/// Builds expression accessing `path` (like `inner.len()`) of the accessor
+/// `name` on top of the field expression `expr`.
+fn accessor_expr(
+    mut expr: TokenStream2,
+    path: &str,
+    name: &str,
+    span: Span,
+) -> Result<TokenStream2> {
+    for segment in path.split('.') {
+        let (segment, call) = match segment.strip_suffix("()") {
+            Some(method) => (method, true),
+            None => (segment, false),
+        };
+        expr = match (segment.parse::<usize>(), call) {
+            (Ok(index), false) => {
+                let index = Index::from(index);
+                quote_spanned! { span => #expr.#index }
+            }
+            _ => {
+                let ident = syn::parse_str::<Ident>(segment).map_err(|_| {
+                    let msg = format!("invalid accessor `{}` in format string", name);
+                    attr_err!(span, msg)
+                })?;
+                if call {
+                    quote_spanned! { span => #expr.#ident() }
+                } else {
+                    quote_spanned! { span => #expr.#ident }
+                }
+            }
+        };
+    }
+    Ok(expr)
+}
+
+/// Returns named fields referenced in the format string which is used together
+/// with explicit arguments.
+fn format_args_fields<'a>(fields: &'a Fields, fmt: &str) -> Vec<&'a Ident> {
+    fields
+        .iter()
+        .filter_map(|field| field.ident.as_ref())
+        .filter(|ident| has_formatters(ident, fmt))
+        .collect()
+}
+
+/// Returns argument providing value for the `{discriminant}` placeholder, if
+/// it is used in the format string and is not shadowed by a variant field.
+fn discriminant_arg(
+    fmt: &TokenStream2,
+    fields: &Fields,
+    discriminant: &TokenStream2,
+) -> Option<TokenStream2> {
+    if !has_formatters(DISCRIMINANT, &fmt.to_string()) ||
+        field_member(fields, DISCRIMINANT).is_some()
+    {
+        return None;
+    }
+    let ident = Ident::new(DISCRIMINANT, Span::call_site());
+    Some(quote! { #ident = #discriminant })
+}
+
+fn has_formatters(ident: impl ToString, s: &str) -> bool {
+    let m1 = format!("{}{}:", '{', ident.to_string());
+    let m2 = format!("{}{}{}", '{', ident.to_string(), '}');
+    s.contains(&m1) || s.contains(&m2)
+}
+
+fn has_accessors(ident: impl ToString, s: &str) -> bool {
+    s.contains(&format!("{}{}.", '{', ident.to_string()))
+}
+
+pub(crate) fn inner(input: DeriveInput) -> Result<TokenStream2> {
+    let debug = inner_debug(&input)?;
+    let fmt_all = inner_fmt_all(&input)?;
+    let static_str = inner_static_str(&input)?;
+    let lookup = catalog_lookup(&input)?;
+    let display = inner_trait(&input, quote! { ::core::fmt::Display }, lookup)?;
+    Ok(quote! {
+        #display
+        #debug
+        #fmt_all
+        #static_str
+    })
+}
+
+/// Generates implementation of the formatting trait. `lookup` is inserted at
+/// the beginning of the `fmt` method and may return early.
+fn inner_trait(
+    input: &DeriveInput,
+    trait_path: TokenStream2,
+    lookup: TokenStream2,
+) -> Result<TokenStream2> {
+    match input.data {
+        Data::Struct(ref data) => inner_struct(input, data, trait_path, lookup),
+        Data::Enum(ref data) => inner_enum(input, data, trait_path, lookup),
+        Data::Union(ref data) => inner_union(input, data, trait_path, lookup),
+    }
+}
+
+/// Generates `Debug` implementation using the same formatting as `Display`
+/// if the type has `#[debug]` attribute. Format strings may be overridden with
+/// `#[debug("...")]` on the type or on enum variants.
+fn inner_debug(input: &DeriveInput) -> Result<TokenStream2> {
+    let mut input = input.clone();
+    let enabled = replace_debug_attr(&mut input.attrs);
+    if let Data::Enum(ref mut data) = input.data {
+        for v in &mut data.variants {
+            if replace_debug_attr(&mut v.attrs) && !enabled {
+                return Err(attr_err!(
+                    v.span(),
+                    "`debug` attribute on a variant requires `#[debug]` attribute on the enum"
+                ));
+            }
+        }
+    }
+    if !enabled {
+        return Ok(TokenStream2::new());
+    }
+    inner_trait(&input, quote! { ::core::fmt::Debug }, TokenStream2::new())
+}
+
+/// Generates inherent `as_str` method and `ALL_NAMES` table for fieldless
+/// enums if the type-level `display` attribute has `static_str` flag. Each
+/// variant must be displayed as a string literal.
+fn inner_static_str(input: &DeriveInput) -> Result<TokenStream2> {
+    if !has_flag(&input.attrs, STATIC_STR) {
+        return Ok(TokenStream2::new());
+    }
+    let data = match input.data {
+        Data::Enum(ref data) => data,
+        _ => return Err(attr_err!(input.span(), "`static_str` may be used only with enums")),
+    };
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let ident_name = &input.ident;
+
+    let global = Technique::from_attrs(&input.attrs, input.span())?;
+    let (global, rename_all) = match global {
+        Some(Technique::RenameAll(case)) => (None, Some(case)),
+        global => (global, None),
+    };
+
+    let mut arms = Vec::with_capacity(data.variants.len());
+    let mut names = Vec::with_capacity(data.variants.len());
+    for v in &data.variants {
+        if !v.fields.is_empty() {
+            return Err(attr_err!(
+                v.span(),
+                "`static_str` requires all enum variants to be fieldless"
+            ));
+        }
+        let type_name = &v.ident;
+        let type_str = type_name.to_string();
+        let name = match variant_technique(&global, rename_all, v)? {
+            None | Some(Technique::Inner) => {
+                rename_all.map_or_else(|| type_str.clone(), |case| case.apply(&type_str))
+            }
+            Some(technique) => match technique.format_str().as_deref().and_then(Pattern::with) {
+                Some(Pattern::Literal(name)) => name,
+                _ => {
+                    return Err(attr_err!(
+                        v.span(),
+                        "`static_str` requires each variant to be displayed as a string literal"
+                    ));
+                }
+            },
+        };
+        arms.push(quote_spanned! { v.span() => Self::#type_name => #name, });
+        names.push(name);
+    }
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics #ident_name #ty_generics #where_clause {
+            /// Names of all enum variants, in the order of their declaration,
+            /// as they are displayed.
+            pub const ALL_NAMES: &'static [&'static str] = &[#( #names ),*];
+
+            /// Returns static string with which the variant is displayed.
+            pub const fn as_str(&self) -> &'static str {
+                match self {
+                    #( #arms )*
+                }
+            }
+        }
+    })
+}
+
+/// Generates `LowerHex`, `UpperHex`, `Octal` and `Binary` implementations if
+/// the type-level `display` attribute has `fmt_all` flag. The implementations
+/// use the display format strings where placeholders without explicit
+/// formatting trait are formatted with the implemented trait.
+fn inner_fmt_all(input: &DeriveInput) -> Result<TokenStream2> {
+    if !has_flag(&input.attrs, FMT_ALL) {
+        return Ok(TokenStream2::new());
+    }
+    let mut res = TokenStream2::new();
+    for (tr, ty) in [
+        (FormattingTrait::LowerHex, 'x'),
+        (FormattingTrait::UpperHex, 'X'),
+        (FormattingTrait::Octal, 'o'),
+        (FormattingTrait::Binary, 'b'),
+    ] {
+        let mut input = input.clone();
+        apply_spec_type(&mut input.attrs, ty);
+        if let Data::Enum(ref mut data) = input.data {
+            for v in &mut data.variants {
+                apply_spec_type(&mut v.attrs, ty);
+            }
+        }
+        res.extend(inner_trait(&input, tr.path(), TokenStream2::new())?);
+    }
+    Ok(res)
+}
+
+/// Adds formatting trait type `ty` to all placeholders of format strings in
+/// `display` attributes which do not specify the type explicitly.
+fn apply_spec_type(attrs: &mut [Attribute], ty: char) {
+    fn apply(s: &str, ty: char) -> String {
+        let fmt = match FormatString::parse(s) {
+            Ok(fmt) => fmt,
+            Err(_) => return s.to_owned(),
+        };
+        fmt.rewrite(|placeholder| {
+            let full = &fmt.source()[placeholder.range.clone()];
+            match placeholder.spec {
+                // Variant name placeholders are replaced with string literals
+                _ if is_variant_placeholder(full) => None,
+                Some(ref spec) if spec.ends_with(['x', 'X', 'o', 'b', 'e', 'E', '?', 'p']) => None,
+                Some(_) => Some(format!("{}{}}}", full.strip_suffix('}')?, ty)),
+                None => Some(format!("{}:{}}}", full.strip_suffix('}')?, ty)),
+            }
+        })
+    }
+
+    fn apply_stream(stream: TokenStream2, ty: char) -> TokenStream2 {
+        stream
+            .into_iter()
+            .map(|tt| match tt {
+                TokenTree::Group(group) => {
+                    let mut res = Group::new(group.delimiter(), apply_stream(group.stream(), ty));
+                    res.set_span(group.span());
+                    TokenTree::Group(res).into()
+                }
+                tt => match syn::parse2::<LitStr>(tt.clone().into()) {
+                    Ok(lit) => {
+                        let lit = LitStr::new(&apply(&lit.value(), ty), lit.span());
+                        quote! { #lit }
+                    }
+                    Err(_) => tt.into(),
+                },
+            })
+            .collect()
+    }
+
+    for attr in attrs.iter_mut().filter(|attr| attr.path().is_ident(NAME)) {
+        let meta = &attr.meta;
+        if let Ok(meta) = syn::parse2(apply_stream(quote! { #meta }, ty)) {
+            attr.meta = meta;
+        }
+    }
+}
+
+/// Replaces `display` attribute with the `debug` one when the latter has
+/// arguments. Returns whether `debug` attribute was present.
+fn replace_debug_attr(attrs: &mut Vec<Attribute>) -> bool {
+    let mut debug = match attrs.iter().position(|attr| attr.path().is_ident(DEBUG)) {
+        Some(pos) => attrs.remove(pos),
+        None => return false,
+    };
+    if let Meta::List(MetaList { ref mut path, .. }) |
+    Meta::NameValue(MetaNameValue { ref mut path, .. }) = debug.meta
+    {
+        attrs.retain(|attr| !attr.path().is_ident(NAME));
+        *path = Ident::new(NAME, path.span()).into();
+        attrs.push(debug);
+    }
+    true
+}
+
+fn inner_struct(
+    input: &DeriveInput,
+    data: &DataStruct,
+    trait_path: TokenStream2,
+    lookup: TokenStream2,
+) -> Result<TokenStream2> {
+    let ident_name = &input.ident;
+
+    let warnings = check_placeholders(&input.attrs, &data.fields, None)?;
+    let mut technique =
+        no_rename_all(Technique::from_attrs(&input.attrs, input.span())?, input.span())?
+            .ok_or_else(|| {
+                Error::new(
+                    input.span(),
+                    format!(
+                        "Deriving `Display`: required attribute `{}` is missing.\n{}",
+                        NAME, EXAMPLE
+                    ),
+                )
+            })?;
+    technique.renumber_positional(&data.fields);
+    let generics = bounded_generics(input, [(Some(&technique), &data.fields)])?;
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let tokens_fmt = technique.to_fmt(false);
+    let tokens_alt = technique.to_fmt(true);
+    let str_fmt = tokens_fmt.to_string();
+    let str_alt = tokens_alt.to_string();
+
+    let (tokens_fmt, accessors, tokens_alt, accessors_alt) = match technique {
+        Technique::WithFormat(..) | Technique::DocComments(..) => {
+            let root =
+                |name: &str| field_member(&data.fields, name).map(|member| quote! { self.#member });
+            let (tokens_fmt, accessors) = expand_accessors(&tokens_fmt, root)?;
+            let (tokens_alt, accessors_alt) = expand_accessors(&tokens_alt, root)?;
+            (tokens_fmt, accessors, tokens_alt, accessors_alt)
+        }
+        _ => (tokens_fmt, vec![], tokens_alt, vec![]),
+    };
+
+    let display = match technique.delegated_field(&data.fields) {
+        Some(member) => technique.delegate(quote_spanned! { data.fields.span() => &self.#member }),
+        None => match (&data.fields, &technique) {
+            (_, Technique::FromTrait(_)) |
+            (_, Technique::FromMethod(_)) |
+            (_, Technique::WithFunction(_)) |
+            (_, Technique::Serialized(_)) |
+            (_, Technique::Encoded(_)) |
+            (_, Technique::WithArgs(..)) => {
+                technique
                     .clone()
-                    .map(|ident| Ident::new(&format!("_{}", ident.index), fields.span()))
-                    .collect::<Vec<_>>();
-                let idents_alt = idents_alt.collect::<Vec<_>>();
-                if str_fmt != str_alt {
+                    .into_token_stream2(&data.fields, input.span(), false)
+            }
+            (Fields::Named(fields), Technique::Inner) => {
+                if fields.named.len() != 1 {
+                    return Err(attr_err!(
+                        fields.span(),
+                        "display(inner) requires only single field in the structure"
+                    ));
+                }
+                let field = fields
+                    .named
+                    .first()
+                    .expect("we just checked that there is a single field")
+                    .ident
+                    .as_ref()
+                    .expect("named fields always have ident with the name");
+                quote_spanned! { field.span() =>
+                    write!(f, #tokens_fmt, _0 = self.#field)
+                }
+            }
+            (Fields::Named(_), _) | (Fields::Unnamed(_), _) => {
+                let fields = &data.fields;
+                let idents = struct_fields(fields, &str_fmt)?;
+                if str_fmt == str_alt {
                     quote_spanned! { fields.span() =>
-                        if !f.alternate() {
-                            write!(f, #tokens_fmt, #( #nums = self.#idents, )*)
-                        } else {
-                            write!(f, #tokens_alt, #( #nums_alt = self.#idents_alt, )*)
-                        }
+                        write!(f, #tokens_fmt, #( #idents, )* #( #accessors, )*)
                     }
                 } else {
+                    let idents_alt = struct_fields(fields, &str_alt)?;
                     quote_spanned! { fields.span() =>
-                        write!(f, #tokens_fmt, #( #nums = self.#idents, )*)
+                        if !f.alternate() {
+                            write!(f, #tokens_fmt, #( #idents, )* #( #accessors, )*)
+                        } else {
+                            write!(f, #tokens_alt, #( #idents_alt, )* #( #accessors_alt, )*)
+                        }
                     }
                 }
             }
-        }
-        (Fields::Unit, _) => {
-            if str_fmt == str_alt {
-                quote_spanned! { data.fields.span() =>
-                    f.write_str(#tokens_fmt)
-                }
-            } else {
-                quote_spanned! { data.fields.span() =>
-                    f.write_str(if !f.alternate() { #tokens_fmt } else { #tokens_alt })
+            (Fields::Unit, _) => {
+                if str_fmt == str_alt {
+                    quote_spanned! { data.fields.span() =>
+                        f.pad(#tokens_fmt)
+                    }
+                } else {
+                    quote_spanned! { data.fields.span() =>
+                        f.pad(if !f.alternate() { #tokens_fmt } else { #tokens_alt })
+                    }
                 }
             }
-        }
+        },
     };
 
     Ok(quote! {
+        #warnings
+
         #[automatically_derived]
-        impl #impl_generics ::core::fmt::Display for #ident_name #ty_generics #where_clause {
+        impl #impl_generics #trait_path for #ident_name #ty_generics #where_clause {
             fn fmt(&self, mut f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                #lookup
                 #display
             }
         }
     })
 }
 
-fn format_field(field: &syn::Field, str_fmt: &str) -> Result<Option<TokenStream2>> {
-    let ident = field.ident.as_ref().unwrap();
-    if !has_formatters(ident, str_fmt) {
+/// Produces format arguments for the structure fields used by the format
+/// string.
+fn struct_fields<'a>(
+    fields: impl IntoIterator<Item = &'a syn::Field>,
+    str_fmt: &str,
+) -> Result<Vec<TokenStream2>> {
+    fields
+        .into_iter()
+        .enumerate()
+        .filter_map(|(index, field)| {
+            let (name, member) = match &field.ident {
+                Some(ident) => (ident.clone(), Member::Named(ident.clone())),
+                None => (
+                    Ident::new(&format!("_{}", index), field.span()),
+                    Member::Unnamed(Index {
+                        index: index as u32,
+                        span: field.span(),
+                    }),
+                ),
+            };
+            format_field(field, &name, quote! { self.#member }, quote! { &self.#member }, str_fmt)
+                .transpose()
+        })
+        .collect()
+}
+
+/// Produces format arguments for the enum variant fields used by the format
+/// string, which are bound by reference in the match arm.
+fn variant_fields(fields: &Fields, str_fmt: &str) -> Result<Vec<TokenStream2>> {
+    fields
+        .iter()
+        .enumerate()
+        .filter_map(|(index, field)| {
+            let name = match &field.ident {
+                Some(ident) => ident.clone(),
+                None => Ident::new(&format!("_{}", index), field.span()),
+            };
+            format_field(field, &name, quote! { #name }, quote! { #name }, str_fmt).transpose()
+        })
+        .collect()
+}
+
+/// Produces named argument `name` for the format string out of the field,
+/// applying adapters requested by the field `#[display(...)]` attribute.
+///
+/// `value` is the expression accessing the field and `reference` is the
+/// expression providing reference to it.
+fn format_field(
+    field: &syn::Field,
+    name: &Ident,
+    value: TokenStream2,
+    reference: TokenStream2,
+    str_fmt: &str,
+) -> Result<Option<TokenStream2>> {
+    if !has_formatters(name, str_fmt) {
         return Ok(None);
     }
-    let attr = match field.attrs.iter().find(|attr| attr.path.is_ident(NAME)) {
+    let span = name.span();
+    if is_skipped(field) {
+        let msg =
+            format!("field `{}` is marked with `#[display(skip)]` and can't be displayed", name);
+        return Err(attr_err!(span, msg));
+    }
+    let attr = match field.attrs.iter().find(|attr| attr.path().is_ident(NAME)) {
         Some(attr) => attr,
-        None => return Ok(Some(quote_spanned! { ident.span() => #ident = self.#ident })),
+        None => return Ok(Some(quote_spanned! { span => #name = #value })),
     };
-    match attr.parse_meta().unwrap() {
-        Meta::List(meta_list) => {
-            if meta_list.nested.len() > 1 {
+    if let Some(path) = with_function(attr) {
+        let value = with_adapter(reference, &path, span);
+        return Ok(Some(quote_spanned! { span => #name = #value }));
+    }
+    match attr.meta {
+        Meta::List(ref meta_list) => {
+            let nested = nested_args(meta_list)?;
+            if nested.len() > 1 {
                 return Err(attr_err!(attr, NAME, "too many arguments", FIELD_EXAMPLE));
             }
-            match meta_list.nested.first() {
-                Some(NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+            match nested.first() {
+                Some(NestedArg::Meta(Meta::NameValue(MetaNameValue {
                     path,
-                    lit: Lit::Str(separator),
+                    value:
+                        Expr::Lit(ExprLit {
+                            lit: Lit::Str(separator),
+                            ..
+                        }),
                     ..
-                }))) if path.is_ident("separator") => Ok(Some(
-                    quote_spanned! { ident.span() => #ident = self.#ident.join(#separator) },
-                )),
+                }))) if path.is_ident("separator") => {
+                    Ok(Some(quote_spanned! { span => #name = #value.join(#separator) }))
+                }
+                Some(NestedArg::Meta(Meta::NameValue(MetaNameValue {
+                    path,
+                    value:
+                        Expr::Lit(ExprLit {
+                            lit: Lit::Str(separator),
+                            ..
+                        }),
+                    ..
+                }))) if path.is_ident("join") => {
+                    let joined = join_adapter(reference, separator, span);
+                    Ok(Some(quote_spanned! { span => #name = #joined }))
+                }
+                Some(NestedArg::Meta(Meta::NameValue(MetaNameValue {
+                    path,
+                    value:
+                        Expr::Lit(ExprLit {
+                            lit: Lit::Str(none),
+                            ..
+                        }),
+                    ..
+                }))) if path.is_ident("none") => {
+                    let value = option_adapter(reference, none, span);
+                    Ok(Some(quote_spanned! { span => #name = #value }))
+                }
+                Some(NestedArg::Meta(Meta::NameValue(MetaNameValue {
+                    path,
+                    value:
+                        Expr::Lit(ExprLit {
+                            lit: Lit::Int(len), ..
+                        }),
+                    ..
+                }))) if path.is_ident("truncate") => {
+                    let len = len.base10_parse::<usize>()?;
+                    let value = truncate_adapter(reference, len, span);
+                    Ok(Some(quote_spanned! { span => #name = #value }))
+                }
                 _ => Err(attr_err!(attr, NAME, "unexpected argument", FIELD_EXAMPLE)),
             }
         }
@@ -559,83 +2056,321 @@ fn format_field(field: &syn::Field, str_fmt: &str) -> Result<Option<TokenStream2
     }
 }
 
-fn inner_enum(input: &DeriveInput, data: &DataEnum) -> Result<TokenStream2> {
-    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+/// Resolves display technique for an enum variant out of its own and enum-level
+/// attributes.
+fn variant_technique(
+    global: &Option<Technique>,
+    rename_all: Option<Case>,
+    v: &Variant,
+) -> Result<Option<Technique>> {
+    let type_str = v.ident.to_string();
+    if catalog_path(&v.attrs)?.is_some() {
+        return Err(attr_err!(v.span(), "`catalog` may be used only in type-level attribute"));
+    }
+    let local = no_rename_all(Technique::from_attrs(&v.attrs, v.span())?, v.span())?;
+    let mut current = local.or_else(|| global.clone());
+
+    if let Some(t) = current.as_mut() {
+        match t {
+            Technique::DocComments(_, first_line) => {
+                *t = Technique::DocComments(String::new(), *first_line);
+                t.apply_docs(&v.attrs);
+                t.fix_fmt();
+            }
+            Technique::Case(case, _) => {
+                *t = Technique::Case(*case, String::new());
+                t.apply_case(&type_str, &v.fields);
+                t.fix_fmt();
+            }
+            _ => {}
+        }
+        t.renumber_positional(&v.fields);
+        t.apply_case_placeholders(&type_str, rename_all);
+    }
+    // Variants without doc comments fall back to the default representation
+    if let Some(Technique::DocComments(doc, _)) = &current {
+        if doc.is_empty() {
+            current = None;
+        }
+    }
+
+    Ok(current)
+}
+
+/// Returns paths of `Display` and of all formatting traits forwarded by the
+/// field adapters.
+fn adapter_traits() -> Vec<TokenStream2> {
+    let mut traits = vec![quote! { ::core::fmt::Display }];
+    traits.extend(FormattingTrait::FORWARDED.iter().map(|tr| tr.path()));
+    traits
+}
+
+/// Produces value displaying the content of `Option` when it is `Some`, or
+/// `none` string otherwise.
+fn option_adapter(option: TokenStream2, none: &LitStr, span: Span) -> TokenStream2 {
+    let traits = adapter_traits();
+    quote_spanned! { span =>
+        {
+            struct OrNone<'a, T>(&'a Option<T>);
+            #(
+                impl<'a, T: #traits> #traits for OrNone<'a, T> {
+                    fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                        match self.0 {
+                            Some(value) => #traits::fmt(value, f),
+                            None => f.pad(#none),
+                        }
+                    }
+                }
+            )*
+            OrNone(#option)
+        }
+    }
+}
+
+/// Produces value displaying `value` with a function having signature
+/// `fn(&T, &mut Formatter) -> fmt::Result`.
+fn with_adapter(value: TokenStream2, function: &Path, span: Span) -> TokenStream2 {
+    quote_spanned! { span =>
+        {
+            struct With<'a, T: ?Sized>(
+                &'a T,
+                fn(&T, &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result,
+            );
+            impl<'a, T: ?Sized> ::core::fmt::Display for With<'a, T> {
+                fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                    (self.1)(self.0, f)
+                }
+            }
+            With(#value, #function)
+        }
+    }
+}
+
+/// Produces value displaying at most `len` first characters of the value
+/// followed by `…` if the value is longer.
+fn truncate_adapter(value: TokenStream2, len: usize, span: Span) -> TokenStream2 {
+    quote_spanned! { span =>
+        {
+            struct Truncate<'a, T: ?Sized>(&'a T, usize);
+            impl<'a, T: ::core::fmt::Display + ?Sized> ::core::fmt::Display for Truncate<'a, T> {
+                fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                    struct Limited<'a, 'b> {
+                        f: &'a mut ::core::fmt::Formatter<'b>,
+                        left: usize,
+                        truncated: bool,
+                    }
+                    impl<'a, 'b> ::core::fmt::Write for Limited<'a, 'b> {
+                        fn write_str(&mut self, s: &str) -> ::core::fmt::Result {
+                            if self.truncated {
+                                return Ok(());
+                            }
+                            match s.char_indices().nth(self.left) {
+                                Some((pos, _)) => {
+                                    self.truncated = true;
+                                    self.f.write_str(&s[..pos])
+                                }
+                                None => {
+                                    self.left -= s.chars().count();
+                                    self.f.write_str(s)
+                                }
+                            }
+                        }
+                    }
+                    let mut limited = Limited { f, left: self.1, truncated: false };
+                    ::core::fmt::Write::write_fmt(&mut limited, format_args!("{}", self.0))?;
+                    if limited.truncated {
+                        limited.f.write_str("…")?;
+                    }
+                    Ok(())
+                }
+            }
+            Truncate(#value, #len)
+        }
+    }
+}
+
+/// Produces value displaying each item of the collection with its own
+/// formatting trait implementation, separated with `separator`.
+fn join_adapter(collection: TokenStream2, separator: &LitStr, span: Span) -> TokenStream2 {
+    let traits = adapter_traits();
+    quote_spanned! { span =>
+        {
+            struct Join<'a, C>(&'a C);
+            #(
+                impl<'a, C> #traits for Join<'a, C>
+                where
+                    &'a C: IntoIterator,
+                    <&'a C as IntoIterator>::Item: #traits,
+                {
+                    fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                        for (index, item) in self.0.into_iter().enumerate() {
+                            if index > 0 {
+                                f.write_str(#separator)?;
+                            }
+                            #traits::fmt(&item, f)?;
+                        }
+                        Ok(())
+                    }
+                }
+            )*
+            Join(#collection)
+        }
+    }
+}
+
+fn inner_enum(
+    input: &DeriveInput,
+    data: &DataEnum,
+    trait_path: TokenStream2,
+    lookup: TokenStream2,
+) -> Result<TokenStream2> {
     let ident_name = &input.ident;
     let mut display = TokenStream2::new();
+    let mut warnings = TokenStream2::new();
+    let mut formats = Vec::with_capacity(data.variants.len());
 
     let global = Technique::from_attrs(&input.attrs, input.span())?;
+    let (global, rename_all) = match global {
+        Some(Technique::RenameAll(case)) => (None, Some(case)),
+        global => (global, None),
+    };
     // Ancient rust versions do not known about `matches!` macro
     #[allow(clippy::match_like_matches_macro)]
     let mut use_global = match global {
-        Some(Technique::Inner) | Some(Technique::Lowercase(_)) | Some(Technique::Uppercase(_)) => {
-            false
-        }
+        Some(Technique::Inner) | Some(Technique::Case(..)) => false,
+        Some(ref technique) if technique.has_placeholders() => false,
+        None => false,
         _ => true,
     };
 
+    // Variants without explicit discriminant follow the previous one
+    let mut last_discriminant = None;
+    let mut offset = 0usize;
     for v in &data.variants {
+        if let Some((_, expr)) = &v.discriminant {
+            last_discriminant = Some(expr);
+            offset = 0;
+        }
+        let offset_lit = Literal::usize_unsuffixed(offset);
+        let discriminant = match last_discriminant {
+            Some(expr) if offset == 0 => quote! { (#expr) },
+            Some(expr) => quote! { (#expr) + #offset_lit },
+            None => quote! { #offset_lit },
+        };
+        offset += 1;
+
         let type_name = &v.ident;
         let type_str = format!("{}", type_name);
+        let renamed_str = rename_all.map_or_else(|| type_str.clone(), |case| case.apply(&type_str));
 
-        let mut local = Technique::from_attrs(&v.attrs, v.span())?;
-        let mut parent = global.clone();
-        let current = local.as_mut().or(parent.as_mut());
-        let mut current = current
-            .map(|r| {
-                r.apply_docs(&v.attrs);
-                r
-            })
-            .cloned();
-
-        if local.is_some() {
+        if v.attrs.iter().any(|attr| attr.path().is_ident(NAME)) {
+            warnings.extend(check_placeholders(&v.attrs, &v.fields, Some(&v.ident))?);
+        } else if global.is_some() {
+            warnings.extend(check_placeholders(&input.attrs, &v.fields, Some(&v.ident))?);
+        }
+        let mut current = variant_technique(&global, rename_all, v)?;
+        let own = v.attrs.iter().any(|attr| attr.path().is_ident(NAME));
+        if own {
             use_global = false;
         }
-
-        if let Some(Technique::DocComments(_)) |
-        Some(Technique::Lowercase(_)) |
-        Some(Technique::Uppercase(_)) = current
-        {
+        // Enum-level `inner` applies only to variants with a single field
+        if let (Some(Technique::Inner), true) = (&current, v.fields.len() > 1) {
+            if own {
+                return Err(attr_err!(
+                    v.span(),
+                    "display(inner) requires variant to have a single field"
+                ));
+            }
+            current = None;
+        }
+        if let Some(Technique::DocComments(..)) | Some(Technique::Case(..)) = current {
             use_global = false;
-            if let Some(t) = current.as_mut() {
-                match t {
-                    Technique::DocComments(_) => {
-                        *t = Technique::DocComments(String::new());
-                        t.apply_docs(&v.attrs);
-                        t.fix_fmt();
-                    }
-                    Technique::Lowercase(_) => {
-                        *t = Technique::Lowercase(String::new());
-                        t.apply_case(&type_str, &v.fields);
-                        t.fix_fmt();
-                    }
-                    Technique::Uppercase(_) => {
-                        *t = Technique::Uppercase(String::new());
-                        t.apply_case(&type_str, &v.fields);
-                        t.fix_fmt();
-                    }
-                    _ => unreachable!(),
+        }
+        if let Some(Technique::Encoded(_)) = current {
+            return Err(attr_err!(v.span(), ENCODING_ERR));
+        }
+        formats.push((current.clone(), &v.fields));
+
+        if let Some(Technique::Serialized(format)) = current {
+            let stream = format.into_token_stream2(v.span());
+            display.extend(quote_spanned! { v.span() =>
+                Self::#type_name { .. } => { #stream }
+            });
+            continue;
+        }
+        if let Some(Technique::FromTrait(tr)) = current {
+            // Variant-level formatting trait is applied to the single field of
+            // the variant; otherwise to the whole value
+            let path = tr.path();
+            let member = field_member(&v.fields, "_0").or_else(|| {
+                v.fields
+                    .iter()
+                    .next()
+                    .and_then(|field| field.ident.clone())
+                    .map(Member::Named)
+            });
+            match member {
+                Some(member) if own && v.fields.len() == 1 => {
+                    display.extend(quote_spanned! { v.span() =>
+                        Self::#type_name { #member: value } => #path::fmt(value, f),
+                    });
                 }
+                _ => display.extend(quote_spanned! { v.span() =>
+                    Self::#type_name { .. } => #path::fmt(self, f),
+                }),
             }
+            continue;
+        }
+        if let Some(Technique::WithFunction(path)) = &current {
+            display.extend(quote_spanned! { v.span() =>
+                Self::#type_name { .. } => #path(self, f),
+            });
+            continue;
+        }
+        if let Some(Technique::WithArgs(fmt, args)) = &current {
+            let idents = format_args_fields(&v.fields, &fmt.value());
+            display.extend(quote_spanned! { v.span() =>
+                Self::#type_name { #( #idents, )* .. } => {
+                    write!(f, #fmt, #( #args, )* #( #idents = #idents, )*)
+                }
+            });
+            continue;
+        }
+        if let Some((technique, member)) = current
+            .as_ref()
+            .and_then(|t| t.delegated_field(&v.fields).map(|member| (t, member)))
+        {
+            let delegate = technique.delegate(quote! { value });
+            display.extend(quote_spanned! { v.span() =>
+                Self::#type_name { #member: value, .. } => { #delegate }
+            });
+            continue;
         }
 
         let tokens_fmt = current.as_ref().map(|t| t.to_fmt(false));
         let tokens_alt = current.as_ref().map(|t| t.to_fmt(true));
+        // Accessors start from the variant fields bound in the match arm
+        let root = |name: &str| {
+            field_member(&v.fields, name).map(|_| {
+                let ident = Ident::new(name, v.span());
+                quote! { #ident }
+            })
+        };
 
         match (&v.fields, &tokens_fmt, &tokens_alt) {
             (Fields::Named(_), None, _) => {
                 display.extend(quote_spanned! { v.span() =>
-                    Self::#type_name { .. } => f.write_str(concat!(#type_str, " { .. }")),
+                    Self::#type_name { .. } => f.pad(concat!(#renamed_str, " { .. }")),
                 });
             }
             (Fields::Unnamed(_), None, _) => {
                 display.extend(quote_spanned! { v.span() =>
-                    Self::#type_name(..) => f.write_str(concat!(#type_str, "(..)")),
+                    Self::#type_name(..) => f.pad(concat!(#renamed_str, "(..)")),
                 });
             }
             (Fields::Unit, None, _) => {
                 display.extend(quote_spanned! { v.span() =>
-                    Self::#type_name => f.write_str(#type_str),
+                    Self::#type_name => f.pad(#renamed_str),
                 });
             }
             (Fields::Named(fields), Some(tokens_fmt), Some(tokens_alt)) => {
@@ -658,86 +2393,90 @@ fn inner_enum(input: &DeriveInput, data: &DataEnum) -> Result<TokenStream2> {
                             write!(f, #tokens_fmt, _0 = #field)
                         }
                     });
-                } else if let Some(Technique::FromTrait(tr)) = current {
-                    let stream =
-                        Technique::FromTrait(tr).into_token_stream2(&v.fields, v.span(), false);
-                    display.extend(quote_spanned! { v.span() =>
-                        Self::#type_name { .. } => {
-                            #stream
-                        }
-                    })
                 } else {
+                    let str_fmt = tokens_fmt.to_string();
+                    let str_alt = tokens_alt.to_string();
                     let f = fields.named.iter().map(|f| f.ident.as_ref().unwrap());
-                    let idents = f
+                    let idents = variant_fields(&v.fields, &str_fmt)?;
+                    let idents_alt = variant_fields(&v.fields, &str_alt)?;
+                    // Fields used by accessors must be bound as well
+                    let bound = f
                         .clone()
-                        .filter(|ident| has_formatters(ident, &tokens_fmt.to_string()))
+                        .filter(|ident| {
+                            has_formatters(ident, &str_fmt) || has_accessors(ident, &str_fmt)
+                        })
                         .collect::<Vec<_>>();
-                    let idents_alt = f
-                        .filter(|ident| has_formatters(ident, &tokens_alt.to_string()))
+                    let bound_alt = f
+                        .filter(|ident| {
+                            has_formatters(ident, &str_alt) || has_accessors(ident, &str_alt)
+                        })
                         .collect::<Vec<_>>();
-                    if tokens_fmt.to_string() != tokens_alt.to_string() {
+                    let (tokens_fmt, mut accessors) = expand_accessors(tokens_fmt, root)?;
+                    let (tokens_alt, mut accessors_alt) = expand_accessors(tokens_alt, root)?;
+                    accessors.extend(discriminant_arg(&tokens_fmt, &v.fields, &discriminant));
+                    accessors_alt.extend(discriminant_arg(&tokens_alt, &v.fields, &discriminant));
+                    if str_fmt != str_alt {
                         display.extend(quote_spanned! { v.span() =>
-                            Self::#type_name { #( #idents, )* .. } if !f.alternate() => {
-                                write!(f, #tokens_fmt, #( #idents = #idents, )*)
+                            Self::#type_name { #( #bound, )* .. } if !f.alternate() => {
+                                write!(f, #tokens_fmt, #( #idents, )* #( #accessors, )*)
                             },
-                            Self::#type_name { #( #idents, )* .. } => {
-                                write!(f, #tokens_alt, #( #idents_alt = #idents_alt, )*)
+                            Self::#type_name { #( #bound_alt, )* .. } => {
+                                write!(f, #tokens_alt, #( #idents_alt, )* #( #accessors_alt, )*)
                             },
                         });
                     } else {
                         display.extend(quote_spanned! { v.span() =>
-                            Self::#type_name { #( #idents, )* .. } => {
-                                write!(f, #tokens_fmt, #( #idents = #idents, )*)
+                            Self::#type_name { #( #bound, )* .. } => {
+                                write!(f, #tokens_fmt, #( #idents, )* #( #accessors, )*)
                             },
                         });
                     }
                 }
             }
             (Fields::Unnamed(fields), Some(tokens_fmt), Some(tokens_alt)) => {
-                if let Some(Technique::FromTrait(tr)) = current {
-                    let stream =
-                        Technique::FromTrait(tr).into_token_stream2(&v.fields, v.span(), false);
+                let f = (0..fields.unnamed.len()).map(|i| Ident::new(&format!("_{}", i), v.span()));
+                // All fields are bound since tuple patterns are positional; underscored
+                // names do not produce unused variable warnings
+                let all = f.collect::<Vec<_>>();
+                let idents = variant_fields(&v.fields, &tokens_fmt.to_string())?;
+                let idents_alt = variant_fields(&v.fields, &tokens_alt.to_string())?;
+                let alternate = tokens_fmt.to_string() != tokens_alt.to_string();
+                let (tokens_fmt, mut accessors) = expand_accessors(tokens_fmt, root)?;
+                let (tokens_alt, mut accessors_alt) = expand_accessors(tokens_alt, root)?;
+                accessors.extend(discriminant_arg(&tokens_fmt, &v.fields, &discriminant));
+                accessors_alt.extend(discriminant_arg(&tokens_alt, &v.fields, &discriminant));
+                if alternate {
                     display.extend(quote_spanned! { v.span() =>
-                        Self::#type_name(..) => {
-                            #stream
-                        }
-                    })
+                        Self::#type_name ( #( #all, )* ) if !f.alternate() => {
+                            write!(f, #tokens_fmt, #( #idents, )* #( #accessors, )*)
+                        },
+                        Self::#type_name ( #( #all, )* ) => {
+                            write!(f, #tokens_alt, #( #idents_alt, )* #( #accessors_alt, )*)
+                        },
+                    });
                 } else {
-                    let f =
-                        (0..fields.unnamed.len()).map(|i| Ident::new(&format!("_{}", i), v.span()));
-                    let idents = f
-                        .clone()
-                        .filter(|ident| has_formatters(ident, &tokens_fmt.to_string()))
-                        .collect::<Vec<_>>();
-                    let idents_alt = f
-                        .filter(|ident| has_formatters(ident, &tokens_alt.to_string()))
-                        .collect::<Vec<_>>();
-                    if tokens_fmt.to_string() != tokens_alt.to_string() {
-                        display.extend(quote_spanned! { v.span() =>
-                            Self::#type_name ( #( #idents, )* .. ) if !f.alternate() => {
-                                write!(f, #tokens_fmt, #( #idents = #idents, )*)
-                            },
-                            Self::#type_name ( #( #idents, )* .. ) => {
-                                write!(f, #tokens_alt, #( #idents_alt = #idents_alt, )*)
-                            },
-                        });
-                    } else {
-                        display.extend(quote_spanned! { v.span() =>
-                            Self::#type_name ( #( #idents, )* .. ) => {
-                                write!(f, #tokens_fmt, #( #idents = #idents, )*)
-                            },
-                        });
-                    }
+                    display.extend(quote_spanned! { v.span() =>
+                        Self::#type_name ( #( #all, )* ) => {
+                            write!(f, #tokens_fmt, #( #idents, )* #( #accessors, )*)
+                        },
+                    });
                 }
             }
             (Fields::Unit, Some(tokens_fmt), Some(tokens_alt)) => {
+                let arg = discriminant_arg(tokens_fmt, &v.fields, &discriminant);
+                let arg_alt = discriminant_arg(tokens_alt, &v.fields, &discriminant);
                 if let Some(Technique::Inner) = current {
                     display.extend(quote_spanned! { v.span() =>
-                        Self::#type_name => f.write_str(#type_str),
+                        Self::#type_name => f.pad(#renamed_str),
+                    });
+                } else if arg.is_some() || arg_alt.is_some() {
+                    display.extend(quote_spanned! { v.span() =>
+                        Self::#type_name if !f.alternate() => write!(f, #tokens_fmt, #arg),
+                        Self::#type_name => write!(f, #tokens_alt, #arg_alt),
                     });
                 } else {
                     display.extend(quote_spanned! { v.span() =>
-                        Self::#type_name => f.write_str(if !f.alternate() { #tokens_fmt } else { #tokens_alt }),
+                        Self::#type_name => f.pad(if !f.alternate() { #tokens_fmt } else { #tokens_alt }),
                     });
                 }
             }
@@ -745,6 +2484,14 @@ fn inner_enum(input: &DeriveInput, data: &DataEnum) -> Result<TokenStream2> {
         }
     }
 
+    let generics = bounded_generics(
+        input,
+        formats
+            .iter()
+            .map(|(technique, fields)| (technique.as_ref(), *fields)),
+    )?;
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
     let content = match (use_global, global) {
         (false, _) => quote! {
             match self {
@@ -773,81 +2520,386 @@ fn inner_enum(input: &DeriveInput, data: &DataEnum) -> Result<TokenStream2> {
     };
 
     Ok(quote! {
+        #warnings
+
         #[automatically_derived]
-        impl #impl_generics ::core::fmt::Display for #ident_name #ty_generics #where_clause {
+        impl #impl_generics #trait_path for #ident_name #ty_generics #where_clause {
             #![allow(clippy::if_same_then_else)]
             fn fmt(&self, mut f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                #lookup
                 #content
             }
         }
     })
 }
 
-fn inner_union(input: &DeriveInput, data: &DataUnion) -> Result<TokenStream2> {
+fn inner_union(
+    input: &DeriveInput,
+    data: &DataUnion,
+    trait_path: TokenStream2,
+    lookup: TokenStream2,
+) -> Result<TokenStream2> {
     let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
     let ident_name = &input.ident;
-    let mut display = vec![];
 
-    let global = Technique::from_attrs(&input.attrs, input.span())?;
+    if let Some(field) = data
+        .fields
+        .named
+        .iter()
+        .find(|field| field.attrs.iter().any(|attr| attr.path().is_ident(NAME)))
+    {
+        return Err(attr_err!(
+            field.span(),
+            "union fields can't have display attributes; use `with` argument for the union instead"
+        ));
+    }
 
-    for field in &data.fields.named {
-        let type_name = field
-            .ident
-            .clone()
-            .expect("named attributes are always named");
-        let type_str = format!("{}", type_name);
+    let technique =
+        no_rename_all(Technique::from_attrs(&input.attrs, input.span())?, input.span())?
+            .ok_or_else(|| {
+                Error::new(
+                    input.span(),
+                    format!(
+                        "Deriving `Display` for unions: required attribute `{}` is missing.\n{}",
+                        NAME, EXAMPLE
+                    ),
+                )
+            })?;
+    // Union fields can't be read safely, so they can't be used in formatting
+    match technique {
+        Technique::Inner | Technique::Case(..) => {
+            return Err(attr_err!(
+                input.span(),
+                "unions can be displayed only with format strings, formatting traits or functions"
+            ));
+        }
+        ref technique if technique.has_placeholders() => {
+            return Err(attr_err!(
+                input.span(),
+                "format strings for unions can't reference fields; use format arguments or `with` \
+                 function instead"
+            ));
+        }
+        _ => {}
+    }
 
-        let format = Technique::from_attrs(&field.attrs, field.span())?
-            .or_else(|| global.clone())
-            .map(|t| (t.to_fmt(false), t.to_fmt(true)));
+    let format_str = technique
+        .clone()
+        .into_token_stream2(&Fields::Unit, input.span(), false);
+    let format_alt = technique.into_token_stream2(&Fields::Unit, input.span(), true);
+    let content = if format_str.to_string() != format_alt.to_string() {
+        quote! {
+            if f.alternate() {
+                #format_alt
+            } else {
+                #format_str
+            }
+        }
+    } else {
+        quote! { #format_str }
+    };
 
-        match format {
-            None => {
-                display.push(quote_spanned! { field.span() =>
-                    Self::#type_name => #type_str,
-                });
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics #trait_path for #ident_name #ty_generics #where_clause {
+            #![allow(clippy::if_same_then_else)]
+            fn fmt(&self, mut f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                #lookup
+                #content
             }
-            Some((format_str, format_alt)) => {
-                display.push(quote_spanned! { field.span() =>
-                    Self::#type_name if !f.alternate() => #format_str,
-                    Self::#type_name => #format_alt,
-                });
+        }
+    })
+}
+
+/// Format string of a display technique split around its single field
+/// placeholder, which allows parsing the displayed value back.
+enum Pattern {
+    Literal(String),
+    Field {
+        prefix: String,
+        name: String,
+        suffix: String,
+    },
+}
+
+impl Pattern {
+    /// Parses format string which either has no placeholders or has a single
+    /// placeholder without format specification.
+    fn with(fmt: &str) -> Option<Pattern> {
+        let mut prefix = String::new();
+        let mut name = None;
+        let mut suffix = String::new();
+        for piece in FormatString::parse(fmt).ok()?.pieces() {
+            match piece {
+                FormatPiece::Text(text) if name.is_none() => prefix.push_str(text),
+                FormatPiece::Text(text) => suffix.push_str(text),
+                FormatPiece::Placeholder(placeholder)
+                    if name.is_none() &&
+                        !placeholder.arg.is_empty() &&
+                        placeholder.spec.is_none() =>
+                {
+                    name = Some(placeholder.arg.clone())
+                }
+                FormatPiece::Placeholder(_) => return None,
             }
         }
+        Some(match name {
+            None => Pattern::Literal(prefix),
+            Some(name) => Pattern::Field {
+                prefix,
+                name,
+                suffix,
+            },
+        })
     }
+}
 
-    let content = match global {
-        Some(tenchique) => {
-            let format_str =
-                tenchique
-                    .clone()
-                    .into_token_stream2(&Fields::Unit, input.span(), false);
-            let format_alt = tenchique.into_token_stream2(&Fields::Unit, input.span(), true);
-            if format_str.to_string() != format_alt.to_string() {
-                quote! {
-                    if f.alternate() {
-                        #format_alt
-                    } else {
-                        #format_str
-                    }
+impl Technique {
+    /// Returns format string used by the technique, if any.
+    fn format_str(&self) -> Option<String> {
+        match self {
+            Technique::WithFormat(fmt, _) => Some(fmt.value()),
+            Technique::DocComments(doc, _) => Some(doc.clone()),
+            Technique::Inner => Some("{_0}".to_owned()),
+            Technique::Case(_, fmt) => Some(fmt.clone()),
+            Technique::FromTrait(_) |
+            Technique::FromMethod(_) |
+            Technique::WithFunction(_) |
+            Technique::Serialized(_) |
+            Technique::Encoded(_) |
+            Technique::WithArgs(..) |
+            Technique::RenameAll(_) => None,
+        }
+    }
+}
+
+/// Generates code constructing `path` from the string `s` displayed with `fmt`
+/// format string; returns either a match arm for literal strings or a
+/// statement trying to parse the field out of the string.
+fn parse_fields(
+    path: TokenStream2,
+    fields: &Fields,
+    fmt: Option<String>,
+    span: Span,
+) -> Result<(Option<TokenStream2>, Option<TokenStream2>)> {
+    const ERR: &str = "FromStr can be derived only for display formats which are string literals \
+                       or contain a single field placeholder";
+    let pattern = fmt
+        .as_deref()
+        .and_then(Pattern::with)
+        .ok_or_else(|| Error::new(span, ERR))?;
+    Ok(match (pattern, fields) {
+        (Pattern::Literal(lit), Fields::Unit) => {
+            (Some(quote_spanned! { span => #lit => Ok(#path), }), None)
+        }
+        (
+            Pattern::Field {
+                prefix,
+                name,
+                suffix,
+            },
+            Fields::Unnamed(fields),
+        ) if fields.unnamed.len() == 1 && name == "_0" => (
+            None,
+            Some(quote_spanned! { span =>
+                if let Some(Ok(value)) = s
+                    .strip_prefix(#prefix)
+                    .and_then(|s| s.strip_suffix(#suffix))
+                    .map(::core::str::FromStr::from_str)
+                {
+                    return Ok(#path(value));
                 }
+            }),
+        ),
+        (
+            Pattern::Field {
+                prefix,
+                name,
+                suffix,
+            },
+            Fields::Named(fields),
+        ) if fields.named.len() == 1 &&
+            (name == "_0" ||
+                fields.named[0].ident.as_ref().map(|ident| ident == &name) == Some(true)) =>
+        {
+            let ident = fields.named[0].ident.as_ref();
+            (
+                None,
+                Some(quote_spanned! { span =>
+                    if let Some(Ok(value)) = s
+                        .strip_prefix(#prefix)
+                        .and_then(|s| s.strip_suffix(#suffix))
+                        .map(::core::str::FromStr::from_str)
+                    {
+                        return Ok(#path { #ident: value });
+                    }
+                }),
+            )
+        }
+        _ => return Err(Error::new(span, ERR)),
+    })
+}
+
+/// Detects single-field tuple variants
+#[allow(clippy::match_like_matches_macro)]
+fn is_newtype(fields: &Fields) -> bool {
+    match fields {
+        Fields::Unnamed(fields) if fields.unnamed.len() == 1 => true,
+        _ => false,
+    }
+}
+
+pub(crate) fn inner_from_str(input: DeriveInput) -> Result<TokenStream2> {
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let ident_name = &input.ident;
+    let ident_str = ident_name.to_string();
+
+    let mut literals = Vec::new();
+    let mut parsers = Vec::new();
+    let mut push = |(literal, parser): (Option<TokenStream2>, Option<TokenStream2>)| {
+        literals.extend(literal);
+        parsers.extend(parser);
+    };
+
+    match input.data {
+        Data::Struct(ref data) => {
+            let technique =
+                no_rename_all(Technique::from_attrs(&input.attrs, input.span())?, input.span())?;
+            if let Some(Technique::Encoded(encoding)) = technique {
+                push((None, Some(encoding.parse(&data.fields, input.span())?)));
             } else {
-                quote! { #format_str }
+                let fmt = technique.as_ref().and_then(Technique::format_str);
+                push(parse_fields(quote! { Self }, &data.fields, fmt, input.span())?);
             }
         }
-        None => quote! {
-            let s = match self {
-                #( #display )*
+        Data::Enum(ref data) => {
+            let global = Technique::from_attrs(&input.attrs, input.span())?;
+            let (global, rename_all) = match global {
+                Some(Technique::RenameAll(case)) => (None, Some(case)),
+                global => (global, None),
             };
-            f.write_str(s)
-        },
+            for v in &data.variants {
+                let type_name = &v.ident;
+                let fmt = match variant_technique(&global, rename_all, v)? {
+                    Some(technique) => technique.format_str(),
+                    // Newtype variants without explicit format are parsed with
+                    // the inner type parser
+                    None if is_newtype(&v.fields) => {
+                        push((
+                            None,
+                            Some(quote_spanned! { v.span() =>
+                                if let Ok(value) = ::core::str::FromStr::from_str(s) {
+                                    return Ok(Self::#type_name(value));
+                                }
+                            }),
+                        ));
+                        continue;
+                    }
+                    None => {
+                        let type_str = type_name.to_string();
+                        Some(rename_all.map_or(type_str.clone(), |case| case.apply(&type_str)))
+                    }
+                };
+                push(parse_fields(quote! { Self::#type_name }, &v.fields, fmt, v.span())?);
+            }
+        }
+        Data::Union(_) => {
+            return Err(Error::new_spanned(&input, "Deriving FromStr is not supported in unions"));
+        }
+    }
+
+    let vis = &input.vis;
+    // Without `std` feature the error relies on `core` and `alloc`
+    let settings = CrateSettings::with(&input.attrs)?;
+    let (alloc_crate, error_mod) = (settings.alloc_crate(), settings.error_mod());
+    let error_name = format_ident!("{}ParseError", ident_name);
+    let error_doc = format!(
+        "Error parsing [`{}`] from a string, containing the unrecognized input.",
+        ident_name
+    );
+    let error_impl = error_mod.map(|error_mod| {
+        quote! {
+            #[automatically_derived]
+            impl #error_mod::Error for #error_name {}
+        }
+    });
+
+    Ok(quote! {
+        #[doc = #error_doc]
+        #[derive(Clone, PartialEq, Eq, Hash, Debug)]
+        #vis struct #error_name(pub #alloc_crate::string::String);
+
+        #[automatically_derived]
+        impl ::core::fmt::Display for #error_name {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                write!(f, "unrecognized {} value `{}`", #ident_str, self.0)
+            }
+        }
+
+        #error_impl
+
+        #[automatically_derived]
+        impl #impl_generics ::core::str::FromStr for #ident_name #ty_generics #where_clause {
+            type Err = #error_name;
+
+            fn from_str(s: &str) -> ::core::result::Result<Self, Self::Err> {
+                match s {
+                    #( #literals )*
+                    _ => {
+                        #( #parsers )*
+                        Err(#error_name(#alloc_crate::string::String::from(s)))
+                    }
+                }
+            }
+        }
+    })
+}
+
+pub(crate) fn inner_variant_name(input: DeriveInput) -> Result<TokenStream2> {
+    let data = match input.data {
+        Data::Enum(ref data) => data,
+        _ => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "Deriving `VariantName` is supported only for enums",
+            ));
+        }
+    };
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let ident_name = &input.ident;
+
+    let global = Technique::from_attrs(&input.attrs, input.span())?;
+    let (global, rename_all) = match global {
+        Some(Technique::RenameAll(case)) => (None, Some(case)),
+        global => (global, None),
     };
+
+    let mut arms = Vec::with_capacity(data.variants.len());
+    for v in &data.variants {
+        let type_name = &v.ident;
+        let type_str = type_name.to_string();
+        // Variants displayed with dynamic data are named after their
+        // identifier, so only literal formats are used as names
+        let name = match variant_technique(&global, rename_all, v)? {
+            Some(Technique::Case(case, _)) => case.apply(&type_str),
+            Some(technique) => match technique.format_str().as_deref().and_then(Pattern::with) {
+                Some(Pattern::Literal(name)) => name,
+                _ => rename_all.map_or_else(|| type_str.clone(), |case| case.apply(&type_str)),
+            },
+            None => rename_all.map_or_else(|| type_str.clone(), |case| case.apply(&type_str)),
+        };
+        arms.push(quote_spanned! { v.span() => Self::#type_name { .. } => #name, });
+    }
+
     Ok(quote! {
         #[automatically_derived]
-        impl #impl_generics ::core::fmt::Display for #ident_name #ty_generics #where_clause {
-            #![allow(clippy::if_same_then_else)]
-            fn fmt(&self, mut f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
-                #content
+        impl #impl_generics #ident_name #ty_generics #where_clause {
+            /// Returns name of the enum variant, not including any of its
+            /// data.
+            pub const fn variant_name(&self) -> &'static str {
+                match *self {
+                    #( #arms )*
+                }
             }
         }
     })
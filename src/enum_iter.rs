@@ -0,0 +1,65 @@
+// Rust language amplification derive library providing multiple generic trait
+// implementations, type wrappers, derive macros and other language enhancements
+//
+// Written in 2019-2020 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the MIT License
+// along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+use proc_macro2::TokenStream as TokenStream2;
+use syn::{Data, DataEnum, DeriveInput, Error, Fields, Result};
+
+pub(crate) fn inner(input: DeriveInput) -> Result<TokenStream2> {
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let ident_name = &input.ident;
+
+    let variants = match input.data {
+        Data::Enum(DataEnum { ref variants, .. }) => variants,
+        _ => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "Deriving `EnumIter` is supported only for enums",
+            ));
+        }
+    };
+    if let Some(variant) = variants
+        .iter()
+        .find(|variant| !matches!(variant.fields, Fields::Unit))
+    {
+        return Err(Error::new_spanned(
+            variant,
+            "Deriving `EnumIter` is supported only for enums with fieldless variants",
+        ));
+    }
+
+    let count = variants.len();
+    let idents = variants.iter().map(|variant| &variant.ident);
+    let all_doc = format!("All variants of [`{}`] in their declaration order.", ident_name);
+    let count_doc = format!("Number of variants in [`{}`].", ident_name);
+    let iter_doc =
+        format!("Iterates over all variants of [`{}`] in their declaration order.", ident_name);
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics #ident_name #ty_generics #where_clause {
+            #[doc = #all_doc]
+            pub const ALL: [Self; #count] = [ #( Self::#idents ),* ];
+
+            #[doc = #count_doc]
+            pub const COUNT: usize = #count;
+
+            #[doc = #iter_doc]
+            #[inline]
+            pub fn iter() -> ::core::array::IntoIter<Self, #count> {
+                ::core::iter::IntoIterator::into_iter(Self::ALL)
+            }
+        }
+    })
+}
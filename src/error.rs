@@ -14,23 +14,958 @@
 // along with this software.
 // If not, see <https://opensource.org/licenses/MIT>.
 
-use proc_macro2::TokenStream as TokenStream2;
-use syn::{DeriveInput, Result};
+use std::convert::TryInto;
+
+use amplify_syn::{AttrReq, ParametrizedAttr, TypeClass, ValueClass, Warnings};
+use proc_macro2::{Literal, Span, TokenStream as TokenStream2};
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::{
+    Attribute, Data, DeriveInput, Error, Field, Fields, GenericArgument, Generics, Ident, Index,
+    LitInt, LitStr, Member, Meta, PathArguments, Result, Type, TypeParamBound, WherePredicate,
+};
+
+use crate::display::Case;
+use crate::util::CrateSettings;
+
+const NAME: &str = "error";
+const SOURCE: &str = "source";
+const FROM: &str = "from";
+const BACKTRACE: &str = "backtrace";
+const NO_SOURCE: &str = "no_source";
+
+pub(crate) fn inner(mut input: DeriveInput) -> Result<TokenStream2> {
+    let global = ErrorAttr::with(&input.attrs, true)?;
+    let mut warnings = Warnings::new();
+    // Without `std` feature generated code relies only on `core`
+    let settings =
+        CrateSettings::with(&input.attrs)?.with_derive_no_std(&input.attrs, NAME, &mut warnings);
+    let no_std = settings.no_std;
+    let error_mod = settings.error_mod();
+    // Aggregate implementations do not require bounds on the type itself,
+    // so they are generated before the generics get extended
+    let aggregate = if global.aggregate {
+        if no_std {
+            return Err(Error::new(input.span(), "Deriving `Error`: `aggregate` requires `std`"));
+        }
+        aggregate(&input)?
+    } else {
+        TokenStream2::new()
+    };
+    input.generics = bounded_generics(&input, &global, error_mod.as_ref())?;
 
-pub(crate) fn inner(input: DeriveInput) -> Result<TokenStream2> {
     let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
     let ident_name = &input.ident;
 
+    let source = field_body(
+        &input,
+        |attrs, fields| source_field(attrs, fields, !global.no_source),
+        quote! { None },
+        |value, field| source_value(value, &field.ty, field.span()),
+    )?;
+    let backtrace = field_body(&input, backtrace_field, quote! {}, |value, field| {
+        let span = field.span();
+        if is_option(&field.ty) {
+            quote_spanned! { span =>
+                if let Some(backtrace) = #value {
+                    request.provide_ref::<::std::backtrace::Backtrace>(backtrace);
+                }
+            }
+        } else {
+            quote_spanned! { span =>
+                request.provide_ref::<::std::backtrace::Backtrace>(#value);
+            }
+        }
+    })?;
+    if no_std && backtrace.is_some() {
+        return Err(Error::new(
+            input.span(),
+            "Deriving `Error`: `#[backtrace]` attribute requires `std`",
+        ));
+    }
+    let provide = backtrace.map(|backtrace| {
+        quote! {
+            fn provide<'a>(&'a self, request: &mut ::std::error::Request<'a>) {
+                #backtrace
+            }
+        }
+    });
+
+    let source = source.zip(error_mod.as_ref()).map(|(source, error_mod)| {
+        quote! {
+            fn source(&self) -> Option<&(dyn #error_mod::Error + 'static)> {
+                // Allows boxed trait objects to be used as a source along with
+                // the concrete error types
+                trait AsDynError {
+                    fn as_dyn_error(&self) -> &(dyn #error_mod::Error + 'static);
+                }
+                impl<T: #error_mod::Error + 'static> AsDynError for T {
+                    fn as_dyn_error(&self) -> &(dyn #error_mod::Error + 'static) { self }
+                }
+                impl AsDynError for dyn #error_mod::Error + 'static {
+                    fn as_dyn_error(&self) -> &(dyn #error_mod::Error + 'static) { self }
+                }
+                impl AsDynError for dyn #error_mod::Error + Send + 'static {
+                    fn as_dyn_error(&self) -> &(dyn #error_mod::Error + 'static) { self }
+                }
+                impl AsDynError for dyn #error_mod::Error + Send + Sync + 'static {
+                    fn as_dyn_error(&self) -> &(dyn #error_mod::Error + 'static) { self }
+                }
+
+                #source
+            }
+        }
+    });
+
+    let codes = error_codes(&input, &global)?;
+    if global.exit_code && no_std {
+        return Err(Error::new(input.span(), "Deriving `Error`: `exit_code` requires `std`"));
+    }
+    if global.exit_code && codes.is_none() {
+        return Err(Error::new(
+            input.span(),
+            "Deriving `Error`: `exit_code` requires error codes to be given with `code` argument",
+        ));
+    }
+    let code = codes.map(|codes| {
+        let exit_code = if global.exit_code {
+            quote! {
+                #[automatically_derived]
+                impl #impl_generics From<&#ident_name #ty_generics> for ::std::process::ExitCode
+                    #where_clause
+                {
+                    fn from(err: &#ident_name #ty_generics) -> Self {
+                        ::std::process::ExitCode::from(err.code() as u8)
+                    }
+                }
+            }
+        } else {
+            TokenStream2::new()
+        };
+        quote! {
+            #[automatically_derived]
+            impl #impl_generics #ident_name #ty_generics #where_clause {
+                /// Returns numeric code of the error.
+                pub fn code(&self) -> u32 {
+                    #codes
+                }
+            }
+
+            #exit_code
+        }
+    });
+
+    let anyhow = if global.anyhow {
+        if !cfg!(feature = "anyhow") {
+            return Err(Error::new(
+                input.span(),
+                "Deriving `Error`: `anyhow` argument requires `anyhow` feature of `amplify_derive`",
+            ));
+        }
+        quote! {
+            #[automatically_derived]
+            impl #impl_generics #ident_name #ty_generics #where_clause {
+                /// Converts the error into [`anyhow::Error`].
+                pub fn into_anyhow(self) -> ::anyhow::Error
+                where
+                    Self: Send + Sync + 'static,
+                {
+                    ::anyhow::Error::new(self)
+                }
+
+                /// Returns reference to the error if the [`anyhow::Error`]
+                /// wraps an error of this type.
+                pub fn downcast_variant(err: &::anyhow::Error) -> Option<&Self>
+                where
+                    Self: Send + Sync + 'static,
+                {
+                    err.downcast_ref::<Self>()
+                }
+            }
+        }
+    } else {
+        TokenStream2::new()
+    };
+
+    let io = io_conversions(&input, &global, no_std)?;
+    let constructors = constructors(&input, &global)?;
+    let kind = if global.kind {
+        kind_enum(&input, no_std)?
+    } else {
+        TokenStream2::new()
+    };
+    // Conversion into `String` is skipped without `std` so the code does not
+    // require `alloc`
+    let string = if no_std {
+        TokenStream2::new()
+    } else {
+        quote! {
+            #[automatically_derived]
+            impl #impl_generics From<#ident_name #ty_generics> for String #where_clause {
+                fn from(err: #ident_name #ty_generics) -> Self {
+                    err.to_string()
+                }
+            }
+        }
+    };
+
+    // Before Rust 1.81 there is no `Error` trait in `core`, so in `no_std` mode
+    // only the inherent methods and conversions are generated
+    let error_impl = error_mod.map(|error_mod| {
+        quote! {
+            #[automatically_derived]
+            impl #impl_generics #error_mod::Error for #ident_name #ty_generics #where_clause {
+                #source
+                #provide
+            }
+        }
+    });
+
+    Ok(quote! {
+        #warnings
+
+        #code
+        #io
+        #constructors
+        #kind
+        #aggregate
+        #anyhow
+
+        #error_impl
+
+        #string
+    })
+}
+
+/// Derives `Display`, `Error` and `From` at once. Unless the type has its own
+/// `#[display]` attribute, it is displayed using doc comments. Fields
+/// converted with bare `#[from]` become error sources, as with separate
+/// derives.
+pub(crate) fn inner_bundle(mut input: DeriveInput) -> Result<TokenStream2> {
+    if !input
+        .attrs
+        .iter()
+        .any(|attr| attr.path().is_ident("display"))
+    {
+        input.attrs.push(parse_quote! { #[display(doc_comments)] });
+    }
+    let display = crate::display::inner(input.clone())?;
+    let from = crate::from::inner(input.clone())?;
+    let error = inner(input)?;
+
     Ok(quote! {
+        #display
+        #error
+        #from
+    })
+}
+
+/// Parameters of `#[error(...)]` attribute given to a type or to an enum
+/// variant.
+struct ErrorAttr {
+    code: Option<LitInt>,
+    exit_code: bool,
+    io_kind: Option<Ident>,
+    io_other: bool,
+    from_io: bool,
+    no_source: bool,
+    constructors: bool,
+    kind: bool,
+    aggregate: bool,
+    anyhow: bool,
+    bound: Option<LitStr>,
+}
+
+impl ErrorAttr {
+    fn with(attrs: &[Attribute], global: bool) -> Result<Self> {
+        let mut attr = ParametrizedAttr::with(NAME, attrs)?;
+        let req = AttrReq::new()
+            .optional("code", ValueClass::int())
+            .optional("io_kind", TypeClass::Path)
+            .flag(NO_SOURCE);
+        let req = if global {
+            req.flag("exit_code")
+                .flag("from_io")
+                .flag("no_std")
+                .flag("constructors")
+                .flag("kind")
+                .flag("aggregate")
+                .flag("anyhow")
+                .optional("bound", ValueClass::str())
+        } else {
+            req.flag("io_other")
+        };
+        attr.check(req)?;
+
+        Ok(ErrorAttr {
+            code: attr
+                .args
+                .get("code")
+                .map(|a| a.clone().try_into())
+                .transpose()?,
+            exit_code: attr.args.contains_key("exit_code"),
+            io_kind: attr
+                .args
+                .get("io_kind")
+                .map(|a| a.clone().try_into())
+                .transpose()?,
+            io_other: attr.args.contains_key("io_other"),
+            from_io: attr.args.contains_key("from_io"),
+            no_source: attr.args.contains_key(NO_SOURCE),
+            constructors: attr.args.contains_key("constructors"),
+            kind: attr.args.contains_key("kind"),
+            aggregate: attr.args.contains_key("aggregate"),
+            anyhow: attr.args.contains_key("anyhow"),
+            bound: attr
+                .args
+                .get("bound")
+                .map(|a| a.clone().try_into())
+                .transpose()?,
+        })
+    }
+}
+
+/// Returns generics of the type extended with the bounds required by the
+/// generated code: the type itself must implement `Debug` and `Display`, and
+/// the types of source fields depending on the generic type parameters must
+/// implement `Error`. If the type-level attribute has `bound = "..."`
+/// argument, its predicates are used instead.
+fn bounded_generics(
+    input: &DeriveInput,
+    global: &ErrorAttr,
+    error_mod: Option<&TokenStream2>,
+) -> Result<Generics> {
+    let predicates = global
+        .bound
+        .as_ref()
+        .map(|bound| bound.parse_with(Punctuated::<WherePredicate, Token![,]>::parse_terminated))
+        .transpose()?;
+
+    let ident_name = &input.ident;
+    let (_, ty_generics, _) = input.generics.split_for_impl();
+    let this: Type = parse_quote! { #ident_name #ty_generics };
+    let mut bounds: Vec<(Type, TypeParamBound)> = vec![
+        (this.clone(), parse_quote! { ::core::fmt::Debug }),
+        (this, parse_quote! { ::core::fmt::Display }),
+    ];
+
+    let fields = match input.data {
+        Data::Struct(ref data) => vec![(&input.attrs, &data.fields)],
+        Data::Enum(ref data) => data
+            .variants
+            .iter()
+            .map(|v| (&v.attrs, &v.fields))
+            .collect(),
+        Data::Union(_) => vec![],
+    };
+    for (attrs, fields) in fields {
+        let ty = match source_field(attrs, fields, !global.no_source)? {
+            Some((_, field)) => wrapped_type(&field.ty, "Option").unwrap_or(&field.ty),
+            None => continue,
+        };
+        let Some(error_mod) = error_mod else {
+            continue;
+        };
+        bounds.push((ty.clone(), parse_quote! { #error_mod::Error }));
+        bounds.push((ty.clone(), parse_quote! { 'static }));
+    }
+    Ok(amplify_syn::bounded_generics(&input.generics, predicates, bounds))
+}
+
+/// Generates body of `code` method out of the `code` arguments of the type
+/// and enum variants. The type-level code applies to the variants without
+/// their own code; if some of the variants have no code, fails.
+fn error_codes(input: &DeriveInput, global: &ErrorAttr) -> Result<Option<TokenStream2>> {
+    let code = |lit: &LitInt| -> Result<Literal> {
+        let code = lit.base10_parse::<u32>()?;
+        if global.exit_code && code > u8::MAX as u32 {
+            return Err(Error::new(
+                lit.span(),
+                "Deriving `Error`: error codes must not exceed 255 when used as exit codes",
+            ));
+        }
+        Ok(Literal::u32_unsuffixed(code))
+    };
+
+    let data = match input.data {
+        Data::Enum(ref data) => data,
+        _ => {
+            return match global.code {
+                Some(ref lit) => {
+                    let code = code(lit)?;
+                    Ok(Some(quote! { #code }))
+                }
+                None => Ok(None),
+            };
+        }
+    };
+    let mut arms = Vec::with_capacity(data.variants.len());
+    let mut missing = None;
+    for v in &data.variants {
+        let type_name = &v.ident;
+        match ErrorAttr::with(&v.attrs, false)?
+            .code
+            .or_else(|| global.code.clone())
+        {
+            Some(lit) => {
+                let code = code(&lit)?;
+                arms.push(quote_spanned! { v.span() => Self::#type_name { .. } => #code, });
+            }
+            None => missing = missing.or(Some(v)),
+        }
+    }
+    match missing {
+        _ if arms.is_empty() => Ok(None),
+        Some(v) => Err(Error::new(
+            v.span(),
+            format!(
+                "Deriving `Error`: variant `{}` has no error code; use `#[error(code = ...)]` on \
+                 the variant or the enum",
+                v.ident
+            ),
+        )),
+        None => Ok(Some(quote! {
+            match self {
+                #( #arms )*
+            }
+        })),
+    }
+}
+
+/// Generates conversion of the error into `std::io::Error` if any of `io_kind`
+/// arguments is present, using the error kinds given by them (`Other` for the
+/// variants without the kind) and `Display` text as the message. With
+/// `from_io` argument also generates conversion from `std::io::Error` into
+/// unit variants keyed by their error kinds, falling back to the variant
+/// marked with `io_other` argument, which must have a single field.
+fn io_conversions(input: &DeriveInput, global: &ErrorAttr, no_std: bool) -> Result<TokenStream2> {
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let ident_name = &input.ident;
+    let default_kind = global
+        .io_kind
+        .clone()
+        .unwrap_or_else(|| Ident::new("Other", Span::call_site()));
+
+    let mut kinds = Vec::new();
+    let mut any_kind = global.io_kind.is_some();
+    let mut keyed = Vec::<(Ident, TokenStream2)>::new();
+    let mut other = None;
+    match input.data {
+        Data::Enum(ref data) => {
+            for v in &data.variants {
+                let attr = ErrorAttr::with(&v.attrs, false)?;
+                let type_name = &v.ident;
+                any_kind |= attr.io_kind.is_some();
+                let kind = attr.io_kind.clone().unwrap_or_else(|| default_kind.clone());
+                kinds.push(quote_spanned! { v.span() =>
+                    #ident_name::#type_name { .. } => ::std::io::ErrorKind::#kind,
+                });
+                if attr.io_other {
+                    let member = match members(&v.fields).collect::<Vec<_>>().as_slice() {
+                        [(member, _)] if other.is_none() => member.clone(),
+                        _ => {
+                            return Err(Error::new(
+                                v.span(),
+                                "Deriving `Error`: `io_other` must be given to a single variant \
+                                 with a single field",
+                            ));
+                        }
+                    };
+                    other = Some(quote! { Self::#type_name { #member: err } });
+                }
+                match attr.io_kind {
+                    Some(kind) if v.fields.is_empty() => {
+                        if keyed.iter().any(|(other, _)| *other == kind) {
+                            let msg = format!(
+                                "Deriving `Error`: repeated use of `{}` error kind for conversion \
+                                 from `std::io::Error`",
+                                kind
+                            );
+                            return Err(Error::new(kind.span(), msg));
+                        }
+                        keyed.push((kind, quote! { Self::#type_name }));
+                    }
+                    _ => {}
+                }
+            }
+        }
+        _ => kinds.push(quote! { _ => ::std::io::ErrorKind::#default_kind, }),
+    }
+
+    if no_std && (any_kind || global.from_io) {
+        return Err(Error::new(
+            input.span(),
+            "Deriving `Error`: conversions with `std::io::Error` require `std`",
+        ));
+    }
+
+    let mut stream = TokenStream2::new();
+    if any_kind {
+        stream.extend(quote! {
+            #[automatically_derived]
+            impl #impl_generics From<#ident_name #ty_generics> for ::std::io::Error #where_clause {
+                fn from(err: #ident_name #ty_generics) -> Self {
+                    let kind = match &err {
+                        #( #kinds )*
+                    };
+                    ::std::io::Error::new(kind, err.to_string())
+                }
+            }
+        });
+    }
+    if global.from_io {
+        let other = other.ok_or_else(|| {
+            Error::new(
+                input.span(),
+                "Deriving `Error`: `from_io` requires a variant marked with `io_other` argument",
+            )
+        })?;
+        let (kinds, variants): (Vec<_>, Vec<_>) = keyed.into_iter().unzip();
+        stream.extend(quote! {
+            #[automatically_derived]
+            impl #impl_generics From<::std::io::Error> for #ident_name #ty_generics #where_clause {
+                fn from(err: ::std::io::Error) -> Self {
+                    match err.kind() {
+                        #( ::std::io::ErrorKind::#kinds => #variants, )*
+                        _ => #other,
+                    }
+                }
+            }
+        });
+    }
+    Ok(stream)
+}
+
+/// Generates fieldless enum named after the error type with `Kind` suffix,
+/// having the same variants as the error, and `kind` method returning kind of
+/// the error. The kind enum is displayed and parsed as the variant names.
+fn kind_enum(input: &DeriveInput, no_std: bool) -> Result<TokenStream2> {
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let ident_name = &input.ident;
+    let vis = &input.vis;
+    let data = match input.data {
+        Data::Enum(ref data) => data,
+        _ => {
+            return Err(Error::new(
+                input.span(),
+                "Deriving `Error`: `kind` may be used only with enums",
+            ));
+        }
+    };
+
+    let kind_name = format_ident!("{}Kind", ident_name);
+    let kind_str = kind_name.to_string();
+    let variants = data.variants.iter().map(|v| &v.ident).collect::<Vec<_>>();
+    let names = variants.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+    let doc = format!("Kinds of [`{}`] variants.", ident_name);
+    let from_str = if no_std {
+        TokenStream2::new()
+    } else {
+        quote! {
+            #[automatically_derived]
+            impl ::core::str::FromStr for #kind_name {
+                type Err = ::std::string::String;
+
+                fn from_str(s: &str) -> ::core::result::Result<Self, Self::Err> {
+                    match s {
+                        #( #names => Ok(#kind_name::#variants), )*
+                        _ => Err(::std::format!("unrecognized {} value `{}`", #kind_str, s)),
+                    }
+                }
+            }
+        }
+    };
+
+    Ok(quote! {
+        #[doc = #doc]
+        #[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+        #vis enum #kind_name {
+            #( #variants, )*
+        }
+
         #[automatically_derived]
-        impl #impl_generics ::std::error::Error for #ident_name #ty_generics #where_clause {
+        impl ::core::fmt::Display for #kind_name {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                f.pad(match self {
+                    #( #kind_name::#variants => #names, )*
+                })
+            }
         }
 
+        #from_str
+
         #[automatically_derived]
-        impl #impl_generics From<#ident_name #ty_generics> for String #where_clause {
-            fn from(err: #ident_name #ty_generics) -> Self {
-                err.to_string()
+        impl #impl_generics #ident_name #ty_generics #where_clause {
+            /// Returns kind of the error.
+            pub fn kind(&self) -> #kind_name {
+                match self {
+                    #( Self::#variants { .. } => #kind_name::#variants, )*
+                }
             }
         }
     })
 }
+
+/// Generates implementations for a newtype over `Vec` of errors, collecting
+/// multiple errors: `Display` as a numbered list of the errors, `FromIterator`
+/// and `Extend` for the inner error type, `is_empty` and `into_result`
+/// methods.
+fn aggregate(input: &DeriveInput) -> Result<TokenStream2> {
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let ident_name = &input.ident;
+    let wrong = || {
+        Error::new(
+            input.span(),
+            "Deriving `Error`: `aggregate` may be used only with newtypes over `Vec` of errors",
+        )
+    };
+    let (member, field) = match input.data {
+        Data::Struct(ref data) if data.fields.len() == 1 => {
+            members(&data.fields).next().ok_or_else(wrong)?
+        }
+        _ => return Err(wrong()),
+    };
+    let inner = wrapped_type(&field.ty, "Vec").ok_or_else(wrong)?;
+
+    let mut display_generics = input.generics.clone();
+    display_generics
+        .make_where_clause()
+        .predicates
+        .push(parse_quote! { #inner: ::core::fmt::Display });
+    let (_, _, display_where_clause) = display_generics.split_for_impl();
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics ::core::fmt::Display for #ident_name #ty_generics
+            #display_where_clause
+        {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                for (no, err) in self.#member.iter().enumerate() {
+                    if no > 0 {
+                        f.write_str("\n")?;
+                    }
+                    write!(f, "{}. {}", no + 1, err)?;
+                }
+                Ok(())
+            }
+        }
+
+        #[automatically_derived]
+        impl #impl_generics ::core::iter::FromIterator<#inner> for #ident_name #ty_generics
+            #where_clause
+        {
+            fn from_iter<I: IntoIterator<Item = #inner>>(iter: I) -> Self {
+                Self { #member: iter.into_iter().collect() }
+            }
+        }
+
+        #[automatically_derived]
+        impl #impl_generics ::core::iter::Extend<#inner> for #ident_name #ty_generics
+            #where_clause
+        {
+            fn extend<I: IntoIterator<Item = #inner>>(&mut self, iter: I) {
+                self.#member.extend(iter)
+            }
+        }
+
+        #[automatically_derived]
+        impl #impl_generics #ident_name #ty_generics #where_clause {
+            /// Detects whether no errors were collected.
+            pub fn is_empty(&self) -> bool { self.#member.is_empty() }
+
+            /// Returns `Ok(())` if no errors were collected, or `self`
+            /// otherwise.
+            pub fn into_result(self) -> ::core::result::Result<(), Self> {
+                if self.is_empty() {
+                    Ok(())
+                } else {
+                    Err(self)
+                }
+            }
+        }
+    })
+}
+
+/// Checks whether the field is marked with `#[error(context)]` attribute.
+fn is_context(field: &Field) -> Result<bool> {
+    let mut attr = ParametrizedAttr::with(NAME, &field.attrs)?;
+    attr.check(AttrReq::new().flag("context"))?;
+    Ok(attr.args.contains_key("context"))
+}
+
+/// Generates inherent constructor for each of enum variants if the type-level
+/// attribute has `constructors` argument, and `context` method if some of the
+/// fields are marked with `#[error(context)]` attribute. Constructors take all
+/// the fields except backtraces, which are captured, and contexts, which are
+/// initialized with default values and may be set with `context` method.
+fn constructors(input: &DeriveInput, global: &ErrorAttr) -> Result<TokenStream2> {
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let ident_name = &input.ident;
+
+    let entities = match input.data {
+        Data::Struct(ref data) => vec![(None, &data.fields)],
+        Data::Enum(ref data) => data
+            .variants
+            .iter()
+            .map(|v| (Some(&v.ident), &v.fields))
+            .collect(),
+        Data::Union(_) => vec![],
+    };
+    match input.data {
+        Data::Enum(_) => {}
+        _ if global.constructors => {
+            return Err(Error::new(
+                input.span(),
+                "Deriving `Error`: `constructors` may be used only with enums",
+            ));
+        }
+        _ => {}
+    }
+
+    let mut methods = Vec::new();
+    let mut contexts = Vec::<(TokenStream2, Member, &Type)>::new();
+    for (variant, fields) in entities {
+        let path = variant.map_or_else(|| quote! { Self }, |v| quote! { Self::#v });
+        let backtrace = backtrace_field(&[], fields)?.map(|(member, _)| member);
+        let mut params = Vec::with_capacity(fields.len());
+        let mut inits = Vec::with_capacity(fields.len());
+        let mut context = None;
+        for (index, (member, field)) in members(fields).enumerate() {
+            let ty = &field.ty;
+            if Some(&member) == backtrace.as_ref() {
+                let capture = quote! { ::std::backtrace::Backtrace::capture() };
+                inits.push(if is_option(ty) {
+                    quote! { #member: Some(#capture) }
+                } else {
+                    quote! { #member: #capture }
+                });
+            } else if is_context(field)? {
+                if context.is_some() {
+                    return Err(Error::new(
+                        field.span(),
+                        "Deriving `Error`: only a single field may be marked with \
+                         `#[error(context)]` attribute",
+                    ));
+                }
+                inits.push(quote! { #member: Default::default() });
+                context = Some((path.clone(), member, ty));
+            } else {
+                let name = field
+                    .ident
+                    .clone()
+                    .unwrap_or_else(|| format_ident!("field{}", index));
+                params.push(quote! { #name: impl Into<#ty> });
+                inits.push(quote! { #member: #name.into() });
+            }
+        }
+        contexts.extend(context);
+
+        let variant = match variant {
+            Some(variant) if global.constructors => variant,
+            _ => continue,
+        };
+        let name = Case::Snake.apply(&variant.to_string());
+        let name = syn::parse_str::<Ident>(&name)
+            .unwrap_or_else(|_| Ident::new_raw(&name, variant.span()));
+        let doc = format!("Constructs [`{}::{}`] error.", ident_name, variant);
+        methods.push(quote_spanned! { variant.span() =>
+            #[doc = #doc]
+            pub fn #name(#( #params ),*) -> Self {
+                #path { #( #inits ),* }
+            }
+        });
+    }
+
+    if let Some((_, _, ty)) = contexts.first() {
+        if let Some((_, member, _)) = contexts
+            .iter()
+            .find(|(_, _, other)| quote! { #other }.to_string() != quote! { #ty }.to_string())
+        {
+            return Err(Error::new(
+                member.span(),
+                "Deriving `Error`: all fields marked with `#[error(context)]` attribute must have \
+                 the same type",
+            ));
+        }
+        let arms = contexts.iter().map(|(path, member, _)| {
+            quote! { #path { #member: value, .. } => *value = context.into(), }
+        });
+        methods.push(quote! {
+            /// Sets context of the error, if the error has a field for it.
+            pub fn context(mut self, context: impl Into<#ty>) -> Self {
+                #[allow(unreachable_patterns)]
+                match &mut self {
+                    #( #arms )*
+                    _ => {}
+                }
+                self
+            }
+        });
+    }
+
+    if methods.is_empty() {
+        return Ok(TokenStream2::new());
+    }
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics #ident_name #ty_generics #where_clause {
+            #( #methods )*
+        }
+    })
+}
+
+/// Generates body of a method working with a field selected by `find` out of
+/// the struct fields or out of the fields of each of enum variants. The field
+/// is provided to `make` as an expression giving reference to its value;
+/// enum variants without such field produce `fallback`.
+fn field_body(
+    input: &DeriveInput,
+    find: impl for<'a> Fn(&[Attribute], &'a Fields) -> Result<Option<(Member, &'a Field)>>,
+    fallback: TokenStream2,
+    make: impl Fn(TokenStream2, &Field) -> TokenStream2,
+) -> Result<Option<TokenStream2>> {
+    Ok(match input.data {
+        Data::Struct(ref data) => find(&input.attrs, &data.fields)?
+            .map(|(member, field)| make(quote! { &self.#member }, field)),
+        Data::Enum(ref data) => {
+            let mut arms = Vec::with_capacity(data.variants.len());
+            for v in &data.variants {
+                if let Some((member, field)) = find(&v.attrs, &v.fields)? {
+                    let type_name = &v.ident;
+                    let value = make(quote! { value }, field);
+                    arms.push(quote_spanned! { v.span() =>
+                        Self::#type_name { #member: value, .. } => { #value }
+                    });
+                }
+            }
+            if arms.is_empty() {
+                None
+            } else {
+                Some(quote! {
+                    match self {
+                        #( #arms )*
+                        _ => { #fallback }
+                    }
+                })
+            }
+        }
+        Data::Union(_) => None,
+    })
+}
+
+/// Finds the single field marked with the attribute of the given name.
+fn marked_field<'a>(fields: &'a Fields, name: &str) -> Result<Option<(Member, &'a Field)>> {
+    let mut marked = members(fields)
+        .filter(|(_, field)| field.attrs.iter().any(|attr| attr.path().is_ident(name)));
+    match (marked.next(), marked.next()) {
+        (_, Some((_, field))) => {
+            let msg = format!(
+                "Deriving `Error`: only a single field may be marked with `#[{}]` attribute",
+                name
+            );
+            Err(Error::new(field.span(), msg))
+        }
+        (marked, None) => Ok(marked),
+    }
+}
+
+/// Iterates fields along with their members.
+fn members(fields: &Fields) -> impl Iterator<Item = (Member, &Field)> {
+    fields.iter().enumerate().map(|(index, field)| {
+        let member = field
+            .ident
+            .clone()
+            .map_or_else(|| Member::Unnamed(Index::from(index)), Member::Named);
+        (member, field)
+    })
+}
+
+/// Finds the field marked with `#[backtrace]` attribute.
+fn backtrace_field<'a>(
+    _attrs: &[Attribute],
+    fields: &'a Fields,
+) -> Result<Option<(Member, &'a Field)>> {
+    let marked = marked_field(fields, BACKTRACE)?;
+    match marked {
+        Some((_, field)) if !cfg!(feature = "backtrace") => Err(Error::new(
+            field.span(),
+            "Deriving `Error`: `#[backtrace]` attribute requires `backtrace` feature of \
+             `amplify_derive`",
+        )),
+        marked => Ok(marked),
+    }
+}
+
+/// Finds the field marked with `#[source]` attribute, or, if there is no such
+/// field, the field named `source`. A single field converted with `#[from]`
+/// attribute of `From` derive, given either to the field or to the variant, is
+/// also treated as the source. Both implicit rules are skipped if `implicit` is
+/// false or the `attrs` contain `#[error(no_source)]`.
+fn source_field<'a>(
+    attrs: &[Attribute],
+    fields: &'a Fields,
+    implicit: bool,
+) -> Result<Option<(Member, &'a Field)>> {
+    if let Some(marked) = marked_field(fields, SOURCE)? {
+        return Ok(Some(marked));
+    }
+    let implicit = implicit &&
+        !ParametrizedAttr::with(NAME, attrs)?
+            .paths
+            .iter()
+            .any(|path| path.is_ident(NO_SOURCE));
+    if !implicit {
+        return Ok(None);
+    }
+    let single = fields.len() == 1;
+    Ok(members(fields).find(|(_, field)| {
+        field.ident.as_ref().map_or(false, |ident| ident == SOURCE) ||
+            single && attrs.iter().chain(&field.attrs).any(is_bare_from)
+    }))
+}
+
+/// Detects `#[from]` attribute without arguments, which makes `From` derive
+/// convert the field type itself into the error.
+fn is_bare_from(attr: &Attribute) -> bool {
+    attr.path().is_ident(FROM) && matches!(attr.meta, Meta::Path(_))
+}
+
+/// Produces expression converting reference to the source field into
+/// `Option<&dyn Error>`; optional sources are returned only when present.
+fn source_value(value: TokenStream2, ty: &Type, span: Span) -> TokenStream2 {
+    if is_option(ty) {
+        quote_spanned! { span => (#value).as_ref().map(|source| source.as_dyn_error()) }
+    } else {
+        quote_spanned! { span => Some((#value).as_dyn_error()) }
+    }
+}
+
+/// Checks whether the field type is an `Option`.
+fn is_option(ty: &Type) -> bool {
+    match ty {
+        Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .map_or(false, |segment| segment.ident == "Option"),
+        _ => false,
+    }
+}
+
+/// Returns type wrapped into the given generic wrapper type (like `Option` or
+/// `Vec`), if the type is such a wrapper.
+fn wrapped_type<'a>(ty: &'a Type, wrapper: &str) -> Option<&'a Type> {
+    let segment = match ty {
+        Type::Path(path) => path.path.segments.last()?,
+        _ => return None,
+    };
+    match &segment.arguments {
+        PathArguments::AngleBracketed(args) if segment.ident == wrapper => {
+            args.args.iter().find_map(|arg| match arg {
+                GenericArgument::Type(ty) => Some(ty),
+                _ => None,
+            })
+        }
+        _ => None,
+    }
+}
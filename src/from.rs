@@ -14,23 +14,37 @@
 // along with this software.
 // If not, see <https://opensource.org/licenses/MIT>.
 
+use amplify_syn::{Diagnostic, mentions_params};
 use proc_macro2::{Span, TokenStream as TokenStream2};
+use syn::parse::ParseStream;
 use syn::punctuated::Punctuated;
 use syn::spanned::Spanned;
 use syn::{
-    Attribute, Data, DataEnum, DataStruct, DataUnion, DeriveInput, Error, Field, Fields,
-    FieldsNamed, FieldsUnnamed, Ident, Result, Type,
+    Attribute, Data, DataEnum, DataStruct, DataUnion, DeriveInput, Error, Expr, Field, Fields,
+    FieldsNamed, FieldsUnnamed, GenericArgument, GenericParam, Ident, Index, Lifetime,
+    LifetimeParam, Member, Meta, Path, PathArguments, Result, Type, TypeParamBound,
 };
 
+use crate::util::field_value;
+
 const NAME: &str = "from";
+const TRY_NAME: &str = "try_from";
+const INTO: &str = "into";
+const GENERIC_PARAM: &str = "AmplifyFrom";
 const EXAMPLE: &str = r#"#[from(::std::fmt::Error)]"#;
 
-#[derive(Clone, PartialEq, Eq, Debug)]
+/// Fields which are not converted with their types and the expressions
+/// initializing them if given in `#[from(default)]` or
+/// `#[from(default = expr)]` attributes.
+type DefaultFields<T> = Vec<(T, Type, Option<TokenStream2>)>;
+
+#[derive(Clone)]
+#[allow(clippy::large_enum_variant)]
 enum InstructionEntity {
     Default,
     DefaultEnumFields {
         variant: Ident,
-        fields: Vec<Ident>,
+        fields: DefaultFields<Ident>,
     },
     Unit {
         variant: Option<Ident>,
@@ -38,12 +52,13 @@ enum InstructionEntity {
     Named {
         variant: Option<Ident>,
         field: Ident,
-        other: Vec<Ident>,
+        ty: Type,
+        other: DefaultFields<Ident>,
     },
     Unnamed {
         variant: Option<Ident>,
         index: usize,
-        total: usize,
+        fields: DefaultFields<usize>,
     },
 }
 
@@ -52,29 +67,30 @@ impl InstructionEntity {
         let res = match (fields.len(), variant, fields.clone(), fields.iter().next().cloned()) {
             (0, Some(v), ..) => InstructionEntity::Unit { variant: Some(v) },
             (_, variant, Fields::Unit, ..) => InstructionEntity::Unit { variant },
-            (1, variant, Fields::Named(f), Some(Field { ident: Some(i), .. })) => {
-                InstructionEntity::Named {
-                    variant,
-                    field: i.clone(),
-                    other: f
-                        .named
-                        .iter()
-                        .filter_map(|f| f.ident.clone())
-                        .filter(|ident| ident != &i)
-                        .collect(),
-                }
-            }
+            (
+                1,
+                variant,
+                Fields::Named(_),
+                Some(Field {
+                    ident: Some(i), ty, ..
+                }),
+            ) => InstructionEntity::Named {
+                variant,
+                field: i,
+                ty,
+                other: vec![],
+            },
             (1, _, Fields::Named(_), ..) => {
                 unreachable!("If we have named field, it will match previous option")
             }
             (_, Some(variant), Fields::Named(f), ..) => InstructionEntity::DefaultEnumFields {
                 variant,
-                fields: f.named.iter().filter_map(|f| f.ident.clone()).collect(),
+                fields: named_defaults(f.named.iter(), None)?,
             },
-            (len, variant, Fields::Unnamed(_), ..) => InstructionEntity::Unnamed {
+            (_, variant, Fields::Unnamed(f), ..) => InstructionEntity::Unnamed {
                 variant,
                 index: 0,
-                total: len,
+                fields: unnamed_defaults(f.unnamed.iter(), 0)?,
             },
             (_, None, ..) => InstructionEntity::Default,
         };
@@ -83,31 +99,111 @@ impl InstructionEntity {
 
     pub fn with_field(
         index: usize,
-        total: usize,
         field: &Field,
         fields: &Fields,
         variant: Option<Ident>,
-    ) -> Self {
-        if let Some(ref ident) = field.ident {
+    ) -> Result<Self> {
+        Ok(if let Some(ref ident) = field.ident {
             InstructionEntity::Named {
                 variant,
                 field: ident.clone(),
-                other: fields
-                    .iter()
-                    .filter_map(|f| f.ident.clone())
-                    .filter(|i| ident != i)
-                    .collect(),
+                ty: field.ty.clone(),
+                other: named_defaults(fields.iter(), Some(ident))?,
             }
         } else {
             InstructionEntity::Unnamed {
                 variant,
                 index,
-                total,
+                fields: unnamed_defaults(fields.iter(), index)?,
             }
+        })
+    }
+
+    pub fn variant(&self) -> Option<&Ident> {
+        match self {
+            InstructionEntity::Default => None,
+            InstructionEntity::DefaultEnumFields { variant, .. } => Some(variant),
+            InstructionEntity::Unit { variant } |
+            InstructionEntity::Named { variant, .. } |
+            InstructionEntity::Unnamed { variant, .. } => variant.as_ref(),
         }
     }
 
+    /// Returns predicates required for constructing the entity, if the types
+    /// of the fields depend on the generic type parameters: conversion of the
+    /// value of `from` type (if given) into the converted field and default
+    /// values of the rest of the fields.
+    pub fn bounds(&self, from: Option<&Type>, params: &[&Ident]) -> Vec<(Type, TypeParamBound)> {
+        let mentions = |ty: &Type| mentions_params(quote! { #ty }, params);
+        let defaults = |fields: Vec<&Type>| {
+            fields
+                .into_iter()
+                .filter(|ty| mentions(ty))
+                .map(|ty| (ty.clone(), parse_quote! { ::core::default::Default }))
+                .collect::<Vec<_>>()
+        };
+        let (converted, mut bounds) = match self {
+            InstructionEntity::Unit { .. } => return vec![],
+            InstructionEntity::Default if params.is_empty() => return vec![],
+            InstructionEntity::Default => {
+                return vec![(parse_quote! { Self }, parse_quote! { ::core::default::Default })];
+            }
+            InstructionEntity::DefaultEnumFields { fields, .. } => (
+                None,
+                defaults(
+                    fields
+                        .iter()
+                        .filter(|f| f.2.is_none())
+                        .map(|f| &f.1)
+                        .collect(),
+                ),
+            ),
+            InstructionEntity::Named {
+                variant: None,
+                ty,
+                other,
+                ..
+            } if !other.is_empty() && other.iter().all(|f| f.2.is_none()) => {
+                let bounds = if params.is_empty() {
+                    vec![]
+                } else {
+                    vec![(parse_quote! { Self }, parse_quote! { ::core::default::Default })]
+                };
+                (Some(ty), bounds)
+            }
+            InstructionEntity::Named { ty, other, .. } => (
+                Some(ty),
+                defaults(
+                    other
+                        .iter()
+                        .filter(|f| f.2.is_none())
+                        .map(|f| &f.1)
+                        .collect(),
+                ),
+            ),
+            InstructionEntity::Unnamed { index, fields, .. } => (
+                fields.get(*index).map(|f| &f.1),
+                defaults(
+                    fields
+                        .iter()
+                        .filter(|f| f.0 != *index && f.2.is_none())
+                        .map(|f| &f.1)
+                        .collect(),
+                ),
+            ),
+        };
+        if let (Some(from), Some(ty)) = (from, converted) {
+            if mentions(ty) {
+                bounds.push((from.clone(), parse_quote! { ::core::convert::Into<#ty> }));
+            }
+        }
+        bounds
+    }
+
     pub fn into_token_stream2(self) -> TokenStream2 {
+        let default = |value: Option<TokenStream2>| {
+            value.unwrap_or_else(|| quote! { ::core::default::Default::default() })
+        };
         match self {
             InstructionEntity::Default => quote! {
                 Self::default()
@@ -116,53 +212,284 @@ impl InstructionEntity {
                 let var = variant.map_or(quote! {}, |v| quote! {:: #v});
                 quote! { Self #var }
             }
+            // Without explicit defaults the rest of the fields are taken from
+            // the `Default` implementation of the structure
             InstructionEntity::Named {
                 variant: None,
                 field,
+                other,
                 ..
-            } => {
+            } if !other.is_empty() && other.iter().all(|(_, _, value)| value.is_none()) => {
                 quote! {
-                    Self { #field: v.into(), ..Default::default() }
+                    Self { #field: v.into(), ..::core::default::Default::default() }
                 }
             }
             InstructionEntity::Named {
-                variant: Some(var),
+                variant,
                 field,
                 other,
+                ..
             } => {
+                let var = variant.map(|v| quote! {:: #v});
+                let (other, values): (Vec<_>, Vec<_>) = other
+                    .into_iter()
+                    .map(|(ident, _, value)| (ident, default(value)))
+                    .unzip();
                 quote! {
-                    Self :: #var { #field: v.into(), #( #other: Default::default(), )* }
+                    Self #var { #field: v.into(), #( #other: #values, )* }
                 }
             }
             InstructionEntity::Unnamed {
                 variant,
                 index,
-                total,
+                fields,
             } => {
                 let var = variant.map_or(quote! {}, |v| quote! {:: #v});
-                let prefix = (0..index).fold(TokenStream2::new(), |mut stream, _| {
-                    stream.extend(quote! {Default::default(),});
-                    stream
-                });
-                let suffix = ((index + 1)..total).fold(TokenStream2::new(), |mut stream, _| {
-                    stream.extend(quote! {Default::default(),});
-                    stream
+                let values = fields.into_iter().map(|(no, _, value)| {
+                    if no == index {
+                        quote! { v.into() }
+                    } else {
+                        default(value)
+                    }
                 });
                 quote! {
-                    Self #var ( #prefix v.into(), #suffix )
+                    Self #var ( #( #values, )* )
                 }
             }
             InstructionEntity::DefaultEnumFields { variant, fields } => {
+                let (fields, values): (Vec<_>, Vec<_>) = fields
+                    .into_iter()
+                    .map(|(ident, _, value)| (ident, default(value)))
+                    .unzip();
                 quote! {
-                    Self #variant { #( #fields: Default::default() )* }
+                    Self :: #variant { #( #fields: #values, )* }
                 }
             }
         }
     }
 }
 
+/// Collects named fields, except the converted one, with their default values.
+fn named_defaults<'a>(
+    fields: impl Iterator<Item = &'a Field>,
+    converted: Option<&Ident>,
+) -> Result<DefaultFields<Ident>> {
+    fields
+        .filter_map(|field| field.ident.as_ref().map(|ident| (ident, field)))
+        .filter(|(ident, _)| Some(*ident) != converted)
+        .map(|(ident, field)| Ok((ident.clone(), field.ty.clone(), field_default(field)?)))
+        .collect()
+}
+
+/// Collects unnamed fields with their default values; the value of the
+/// converted field is never used.
+fn unnamed_defaults<'a>(
+    fields: impl Iterator<Item = &'a Field>,
+    converted: usize,
+) -> Result<DefaultFields<usize>> {
+    fields
+        .enumerate()
+        .map(|(no, field)| {
+            let value = if no == converted {
+                None
+            } else {
+                field_default(field)?
+            };
+            Ok((no, field.ty.clone(), value))
+        })
+        .collect()
+}
+
+/// Returns expression initializing the field which is not converted, if it is
+/// given with `#[from(default)]` or `#[from(default = expr)]` attribute.
+fn field_default(field: &Field) -> Result<Option<TokenStream2>> {
+    let mut default = None;
+    for attr in &field.attrs {
+        match FieldOption::with_attr(attr)? {
+            Some(FieldOption::Default) => {
+                default = Some(quote! { ::core::default::Default::default() })
+            }
+            Some(FieldOption::DefaultExpr(expr)) => default = Some(field_value(&expr)),
+            _ => {}
+        }
+    }
+    Ok(default)
+}
+
+/// Per-field options given as `#[from(rename = other_name)]` for conversions
+/// from another structure, and `#[from(default)]` or `#[from(default = expr)]`
+/// for fields not set by a conversion.
+#[allow(clippy::large_enum_variant)]
+enum FieldOption {
+    Rename(Member),
+    Default,
+    DefaultExpr(Expr),
+}
+
+impl FieldOption {
+    pub fn with_attr(attr: &Attribute) -> Result<Option<Self>> {
+        if !attr.path().is_ident(NAME) || matches!(attr.meta, Meta::Path(_)) {
+            return Ok(None);
+        }
+        attr.parse_args_with(|input: ParseStream| {
+            let fork = input.fork();
+            match fork.parse::<Ident>() {
+                Ok(ident) if ident == "default" && fork.is_empty() => {
+                    input.parse::<Ident>()?;
+                    Ok(Some(FieldOption::Default))
+                }
+                Ok(ident) if ident == "default" && fork.peek(Token![=]) => {
+                    input.parse::<Ident>()?;
+                    input.parse::<Token![=]>()?;
+                    Ok(Some(FieldOption::DefaultExpr(input.parse()?)))
+                }
+                Ok(ident) if ident == "rename" && fork.peek(Token![=]) => {
+                    input.parse::<Ident>()?;
+                    input.parse::<Token![=]>()?;
+                    Ok(Some(FieldOption::Rename(input.parse()?)))
+                }
+                _ => {
+                    input.parse::<TokenStream2>()?;
+                    Ok(None)
+                }
+            }
+        })
+    }
+}
+
+/// Binds elided lifetimes of the source type to the lifetime parameter of the
+/// entity, if the entity has exactly one, and collects named lifetimes which
+/// are not declared by the entity, so they can be added to the implementation.
+fn bind_lifetimes(ty: &mut Type, declared: &[Lifetime], extra: &mut Vec<Lifetime>) {
+    match ty {
+        Type::Reference(reference) => {
+            bind_lifetime(&mut reference.lifetime, declared, extra);
+            bind_lifetimes(&mut reference.elem, declared, extra);
+        }
+        Type::Path(path) => {
+            if let Some(ref mut qself) = path.qself {
+                bind_lifetimes(&mut qself.ty, declared, extra);
+            }
+            for segment in path.path.segments.iter_mut() {
+                if let PathArguments::AngleBracketed(ref mut args) = segment.arguments {
+                    for arg in args.args.iter_mut() {
+                        match arg {
+                            GenericArgument::Lifetime(lt) => {
+                                let mut lifetime = Some(lt.clone());
+                                bind_lifetime(&mut lifetime, declared, extra);
+                                if let Some(lifetime) = lifetime {
+                                    *lt = lifetime;
+                                }
+                            }
+                            GenericArgument::Type(ty) => bind_lifetimes(ty, declared, extra),
+                            GenericArgument::AssocType(binding) => {
+                                bind_lifetimes(&mut binding.ty, declared, extra)
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+            }
+        }
+        Type::Tuple(tuple) => {
+            for elem in tuple.elems.iter_mut() {
+                bind_lifetimes(elem, declared, extra);
+            }
+        }
+        Type::Slice(slice) => bind_lifetimes(&mut slice.elem, declared, extra),
+        Type::Array(array) => bind_lifetimes(&mut array.elem, declared, extra),
+        Type::Paren(paren) => bind_lifetimes(&mut paren.elem, declared, extra),
+        Type::Group(group) => bind_lifetimes(&mut group.elem, declared, extra),
+        Type::Ptr(ptr) => bind_lifetimes(&mut ptr.elem, declared, extra),
+        _ => {}
+    }
+}
+
+/// Binds or collects a single lifetime, as described in [`bind_lifetimes`].
+fn bind_lifetime(
+    lifetime: &mut Option<Lifetime>,
+    declared: &[Lifetime],
+    extra: &mut Vec<Lifetime>,
+) {
+    if let Some(lt) = lifetime.as_ref().filter(|lt| lt.ident != "_") {
+        if lt.ident != "static" && !declared.contains(lt) && !extra.contains(lt) {
+            extra.push(lt.clone());
+        }
+    } else if declared.len() == 1 {
+        *lifetime = Some(declared[0].clone());
+    }
+}
+
+/// Detects attribute consisting of a single flag, like `#[from(tuple)]`.
+fn is_flag(attr: &Attribute, flag: &str) -> bool {
+    attr.path().is_ident(NAME) &&
+        attr.parse_args::<Ident>()
+            .map_or(false, |ident| ident == flag)
+}
+
+/// Arguments modifying conversion from the source type given in `#[from]`
+/// attribute.
+enum Modifier {
+    Map(Path),
+    Fields,
+    Boxed,
+}
+
+/// Splits `Box`, `Arc` or `Rc` type into the pointer path without generic
+/// arguments and the pointed type.
+fn smart_pointer(ty: &Type) -> Option<(Path, &Type)> {
+    let path = match ty {
+        Type::Path(path) if path.qself.is_none() => &path.path,
+        _ => return None,
+    };
+    let segment = path.segments.last()?;
+    if segment.ident != "Box" && segment.ident != "Arc" && segment.ident != "Rc" {
+        return None;
+    }
+    let inner = match &segment.arguments {
+        PathArguments::AngleBracketed(args) => args.args.iter().find_map(|arg| match arg {
+            GenericArgument::Type(ty) => Some(ty),
+            _ => None,
+        })?,
+        _ => return None,
+    };
+    let mut pointer = path.clone();
+    if let Some(segment) = pointer.segments.last_mut() {
+        segment.arguments = PathArguments::None;
+    }
+    Some((pointer, inner))
+}
+
+/// The way the source value is converted into the field value.
+#[derive(Clone)]
+#[allow(clippy::large_enum_variant)]
+enum Conversion {
+    /// Infallible conversion with [`Into`].
+    Into,
+    /// Infallible conversion with the given function, which result is
+    /// converted with [`Into`].
+    Map(Path),
+    /// Infallible conversion from a reference by cloning the value and
+    /// converting it with the conversion from the `owned` type.
+    Ref { owned: Type },
+    /// Infallible conversion from any type which can be converted into the
+    /// field type, implemented with a generic parameter.
+    Generic { target: Type },
+    /// Infallible conversion putting the source value into the smart pointer
+    /// (`Box`, `Arc` or `Rc`) of the field type.
+    Boxed { target: Type, pointer: Path },
+    /// Infallible conversion moving parts of the source value into the fields;
+    /// keeps the complete constructing expression.
+    Construct(TokenStream2),
+    /// Fallible conversion with `TryInto` into the field type; the errors are
+    /// converted into `error` type, if it is given.
+    TryInto { target: Type, error: Option<Type> },
+}
+
+/// Conversion from the source type into the entity, with optional `#[cfg]`
+/// attribute of the generated implementation.
 #[derive(Clone)]
-struct InstructionEntry(pub Type, pub InstructionEntity);
+struct InstructionEntry(pub Type, pub InstructionEntity, pub Conversion, pub TokenStream2);
 
 impl PartialEq for InstructionEntry {
     // Ugly way, but with current `syn` version no other way is possible
@@ -171,26 +498,105 @@ impl PartialEq for InstructionEntry {
         let r = &other.0;
         let a = quote! { #l };
         let b = quote! { #r };
-        format!("{}", a) == format!("{}", b)
+        format!("{}", a) == format!("{}", b) && self.3.to_string() == other.3.to_string()
     }
 }
 
 impl InstructionEntry {
+    pub fn with(ty: Type, entity: &InstructionEntity, conversion: Conversion) -> Self {
+        Self(ty, entity.clone(), conversion, TokenStream2::new())
+    }
+
     pub fn with_type(ty: &Type, entity: &InstructionEntity) -> Self {
-        Self(ty.clone(), entity.clone())
+        Self::with(ty.clone(), entity, Conversion::Into)
+    }
+
+    pub fn with_ref(ty: &Type, entity: &InstructionEntity) -> Self {
+        Self::with(parse_quote! { &#ty }, entity, Conversion::Ref { owned: ty.clone() })
+    }
+
+    pub fn with_cfg(mut self, cfg: &Option<TokenStream2>) -> Self {
+        if let Some(cfg) = cfg {
+            self.3 = quote! { #[cfg(#cfg)] };
+        }
+        self
     }
 
     pub fn parse(
         fields: &Fields,
         attrs: &[Attribute],
         entity: InstructionEntity,
+        name: &str,
+        entity_level: bool,
     ) -> Result<Vec<InstructionEntry>> {
+        if name == TRY_NAME {
+            return Self::parse_try(fields, attrs, entity);
+        }
         let mut list = Vec::<InstructionEntry>::new();
-        for attr in attrs.iter().filter(|attr| attr.path.is_ident(NAME)) {
-            // #[from]
-            if attr.tokens.is_empty() {
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident(NAME)) {
+            // #[from(infallible)]
+            if is_flag(attr, "infallible") {
+                list.push(Self::with(
+                    parse_quote! { ::core::convert::Infallible },
+                    &entity,
+                    Conversion::Construct(quote! { match v {} }),
+                ));
+                continue;
+            }
+            // #[from(tuple)]
+            if is_flag(attr, "tuple") {
+                if !entity_level {
+                    return Err(attr_err!(
+                        attr,
+                        "`tuple` argument must be given to a structure or enum variant"
+                    ));
+                }
+                list.push(Self::tuple_conversion(fields, &entity));
+                continue;
+            }
+            // #[from(generic)]
+            if is_flag(attr, "generic") {
+                let target = match (fields.len(), fields.iter().next()) {
+                    (1, Some(field)) => field.ty.clone(),
+                    _ => {
+                        return Err(attr_err!(
+                            attr,
+                            "`generic` argument is allowed only for entities with a single field; \
+                             for multi-field entities specify the attribute right ahead of the \
+                             target field"
+                        ));
+                    }
+                };
+                let param = Ident::new(GENERIC_PARAM, Span::call_site());
+                list.push(InstructionEntry::with(
+                    parse_quote! { #param },
+                    &entity,
+                    Conversion::Generic { target },
+                ));
+                continue;
+            }
+            // #[from(boxed)]
+            if is_flag(attr, "boxed") {
+                let (from, conversion) = Self::boxed_conversion(attr, fields)?;
+                list.push(InstructionEntry::with(from, &entity, conversion));
+                continue;
+            }
+            if FieldOption::with_attr(attr)?.is_some() {
+                if entity_level {
+                    return Err(attr_err!(attr, "field options must be given to fields"));
+                }
+                continue;
+            }
+            // #[from] or #[from(ref)]
+            let ref_only = attr.parse_args::<Token![ref]>().is_ok();
+            if matches!(attr.meta, Meta::Path(_)) || ref_only {
                 match (fields.len(), fields.iter().next()) {
-                    (1, Some(field)) => list.push(InstructionEntry::with_type(&field.ty, &entity)),
+                    (1, Some(field)) => {
+                        list.push(InstructionEntry::with_type(&field.ty, &entity));
+                        if ref_only {
+                            list.push(InstructionEntry::with_ref(&field.ty, &entity));
+                        }
+                    }
                     _ => {
                         return Err(attr_err!(
                             attr,
@@ -201,11 +607,174 @@ impl InstructionEntry {
                     }
                 }
             } else {
-                list.push(InstructionEntry::with_type(&attr.parse_args()?, &entity));
+                // #[from(SourceTy)], #[from(SourceTy, map = path::to::fn)],
+                // #[from(SourceTy, fields)] or #[from(SourceTy, boxed)], each
+                // optionally with `ref` and `cfg(...)` arguments
+                let (from, modifier, by_ref, cfg) =
+                    attr.parse_args_with(|input: ParseStream| {
+                        let from = input.parse::<Type>()?;
+                        let mut modifier = None;
+                        let mut by_ref = false;
+                        let mut cfg = None;
+                        while !input.is_empty() {
+                            input.parse::<Token![,]>()?;
+                            if input.peek(Token![ref]) && !by_ref {
+                                input.parse::<Token![ref]>()?;
+                                by_ref = true;
+                                continue;
+                            }
+                            let arg = input.parse::<Ident>()?;
+                            if arg == "cfg" && cfg.is_none() {
+                                let content;
+                                parenthesized!(content in input);
+                                cfg = Some(content.parse::<TokenStream2>()?);
+                                continue;
+                            }
+                            modifier = Some(match arg.to_string().as_str() {
+                                "map" if modifier.is_none() => {
+                                    input.parse::<Token![=]>()?;
+                                    Modifier::Map(input.parse::<Path>()?)
+                                }
+                                "fields" if modifier.is_none() => Modifier::Fields,
+                                "boxed" if modifier.is_none() => Modifier::Boxed,
+                                _ => {
+                                    return Err(Error::new(
+                                        arg.span(),
+                                        format!(
+                                            "Attribute `#[{}]`: unknown, repeated or conflicting \
+                                             argument `{}`",
+                                            NAME, arg
+                                        ),
+                                    ));
+                                }
+                            });
+                        }
+                        Ok((from, modifier, by_ref, cfg))
+                    })?;
+                let conversion = match modifier {
+                    None => Conversion::Into,
+                    Some(Modifier::Map(map)) => Conversion::Map(map),
+                    Some(Modifier::Fields) if entity_level => {
+                        Conversion::Construct(Self::fields_conversion(fields, entity.variant())?)
+                    }
+                    Some(Modifier::Fields) => {
+                        return Err(attr_err!(
+                            attr,
+                            "`fields` argument must be given to a structure or enum variant"
+                        ));
+                    }
+                    Some(Modifier::Boxed) => Self::boxed_conversion(attr, fields)?.1,
+                };
+                if by_ref {
+                    list.push(InstructionEntry::with_ref(&from, &entity).with_cfg(&cfg));
+                }
+                list.push(InstructionEntry::with(from, &entity, conversion).with_cfg(&cfg));
             }
         }
         Ok(list)
     }
+
+    // Puts the value into the smart pointer type of the single field; returns
+    // the type pointed by the field as the default source type
+    fn boxed_conversion(attr: &Attribute, fields: &Fields) -> Result<(Type, Conversion)> {
+        let target = match (fields.len(), fields.iter().next()) {
+            (1, Some(field)) => &field.ty,
+            _ => {
+                return Err(attr_err!(
+                    attr,
+                    "`boxed` argument is allowed only for entities with a single field; for \
+                     multi-field entities specify the attribute right ahead of the target field"
+                ));
+            }
+        };
+        let (pointer, inner) = smart_pointer(target).ok_or_else(|| {
+            attr_err!(attr, "`boxed` argument requires field of `Box`, `Arc` or `Rc` type")
+        })?;
+        Ok((inner.clone(), Conversion::Boxed {
+            target: target.clone(),
+            pointer,
+        }))
+    }
+
+    // Constructs the entity from the tuple of all its field values
+    fn tuple_conversion(fields: &Fields, entity: &InstructionEntity) -> InstructionEntry {
+        let var = entity.variant().map(|v| quote! {:: #v});
+        let types = fields.iter().map(|field| &field.ty);
+        let values = fields.iter().enumerate().map(|(index, field)| {
+            let member = field
+                .ident
+                .clone()
+                .map_or_else(|| Member::Unnamed(Index::from(index)), Member::Named);
+            let index = Index::from(index);
+            quote! { #member: v.#index }
+        });
+        let from = parse_quote! { ( #( #types, )* ) };
+        let construct = quote! { Self #var { #( #values, )* } };
+        InstructionEntry::with(from, entity, Conversion::Construct(construct))
+    }
+
+    // Constructs the entity from the same-named fields of the source value `v`
+    fn fields_conversion(fields: &Fields, variant: Option<&Ident>) -> Result<TokenStream2> {
+        let var = variant.map_or(quote! {}, |v| quote! {:: #v});
+        let mut values = vec![];
+        for (index, field) in fields.iter().enumerate() {
+            let member = field
+                .ident
+                .clone()
+                .map_or_else(|| Member::Unnamed(Index::from(index)), Member::Named);
+            let mut source = member.clone();
+            for attr in &field.attrs {
+                if let Some(FieldOption::Rename(other)) = FieldOption::with_attr(attr)? {
+                    source = other;
+                }
+            }
+            values.push(match field_default(field)? {
+                Some(value) => quote! { #member: #value },
+                None => quote! { #member: v.#source.into() },
+            });
+        }
+        Ok(quote! { Self #var { #( #values, )* } })
+    }
+
+    // #[try_from(SourceTy)] or #[try_from(SourceTy, error = ErrorTy)]
+    fn parse_try(
+        fields: &Fields,
+        attrs: &[Attribute],
+        entity: InstructionEntity,
+    ) -> Result<Vec<InstructionEntry>> {
+        let mut list = Vec::<InstructionEntry>::new();
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident(TRY_NAME)) {
+            let target = match (fields.len(), fields.iter().next()) {
+                (1, Some(field)) => field.ty.clone(),
+                _ => {
+                    return Err(attr_err!(
+                        attr,
+                        "attribute is allowed only for entities with a single field; for \
+                         multi-field entities specify the attribute right ahead of the target \
+                         field"
+                    ));
+                }
+            };
+            let (from, error) = attr.parse_args_with(|input: ParseStream| {
+                let from = input.parse::<Type>()?;
+                if input.is_empty() {
+                    return Ok((from, None));
+                }
+                input.parse::<Token![,]>()?;
+                let arg = input.parse::<Ident>()?;
+                if arg != "error" {
+                    return Err(Error::new(
+                        arg.span(),
+                        format!("Attribute `#[{}]`: unknown argument `{}`", TRY_NAME, arg),
+                    ));
+                }
+                input.parse::<Token![=]>()?;
+                Ok((from, Some(input.parse::<Type>()?)))
+            })?;
+            list.push(InstructionEntry::with(from, &entity, Conversion::TryInto { target, error }));
+        }
+        Ok(list)
+    }
 }
 
 #[derive(Default)]
@@ -219,9 +788,10 @@ impl InstructionTable {
         fields: &Fields,
         attrs: &[Attribute],
         variant: Option<Ident>,
+        name: &str,
     ) -> Result<&Self> {
         let entity = InstructionEntity::with_fields(fields, variant.clone())?;
-        self.extend(InstructionEntry::parse(fields, attrs, entity.clone())?)?;
+        self.extend(InstructionEntry::parse(fields, attrs, entity.clone(), name, true)?)?;
         for (index, field) in fields.iter().enumerate() {
             let mut punctuated = Punctuated::new();
             punctuated.push_value(field.clone());
@@ -239,10 +809,12 @@ impl InstructionTable {
                     },
                 ),
                 &field.attrs,
-                InstructionEntity::with_field(index, fields.len(), field, fields, variant.clone()),
+                InstructionEntity::with_field(index, field, fields, variant.clone())?,
+                name,
+                false,
             )?)?;
         }
-        if variant.is_none() && fields.len() == 1 && self.0.is_empty() {
+        if name == NAME && variant.is_none() && fields.len() == 1 && self.0.is_empty() {
             let field = fields
                 .into_iter()
                 .next()
@@ -258,57 +830,209 @@ impl InstructionTable {
     where T: IntoIterator<Item = InstructionEntry> {
         let mut count = 0;
         for entry in list {
-            self.0.iter().find(|e| *e == &entry).map_or(Ok(()), |_| {
-                Err(Error::new(
-                    Span::call_site(),
-                    format!("Attribute `#[{}]`: repeated use of type `{}`", NAME, quote! {ty}),
-                ))
-            })?;
+            #[allow(clippy::match_like_matches_macro)]
+            let generic = |e: &InstructionEntry| match e.2 {
+                Conversion::Generic { .. } => true,
+                _ => false,
+            };
+            if !self.0.is_empty() && (generic(&entry) || self.0.iter().any(generic)) {
+                return Err(Error::new(
+                    entry.0.span(),
+                    format!(
+                        "Attribute `#[{}]`: `generic` conversion conflicts with all other \
+                         conversions",
+                        NAME
+                    ),
+                ));
+            }
+            if let Some(prev) = self.0.iter().find(|e| *e == &entry) {
+                return Err(Self::conflict_err(prev, &entry));
+            }
             self.0.push(entry);
             count += 1;
         }
         Ok(count)
     }
 
+    // Reports the repeated source type both at the conflicting and at the
+    // previous use
+    fn conflict_err(prev: &InstructionEntry, entry: &InstructionEntry) -> Error {
+        let ty = &entry.0;
+        let diag = Diagnostic::spanned(
+            ty,
+            format!("Attribute `#[{}]`: repeated use of source type `{}`", NAME, quote! { #ty }),
+        )
+        .note_spanned(&prev.0, "the type is first used here");
+        match (prev.1.variant(), entry.1.variant()) {
+            (Some(prev), Some(variant)) if prev != variant => diag.help(format!(
+                "keep the conversion into `{}` variant and remove this one from `{}` variant",
+                prev, variant
+            )),
+            _ => diag.help("keep the first conversion and remove this one"),
+        }
+        .into_syn_error()
+    }
+
     pub fn into_token_stream2(self, input: &DeriveInput) -> TokenStream2 {
-        let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+        let (_, ty_generics, _) = input.generics.split_for_impl();
         let ident_name = &input.ident;
+        let params = input
+            .generics
+            .type_params()
+            .map(|param| &param.ident)
+            .collect::<Vec<_>>();
+        let declared = input
+            .generics
+            .lifetimes()
+            .map(|def| def.lifetime.clone())
+            .collect::<Vec<_>>();
 
-        self.0.into_iter().fold(TokenStream2::new(), |mut stream, InstructionEntry(from, entity)| {
-            let convert = entity.into_token_stream2();
-            stream.extend(quote! {
-                #[automatically_derived]
-                impl #impl_generics ::core::convert::From<#from> for #ident_name #ty_generics #where_clause {
-                    fn from(v: #from) -> Self {
-                        #convert
+        self.0.into_iter().fold(
+            TokenStream2::new(),
+            |mut stream, InstructionEntry(mut from, entity, mut conversion, cfg)| {
+                let mut extra = vec![];
+                bind_lifetimes(&mut from, &declared, &mut extra);
+                if let Conversion::Ref { ref mut owned } = conversion {
+                    bind_lifetimes(owned, &declared, &mut extra);
+                }
+                let mut generics = input.generics.clone();
+                for lifetime in extra.into_iter().rev() {
+                    generics
+                        .params
+                        .insert(0, GenericParam::Lifetime(LifetimeParam::new(lifetime)));
+                }
+                let mut bounds = match conversion {
+                    Conversion::Into => entity.bounds(Some(&from), &params),
+                    _ => entity.bounds(None, &params),
+                };
+                match conversion {
+                    Conversion::Ref { ref owned } if !params.is_empty() => {
+                        bounds.push((parse_quote! { Self }, parse_quote! { ::core::convert::From<#owned> }))
+                    }
+                    Conversion::TryInto { ref target, .. }
+                        if mentions_params(quote! { #from #target }, &params) =>
+                    {
+                        bounds.push((from.clone(), parse_quote! { ::core::convert::TryInto<#target> }))
                     }
+                    _ => {}
                 }
-            });
-            stream
-        })
+                amplify_syn::add_bounds(&mut generics, bounds);
+                let (impl_generics, _, where_clause) = generics.split_for_impl();
+
+                let convert = entity.into_token_stream2();
+                stream.extend(cfg);
+                stream.extend(match conversion {
+                    Conversion::Into => quote! {
+                        #[automatically_derived]
+                        impl #impl_generics ::core::convert::From<#from> for #ident_name #ty_generics #where_clause {
+                            fn from(v: #from) -> Self {
+                                #convert
+                            }
+                        }
+                    },
+                    Conversion::Map(map) => quote! {
+                        #[automatically_derived]
+                        impl #impl_generics ::core::convert::From<#from> for #ident_name #ty_generics #where_clause {
+                            fn from(v: #from) -> Self {
+                                let v = #map(v);
+                                #convert
+                            }
+                        }
+                    },
+                    Conversion::Ref { owned } => quote! {
+                        #[automatically_derived]
+                        impl #impl_generics ::core::convert::From<#from> for #ident_name #ty_generics #where_clause {
+                            fn from(v: #from) -> Self {
+                                <Self as ::core::convert::From<#owned>>::from(::core::clone::Clone::clone(v))
+                            }
+                        }
+                    },
+                    Conversion::Generic { target } => {
+                        let mut generics = generics.clone();
+                        generics.params.push(parse_quote! {
+                            #from: ::core::convert::Into<#target>
+                        });
+                        let (impl_generics, _, where_clause) = generics.split_for_impl();
+                        quote! {
+                            #[automatically_derived]
+                            impl #impl_generics ::core::convert::From<#from> for #ident_name #ty_generics #where_clause {
+                                fn from(v: #from) -> Self {
+                                    #convert
+                                }
+                            }
+                        }
+                    }
+                    Conversion::Boxed { target, pointer } => quote! {
+                        #[automatically_derived]
+                        impl #impl_generics ::core::convert::From<#from> for #ident_name #ty_generics #where_clause {
+                            fn from(v: #from) -> Self {
+                                let v: #target = #pointer::new(v);
+                                #convert
+                            }
+                        }
+                    },
+                    Conversion::Construct(construct) => quote! {
+                        #[automatically_derived]
+                        impl #impl_generics ::core::convert::From<#from> for #ident_name #ty_generics #where_clause {
+                            fn from(v: #from) -> Self {
+                                #construct
+                            }
+                        }
+                    },
+                    Conversion::TryInto { target, error } => {
+                        let error = error.map_or_else(
+                            || quote! { <#from as ::core::convert::TryInto<#target>>::Error },
+                            |error| quote! { #error },
+                        );
+                        quote! {
+                            #[automatically_derived]
+                            impl #impl_generics ::core::convert::TryFrom<#from> for #ident_name #ty_generics #where_clause {
+                                type Error = #error;
+
+                                fn try_from(v: #from) -> ::core::result::Result<Self, Self::Error> {
+                                    let v: #target = ::core::convert::TryInto::try_into(v)?;
+                                    Ok(#convert)
+                                }
+                            }
+                        }
+                    }
+                });
+                stream
+            },
+        )
     }
 }
 
 pub(crate) fn inner(input: DeriveInput) -> Result<TokenStream2> {
+    let into = inner_into(&input)?;
+    let mut stream = inner_with(input, NAME)?;
+    stream.extend(into);
+    Ok(stream)
+}
+
+pub(crate) fn inner_try(input: DeriveInput) -> Result<TokenStream2> { inner_with(input, TRY_NAME) }
+
+fn inner_with(input: DeriveInput, name: &str) -> Result<TokenStream2> {
     match input.data {
-        Data::Struct(ref data) => inner_struct(&input, data),
-        Data::Enum(ref data) => inner_enum(&input, data),
-        Data::Union(ref data) => inner_union(&input, data),
+        Data::Struct(ref data) => inner_struct(&input, data, name),
+        Data::Enum(ref data) => inner_enum(&input, data, name),
+        Data::Union(ref data) => inner_union(&input, data, name),
     }
 }
 
-fn inner_struct(input: &DeriveInput, data: &DataStruct) -> Result<TokenStream2> {
+fn inner_struct(input: &DeriveInput, data: &DataStruct, name: &str) -> Result<TokenStream2> {
     let mut instructions = InstructionTable::new();
-    instructions.parse(&data.fields, &input.attrs, None)?;
+    instructions.parse(&data.fields, &input.attrs, None, name)?;
     Ok(instructions.into_token_stream2(input))
 }
 
-fn inner_enum(input: &DeriveInput, data: &DataEnum) -> Result<TokenStream2> {
-    // Do not let top-level `from` on enums
+fn inner_enum(input: &DeriveInput, data: &DataEnum, name: &str) -> Result<TokenStream2> {
+    // Do not let top-level `from` on enums, except conversion from
+    // `Infallible`, which does not depend on variants
     input
         .attrs
         .iter()
-        .find(|attr| attr.path.is_ident(NAME))
+        .find(|attr| attr.path().is_ident(name) && !is_flag(attr, "infallible"))
         .map_or(Ok(()), |a| {
             Err(attr_err!(
                 a,
@@ -318,13 +1042,121 @@ fn inner_enum(input: &DeriveInput, data: &DataEnum) -> Result<TokenStream2> {
 
     let mut instructions = InstructionTable::new();
     for v in &data.variants {
-        instructions.parse(&v.fields, &v.attrs, Some(v.ident.clone()))?;
+        instructions.parse(&v.fields, &v.attrs, Some(v.ident.clone()), name)?;
     }
+    instructions.extend(InstructionEntry::parse(
+        &Fields::Unit,
+        &input.attrs,
+        InstructionEntity::Default,
+        name,
+        true,
+    )?)?;
     Ok(instructions.into_token_stream2(input))
 }
 
-fn inner_union(input: &DeriveInput, data: &DataUnion) -> Result<TokenStream2> {
+fn inner_union(input: &DeriveInput, data: &DataUnion, name: &str) -> Result<TokenStream2> {
     let mut instructions = InstructionTable::new();
-    instructions.parse(&Fields::Named(data.fields.clone()), &input.attrs, None)?;
+    instructions.parse(&Fields::Named(data.fields.clone()), &input.attrs, None, name)?;
     Ok(instructions.into_token_stream2(input))
 }
+
+/// Generates conversions of the type back into the types of its fields marked
+/// with `#[into]` attribute (or into the type given as the attribute
+/// argument). Since an enum may be converted into the field type only when it
+/// has the variant containing the field, for enums `TryFrom` is implemented,
+/// returning the original value as an error.
+fn inner_into(input: &DeriveInput) -> Result<TokenStream2> {
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let ident_name = &input.ident;
+
+    let entities = match input.data {
+        Data::Struct(ref data) => vec![(None, &input.attrs, &data.fields)],
+        Data::Enum(ref data) => {
+            // Do not let top-level `into` on enums
+            input
+                .attrs
+                .iter()
+                .find(|attr| attr.path().is_ident(INTO))
+                .map_or(Ok(()), |a| {
+                    Err(attr_err!(
+                        a,
+                        "top-level attribute is not allowed, use it for specific fields or \
+                         variants"
+                    ))
+                })?;
+            data.variants
+                .iter()
+                .map(|v| (Some(&v.ident), &v.attrs, &v.fields))
+                .collect()
+        }
+        Data::Union(_) => {
+            return match input.attrs.iter().find(|attr| attr.path().is_ident(INTO)) {
+                Some(attr) => Err(attr_err!(attr, "attribute is not supported for unions")),
+                None => Ok(TokenStream2::new()),
+            };
+        }
+    };
+
+    let mut stream = TokenStream2::new();
+    for (variant, attrs, fields) in entities {
+        let mut marks = vec![];
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident(INTO)) {
+            match (fields.len(), fields.iter().next()) {
+                (1, Some(field)) => marks.push((0, field, attr)),
+                _ => {
+                    return Err(attr_err!(
+                        attr,
+                        "attribute is allowed only for entities with a single field; for \
+                         multi-field entities specify the attribute right ahead of the target \
+                         field"
+                    ));
+                }
+            }
+        }
+        for (index, field) in fields.iter().enumerate() {
+            for attr in field.attrs.iter().filter(|attr| attr.path().is_ident(INTO)) {
+                marks.push((index, field, attr));
+            }
+        }
+
+        for (index, field, attr) in marks {
+            let member = field
+                .ident
+                .clone()
+                .map_or_else(|| Member::Unnamed(Index::from(index)), Member::Named);
+            let (target, value) = if matches!(attr.meta, Meta::Path(_)) {
+                let ty = &field.ty;
+                (quote! { #ty }, quote! { value })
+            } else {
+                let ty = attr.parse_args::<Type>()?;
+                (quote! { #ty }, quote! { value.into() })
+            };
+            stream.extend(match variant {
+                None => quote! {
+                    #[automatically_derived]
+                    impl #impl_generics ::core::convert::From<#ident_name #ty_generics> for #target #where_clause {
+                        fn from(v: #ident_name #ty_generics) -> Self {
+                            let value = v.#member;
+                            #value
+                        }
+                    }
+                },
+                Some(variant) => quote! {
+                    #[automatically_derived]
+                    impl #impl_generics ::core::convert::TryFrom<#ident_name #ty_generics> for #target #where_clause {
+                        type Error = #ident_name #ty_generics;
+
+                        #[allow(unreachable_patterns)]
+                        fn try_from(v: #ident_name #ty_generics) -> ::core::result::Result<Self, Self::Error> {
+                            match v {
+                                #ident_name::#variant { #member: value, .. } => Ok(#value),
+                                other => Err(other),
+                            }
+                        }
+                    }
+                },
+            });
+        }
+    }
+    Ok(stream)
+}
@@ -13,31 +13,36 @@
 // along with this software.
 // If not, see <https://opensource.org/licenses/MIT>.
 
-use std::collections::HashMap;
 use std::convert::TryInto;
-use std::iter::FromIterator;
 
-use amplify_syn::{ArgValue, ArgValueReq, AttrReq, ParametrizedAttr, ValueClass};
+use amplify_syn::{ArgValue, AttrReq, ParametrizedAttr, ValueClass};
 use proc_macro2::{Ident, Span, TokenStream as TokenStream2};
 use quote::ToTokens;
 use syn::spanned::Spanned;
 use syn::{
-    Attribute, Data, DataStruct, DeriveInput, Error, Field, Fields, ImplGenerics, LitStr, Result,
-    TypeGenerics, WhereClause,
+    Attribute, Data, DataStruct, DeriveInput, Error, Field, Fields, FieldsNamed, FieldsUnnamed,
+    GenericArgument, ImplGenerics, LitStr, Member, PathArguments, Result, Type, TypeGenerics,
+    TypePath, WhereClause,
 };
 
+use crate::util::accessor_attrs;
+
 pub(crate) fn derive(input: DeriveInput) -> Result<TokenStream2> {
     let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
     let struct_name = &input.ident;
 
     let mut global_param = ParametrizedAttr::with("getter", &input.attrs)?;
-    let _ = GetterDerive::try_from(&mut global_param, true)?;
+    let global = GetterDerive::try_from(&mut global_param, true)?;
+    // Not a getter method argument, so it must not be merged with field-level
+    // arguments
+    global_param.args.remove("into_parts");
 
     match input.data {
         Data::Struct(data) => derive_struct_impl(
             data,
             struct_name,
             global_param,
+            &global,
             impl_generics,
             ty_generics,
             where_clause,
@@ -54,33 +59,62 @@ pub(crate) fn derive(input: DeriveInput) -> Result<TokenStream2> {
 #[derive(Clone)]
 struct GetterDerive {
     pub prefix: LitStr,
+    pub suffix: LitStr,
     // pub doc: Attribute,
     pub skip: bool,
     pub copy: bool,
     pub base: Option<LitStr>,
+    pub name: Option<LitStr>,
     pub main: Option<LitStr>,
     pub as_ref: Option<LitStr>,
     pub as_mut: Option<LitStr>,
+    /// Whether the way of returning the field value (copy, clone or
+    /// reference) was explicitly specified by the attribute
+    pub explicit: bool,
+    pub deref: bool,
+    pub constness: bool,
+    pub into_parts: bool,
 }
 
 impl GetterDerive {
-    #[allow(clippy::blocks_in_if_conditions)]
     fn try_from(attr: &mut ParametrizedAttr, global: bool) -> Result<GetterDerive> {
-        let mut map = HashMap::from_iter(vec![
-            ("prefix", ArgValueReq::with_default("")),
-            ("all", ArgValueReq::Prohibited),
-            ("as_copy", ArgValueReq::with_default("")),
-            ("as_clone", ArgValueReq::with_default("")),
-            ("as_ref", ArgValueReq::with_default("")),
-            ("as_mut", ArgValueReq::with_default("_mut")),
-        ]);
-
-        if !global {
-            map.insert("skip", ArgValueReq::Prohibited);
-            map.insert("base_name", ArgValueReq::Optional(ValueClass::str()));
+        let req = AttrReq::new()
+            .with_default("prefix", "")
+            .with_default("suffix", "")
+            .flag("all")
+            .with_default("as_copy", "")
+            .flag("copy")
+            .flag("deref")
+            .flag("const")
+            .with_default("as_clone", "")
+            .with_default("as_ref", "")
+            .with_default("as_mut", "_mut");
+        let req = if global {
+            req.flag("into_parts")
+        } else {
+            req.flag("skip")
+                .optional("base_name", ValueClass::str())
+                .optional("name", ValueClass::str())
+        };
+
+        attr.check(req)?;
+
+        if attr.args.contains_key("name") && attr.args.contains_key("base_name") {
+            return Err(Error::new(
+                Span::call_site(),
+                "`name` and `base_name` attributes can't be present together",
+            ));
+        }
+
+        // `copy` is a shorthand for the `as_copy` with the default suffix
+        if attr.args.remove("copy").is_some() && !attr.args.contains_key("as_copy") {
+            attr.args.insert("as_copy".to_owned(), ArgValue::from(""));
         }
 
-        attr.check(AttrReq::with(map))?;
+        let explicit = attr
+            .args
+            .keys()
+            .any(|k| k == "all" || k == "as_copy" || k == "as_clone" || k == "as_ref");
 
         if attr.args.contains_key("all") {
             if attr.args.contains_key("as_clone") ||
@@ -141,13 +175,24 @@ impl GetterDerive {
                 .map(|a| a.clone().try_into())
                 .transpose()?
                 .unwrap_or_else(|| LitStr::new("", Span::call_site())),
-            skip: attr.args.get("skip").is_some(),
+            suffix: attr
+                .args
+                .get("suffix")
+                .map(|a| a.clone().try_into())
+                .transpose()?
+                .unwrap_or_else(|| LitStr::new("", Span::call_site())),
+            skip: attr.args.contains_key("skip"),
             copy: attr.args.contains_key("as_copy"),
             base: attr
                 .args
                 .get("base_name")
                 .map(|a| a.clone().try_into())
                 .transpose()?,
+            name: attr
+                .args
+                .get("name")
+                .map(|a| a.clone().try_into())
+                .transpose()?,
             main: attr
                 .args
                 .get("as_copy")
@@ -164,6 +209,10 @@ impl GetterDerive {
                 .get("as_mut")
                 .map(|a| a.clone().try_into())
                 .transpose()?,
+            explicit,
+            deref: attr.args.contains_key("deref"),
+            constness: attr.args.contains_key("const"),
+            into_parts: attr.args.contains_key("into_parts"),
         })
     }
 }
@@ -232,20 +281,19 @@ impl GetterDerive {
         &self,
         method: GetterMethod,
         field_name: Option<&Ident>,
+        field_index: usize,
         span: Span,
     ) -> Result<Ident> {
         let base_string = self
             .base
             .as_ref()
             .map(LitStr::value)
-            .or_else(|| field_name.map(Ident::to_string))
-            .ok_or_else(|| {
-                Error::new(
-                    span,
-                    "Unnamed fields must be equipped with `#[getter(base_name = \"name\"]` \
-                     attribute",
-                )
-            })?;
+            .or_else(|| {
+                field_name
+                    .map(Ident::to_string)
+                    .map(|name| name.trim_start_matches("r#").to_owned())
+            })
+            .unwrap_or_else(|| field_index.to_string());
 
         let name_lit = match method {
             GetterMethod::Main { .. } => &self.main,
@@ -255,9 +303,22 @@ impl GetterDerive {
         .clone()
         .expect("Internal inconsistency in getter derivation macro implementation");
 
-        let s = format!("{}{}{}", self.prefix.value(), base_string, name_lit.value());
+        let mut s = match self.name {
+            Some(ref name) => format!("{}{}", name.value(), name_lit.value()),
+            None => format!(
+                "{}{}{}{}",
+                self.prefix.value(),
+                base_string,
+                self.suffix.value(),
+                name_lit.value()
+            ),
+        };
+        // Getters for unnamed fields without prefix are named `_0`, `_1` etc
+        if s.starts_with(|c: char| c.is_ascii_digit()) {
+            s.insert(0, '_');
+        }
 
-        Ok(Ident::new(&s, span))
+        method_ident(&s, self.name.as_ref().map_or(span, LitStr::span))
     }
 
     pub fn getter_fn_doc(
@@ -266,26 +327,29 @@ impl GetterDerive {
         struct_name: &Ident,
         field_name: Option<&Ident>,
         field_index: usize,
-        field_doc: Option<&Attribute>,
+        field_attrs: &[&Attribute],
     ) -> TokenStream2 {
-        let fn_doc = format!(
-            "Method {} [`{}::{}`] field.\n",
-            method.doc_phrase(),
-            struct_name,
-            field_name
-                .map(Ident::to_string)
-                .unwrap_or_else(|| field_index.to_string())
-        );
-
-        if let Some(field_doc) = field_doc {
-            quote! {
-                #[doc = #fn_doc]
-                #field_doc
-            }
-        } else {
-            quote! {
-                #[doc = #fn_doc]
+        // Tuple struct fields can't be linked to
+        let fn_doc = match field_name {
+            Some(field_name) => {
+                format!(
+                    "Method {} [`{}::{}`] field.\n",
+                    method.doc_phrase(),
+                    struct_name,
+                    field_name
+                )
             }
+            None => format!(
+                "Method {} field `{}` of [`{}`].\n",
+                method.doc_phrase(),
+                field_index,
+                struct_name
+            ),
+        };
+
+        quote! {
+            #[doc = #fn_doc]
+            #( #field_attrs )*
         }
     }
 }
@@ -294,22 +358,32 @@ fn derive_struct_impl(
     data: DataStruct,
     struct_name: &Ident,
     global_param: ParametrizedAttr,
+    global: &GetterDerive,
     impl_generics: ImplGenerics,
     ty_generics: TypeGenerics,
     where_clause: Option<&WhereClause>,
 ) -> Result<TokenStream2> {
     let mut methods = Vec::with_capacity(data.fields.len());
     match data.fields {
-        Fields::Named(ref fields) => {
-            for (index, field) in fields.named.iter().enumerate() {
-                methods.extend(derive_field_methods(field, index, struct_name, &global_param)?)
+        Fields::Named(FieldsNamed {
+            named: ref fields, ..
+        }) |
+        Fields::Unnamed(FieldsUnnamed {
+            unnamed: ref fields,
+            ..
+        }) => {
+            for (index, field) in fields.iter().enumerate() {
+                methods.extend(derive_field_methods(
+                    field,
+                    index,
+                    struct_name,
+                    &global_param,
+                    global.explicit,
+                )?)
+            }
+            if global.into_parts {
+                methods.extend(derive_parts_methods(&data.fields, struct_name)?);
             }
-        }
-        Fields::Unnamed(_) => {
-            return Err(Error::new(
-                Span::call_site(),
-                "Deriving getters is not supported for tuple-bases structs",
-            ));
         }
         Fields::Unit => {
             return Err(Error::new(
@@ -332,11 +406,19 @@ fn derive_field_methods(
     index: usize,
     struct_name: &Ident,
     global_param: &ParametrizedAttr,
+    global_explicit: bool,
 ) -> Result<Vec<TokenStream2>> {
     let mut local_param = ParametrizedAttr::with("getter", &field.attrs)?;
 
     // First, test individual attribute
-    let _ = GetterDerive::try_from(&mut local_param, false)?;
+    let local = GetterDerive::try_from(&mut local_param, false)?;
+    // Primitive types are returned by value unless other way was specified
+    if !global_explicit && !local.explicit && is_copy_primitive(&field.ty) {
+        local_param.args.remove("as_ref");
+        local_param
+            .args
+            .insert("as_copy".to_owned(), ArgValue::from(""));
+    }
     // Second, combine global and local together
     let mut local_args = local_param.args.clone();
     let mut params = global_param.clone().merged(local_param)?;
@@ -365,25 +447,205 @@ fn derive_field_methods(
     }
 
     let field_name = field.ident.as_ref();
+    let member = field_name.map_or_else(|| Member::from(index), |name| Member::from(name.clone()));
     let ty = &field.ty;
-    let doc = field.attrs.iter().find(|a| a.path.is_ident("doc"));
+    let attrs = accessor_attrs(&field.attrs);
 
     let mut res = Vec::with_capacity(3);
     for method in getter.all_methods() {
-        let fn_name = getter.getter_fn_ident(method, field_name, field.span())?;
-        let fn_doc = getter.getter_fn_doc(method, struct_name, field_name, index, doc);
+        let fn_name = getter.getter_fn_ident(method, field_name, index, field.span())?;
+        let fn_doc = getter.getter_fn_doc(method, struct_name, field_name, index, &attrs);
         let ret_prefix = method.ret_prefix();
         let ret_suffix = method.ret_suffix();
         let mut_prefix = method.mut_prefix();
 
+        let deref = match method {
+            GetterMethod::AsRef | GetterMethod::AsMut if getter.deref => {
+                deref_return(ty, method, &member)
+            }
+            _ => None,
+        };
+        // Mutable borrows, clones and deref conversions are not allowed in
+        // const context
+        let constness = match method {
+            GetterMethod::Main { copy: true } => getter.constness,
+            GetterMethod::AsRef => getter.constness && deref.is_none(),
+            GetterMethod::Main { copy: false } | GetterMethod::AsMut => false,
+        };
+        let constness = if constness {
+            quote! { const }
+        } else {
+            quote! {}
+        };
+        let (ret_ty, body) = match deref {
+            Some(deref) => deref,
+            None if local.deref && method != GetterMethod::Main { copy: getter.copy } => {
+                return Err(Error::new_spanned(
+                    ty,
+                    "`#[getter(deref)]` is supported only for `String`, `Vec`, `Box`, `PathBuf`, \
+                     `OsString` and `Option` field types",
+                ));
+            }
+            None => (quote! { #ret_prefix #ty }, quote! { #ret_prefix self.#member #ret_suffix }),
+        };
+
         res.push(quote_spanned! { field.span() =>
             #fn_doc
             #[inline]
-            pub fn #fn_name(&#mut_prefix self) -> #ret_prefix #ty {
-                #ret_prefix self.#field_name #ret_suffix
+            pub #constness fn #fn_name(&#mut_prefix self) -> #ret_ty {
+                #body
             }
         })
     }
 
     Ok(res)
 }
+
+/// Derives `into_parts` method decomposing the structure into a tuple of its
+/// field values and `from_parts` constructor doing the reverse.
+fn derive_parts_methods(fields: &Fields, struct_name: &Ident) -> Result<Vec<TokenStream2>> {
+    if let Some(field) = fields
+        .iter()
+        .find(|field| field.attrs.iter().any(|attr| attr.path().is_ident("cfg")))
+    {
+        return Err(Error::new_spanned(
+            field,
+            "`#[getter(into_parts)]` can't be used with conditionally compiled fields",
+        ));
+    }
+
+    let types = fields.iter().map(|field| &field.ty).collect::<Vec<_>>();
+    let members = fields
+        .iter()
+        .enumerate()
+        .map(|(index, field)| {
+            field
+                .ident
+                .clone()
+                .map_or_else(|| Member::from(index), Member::from)
+        })
+        .collect::<Vec<_>>();
+    let args = members
+        .iter()
+        .map(|member| match member {
+            Member::Named(name) => name.clone(),
+            Member::Unnamed(index) => format_ident!("_{}", index.index),
+        })
+        .collect::<Vec<_>>();
+    let construct = match fields {
+        Fields::Named(_) => quote! { Self { #( #args ),* } },
+        _ => quote! { Self( #( #args ),* ) },
+    };
+    let into_doc = format!("Decomposes [`{}`] into a tuple of its field values.", struct_name);
+    let from_doc = format!("Constructs [`{}`] from a tuple of its field values.", struct_name);
+
+    Ok(vec![
+        quote! {
+            #[doc = #into_doc]
+            #[inline]
+            pub fn into_parts(self) -> ( #( #types, )* ) {
+                ( #( self.#members, )* )
+            }
+        },
+        quote! {
+            #[doc = #from_doc]
+            #[inline]
+            pub fn from_parts(( #( #args, )* ): ( #( #types, )* )) -> Self {
+                #construct
+            }
+        },
+    ])
+}
+
+/// Detects primitive types, which implement `Copy` and are better returned
+/// from getters by value.
+fn is_copy_primitive(ty: &Type) -> bool {
+    const PRIMITIVES: [&str; 16] = [
+        "bool", "char", "u8", "u16", "u32", "u64", "u128", "usize", "i8", "i16", "i32", "i64",
+        "i128", "isize", "f32", "f64",
+    ];
+    match ty {
+        Type::Path(TypePath { qself: None, path }) => {
+            PRIMITIVES.iter().any(|primitive| path.is_ident(primitive))
+        }
+        _ => false,
+    }
+}
+
+/// Detects types dereferencing to a borrowed form, which is better returned
+/// from the borrowing getters, and returns this form.
+fn deref_target(ty: &Type) -> Option<TokenStream2> {
+    let segment = match ty {
+        Type::Path(TypePath { qself: None, path }) => path.segments.last()?,
+        _ => return None,
+    };
+    let inner = match segment.arguments {
+        PathArguments::AngleBracketed(ref args) if args.args.len() == 1 => match args.args[0] {
+            GenericArgument::Type(ref inner) => Some(inner),
+            _ => None,
+        },
+        _ => None,
+    };
+    match (segment.ident.to_string().as_str(), inner) {
+        ("String", None) => Some(quote! { str }),
+        ("PathBuf", None) => Some(quote! { ::std::path::Path }),
+        ("OsString", None) => Some(quote! { ::std::ffi::OsStr }),
+        ("Vec", Some(inner)) => Some(quote! { [#inner] }),
+        ("Box", Some(inner)) => Some(quote! { #inner }),
+        _ => None,
+    }
+}
+
+/// Constructs return type and the expression returning the field value for
+/// the `#[getter(deref)]` borrowing getters, if the type of the field is
+/// supported.
+fn deref_return(
+    ty: &Type,
+    method: GetterMethod,
+    member: &Member,
+) -> Option<(TokenStream2, TokenStream2)> {
+    let ret_prefix = method.ret_prefix();
+    if let Some(target) = deref_target(ty) {
+        // Deref coercion does the rest
+        return Some((quote! { #ret_prefix #target }, quote! { #ret_prefix self.#member }));
+    }
+    let segment = match ty {
+        Type::Path(TypePath { qself: None, path }) => path.segments.last()?,
+        _ => return None,
+    };
+    let inner = match segment.arguments {
+        PathArguments::AngleBracketed(ref args)
+            if segment.ident == "Option" && args.args.len() == 1 =>
+        {
+            match args.args[0] {
+                GenericArgument::Type(ref inner) => inner,
+                _ => return None,
+            }
+        }
+        _ => return None,
+    };
+    let (target, convert) = match (deref_target(inner), method) {
+        (Some(target), GetterMethod::AsMut) => (target, quote! { as_deref_mut }),
+        (Some(target), _) => (target, quote! { as_deref }),
+        (None, GetterMethod::AsMut) => (quote! { #inner }, quote! { as_mut }),
+        (None, _) => (quote! { #inner }, quote! { as_ref }),
+    };
+    Some((
+        quote! { ::core::option::Option<#ret_prefix #target> },
+        quote! { self.#member.#convert() },
+    ))
+}
+
+/// Constructs identifier for the getter or setter method, which is made raw if
+/// the name is a keyword (like for `r#type` field).
+pub(crate) fn method_ident(name: &str, span: Span) -> Result<Ident> {
+    if syn::parse_str::<Ident>(name).is_ok() {
+        Ok(Ident::new(name, span))
+    } else if !["self", "Self", "super", "crate"].contains(&name) &&
+        syn::parse_str::<Ident>(&format!("r#{}", name)).is_ok()
+    {
+        Ok(Ident::new_raw(name, span))
+    } else {
+        Err(Error::new(span, format!("`{}` is not a valid method name", name)))
+    }
+}
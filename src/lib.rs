@@ -16,6 +16,24 @@
 
 //! Amplifying Rust language capabilities: multiple generic trait
 //! implementations, type wrappers, derive macros.
+//!
+//! All derives accept `#[amplify_crate(...)]` attribute controlling the code
+//! they generate:
+//! - `#[amplify_crate(path)]` sets the path to the `amplify` crate, which is
+//!   required when `amplify` is accessed through a re-export. Otherwise the
+//!   name under which `amplify` is given in the dependencies is used, such
+//!   that renamed dependencies work without the attribute;
+//! - `#[amplify_crate(no_std)]` makes the generated code rely only on `core`
+//!   and `alloc` crates. This is always the case when the default `std`
+//!   feature of this crate is disabled. Since cargo enables the feature for
+//!   the whole build once any of the dependent crates requests it, `no_std`
+//!   crates should rely on the attribute rather than on the feature.
+//!
+//! Both arguments may be combined, like in
+//! `#[amplify_crate(::facade::amplify, no_std)]`. The `no_std` argument of the
+//! derive-specific attributes (`#[wrapper(no_std)]`, `#[from_repr(no_std)]`
+//! and `#[error(no_std)]`) is deprecated in favour of
+//! `#[amplify_crate(no_std)]`, which it is equivalent to.
 
 #[macro_use]
 extern crate quote;
@@ -27,10 +45,17 @@ extern crate proc_macro;
 mod util;
 
 mod as_any;
+mod by_key;
+mod default;
 mod display;
+mod enum_iter;
 mod error;
 mod from;
 mod getters;
+mod new;
+mod repr;
+mod serde_str;
+mod setters;
 mod wrapper;
 
 use proc_macro::TokenStream;
@@ -48,6 +73,28 @@ use syn::DeriveInput;
 ///         Twice(u8)
 ///     }
 ///    ```
+///    When given to an enum variant having a single field, the formatting
+///    trait is applied to that field; for other variants it is applied to
+///    the whole value:
+///    ```
+///     # #[macro_use] extern crate amplify_derive;
+///     #[derive(Display, Debug)]
+///     #[display(lowercase)]
+///     enum Register {
+///         #[display(LowerHex)]
+///         Address(u32),
+///         #[display(Binary)]
+///         Flags { bits: u8 },
+///         #[display(Debug)]
+///         Pair(u8, u8),
+///         Zero,
+///     }
+///     assert_eq!(format!("{}", Register::Address(0xbeef)), "beef");
+///     assert_eq!(format!("{:#}", Register::Address(0xbeef)), "0xbeef");
+///     assert_eq!(format!("{:08}", Register::Flags { bits: 5 }), "00000101");
+///     assert_eq!(format!("{}", Register::Pair(1, 2)), "Pair(1, 2)");
+///     assert_eq!(format!("{}", Register::Zero), "zero");
+///    ```
 /// 2. Use existing function for displaying descriptions:
 ///    ```
 ///     # #[macro_use] extern crate amplify_derive;
@@ -87,6 +134,64 @@ use syn::DeriveInput;
 ///    Formatting function must return [`String`] and take a single `self`
 ///    argument (if you need formatting with streamed output, use one of
 ///    existing formatting traits as shown in pt. 1).
+///    For streamed output, `#[display(with = path::to::function)]` may be
+///    given to a type, an enum variant or a structure field. The function
+///    must have `fn(&T, &mut Formatter) -> fmt::Result` signature, where `T`
+///    is the type itself (for types and variants) or the type of the field:
+///    ```
+///     # #[macro_use] extern crate amplify_derive;
+///     use std::fmt::{self, Formatter};
+///
+///     fn short_hex(data: &[u8; 4], f: &mut Formatter) -> fmt::Result {
+///         write!(f, "{:02x}..{:02x}", data[0], data[3])
+///     }
+///
+///     fn fmt_entry(entry: &Entry, f: &mut Formatter) -> fmt::Result {
+///         match entry {
+///             Entry::Empty => f.write_str("<empty>"),
+///             Entry::Hash(hash) => short_hex(hash, f),
+///         }
+///     }
+///
+///     #[derive(Display)]
+///     #[display("{id} ({hash})")]
+///     struct Record {
+///         id: u8,
+///         #[display(with = short_hex)]
+///         hash: [u8; 4],
+///     }
+///
+///     #[derive(Display)]
+///     enum Entry {
+///         #[display(with = fmt_entry)]
+///         Empty,
+///         #[display(with = fmt_entry)]
+///         Hash([u8; 4]),
+///     }
+///
+///     #[derive(Display)]
+///     #[display("#{0} {1}")]
+///     struct Row(u8, #[display(with = short_hex)] [u8; 4]);
+///
+///     #[derive(Display)]
+///     enum Link {
+///         #[display("to {0}")]
+///         To(#[display(with = short_hex)] [u8; 4]),
+///         #[display("from {hash}")]
+///         From {
+///             #[display(with = short_hex)]
+///             hash: [u8; 4],
+///         },
+///     }
+///
+///     let record = Record { id: 1, hash: [0xde, 0xad, 0xbe, 0xef] };
+///     assert_eq!(format!("{}", record), "1 (de..ef)");
+///     assert_eq!(format!("{}", Entry::Empty), "<empty>");
+///     assert_eq!(format!("{}", Entry::Hash([1, 2, 3, 4])), "01..04");
+///     assert_eq!(format!("{}", Row(2, [0xca, 0xfe, 0xba, 0xbe])), "#2 ca..be");
+///     assert_eq!(format!("{}", Link::To([1, 2, 3, 4])), "to 01..04");
+///     assert_eq!(format!("{}", Link::From { hash: [5, 6, 7, 8] }), "from 05..08");
+///    ```
 /// 3. Custom format string:
 ///    ```
 ///     # #[macro_use] extern crate amplify_derive;
@@ -101,6 +206,160 @@ use syn::DeriveInput;
 ///     assert_eq!(format!("{}", Data { vec: vec!["foo".into(), "bar".into()]}),
 ///         "[foo, bar]");
 ///    ```
+///    While `separator` requires a collection of strings, `join` displays
+///    items of any collection with their own [`Display`] implementation:
+///    ```
+///     # #[macro_use] extern crate amplify_derive;
+///     # use std::collections::BTreeSet;
+///     #[derive(Display)]
+///     #[display("{name}: {ports}")]
+///     struct Service {
+///         name: &'static str,
+///         #[display(join = ", ")]
+///         ports: BTreeSet<u16>,
+///     }
+///     let service = Service { name: "web", ports: [443, 80].into_iter().collect() };
+///     assert_eq!(format!("{}", service), "web: 80, 443");
+///    ```
+///    Field attributes apply to the fields of tuple structures and enum
+///    variants as well:
+///    ```
+///     # #[macro_use] extern crate amplify_derive;
+///     #[derive(Display)]
+///     #[display("{0}: {1}")]
+///     struct Route(&'static str, #[display(join = " -> ")] Vec<&'static str>);
+///
+///     #[derive(Display)]
+///     enum Path {
+///         #[display("hops {0}")]
+///         Hops(#[display(join = ", ")] Vec<u8>),
+///         #[display("via {nodes:x}")]
+///         Via {
+///             #[display(join = "/")]
+///             nodes: Vec<u16>,
+///         },
+///     }
+///
+///     let route = Route("main", vec!["a", "b", "c"]);
+///     assert_eq!(format!("{}", route), "main: a -> b -> c");
+///     assert_eq!(format!("{}", Path::Hops(vec![1, 2, 3])), "hops 1, 2, 3");
+///     assert_eq!(format!("{}", Path::Via { nodes: vec![10, 255] }), "via a/ff");
+///    ```
+///    Optional fields may be displayed with `none` attribute, which shows the
+///    value when it is present and the given placeholder otherwise:
+///    ```
+///     # #[macro_use] extern crate amplify_derive;
+///     #[derive(Display)]
+///     #[display("{name} <{email}>")]
+///     struct Contact {
+///         name: &'static str,
+///         #[display(none = "n/a")]
+///         email: Option<&'static str>,
+///     }
+///     let contact = Contact { name: "Alice", email: Some("alice@example.com") };
+///     assert_eq!(format!("{}", contact), "Alice <alice@example.com>");
+///     let contact = Contact { name: "Bob", email: None };
+///     assert_eq!(format!("{}", contact), "Bob <n/a>");
+///
+///     #[derive(Display)]
+///     #[display("{0}:{1}")]
+///     struct Endpoint(&'static str, #[display(none = "*")] Option<u16>);
+///     assert_eq!(format!("{}", Endpoint("localhost", Some(80))), "localhost:80");
+///     assert_eq!(format!("{}", Endpoint("localhost", None)), "localhost:*");
+///
+///     #[derive(Display)]
+///     enum Reply {
+///         #[display("code {0}")]
+///         Code(#[display(none = "unknown")] Option<u16>),
+///         #[display("{text}")]
+///         Text {
+///             #[display(none = "<empty>")]
+///             text: Option<String>,
+///         },
+///     }
+///     assert_eq!(format!("{}", Reply::Code(Some(404))), "code 404");
+///     assert_eq!(format!("{}", Reply::Code(None)), "code unknown");
+///     assert_eq!(format!("{}", Reply::Text { text: None }), "<empty>");
+///    ```
+///    Field placeholders accept any standard format spec, like `{code:08x}`
+///    or `{flags:#b}`; for fields with `join` and `none` attributes the spec
+///    is applied to each of the displayed values:
+///    ```
+///     # #[macro_use] extern crate amplify_derive;
+///     #[derive(Display)]
+///     #[display("{code:04x} {flags:#b} [{bytes:02X}] {extra:#x}")]
+///     struct Packet {
+///         code: u16,
+///         flags: u8,
+///         #[display(join = " ")]
+///         bytes: Vec<u8>,
+///         #[display(none = "-")]
+///         extra: Option<u32>,
+///     }
+///     let packet = Packet { code: 0x1f, flags: 5, bytes: vec![0xa, 0xff], extra: None };
+///     assert_eq!(format!("{}", packet), "001f 0b101 [0A FF] -");
+///    ```
+///    Long values, like hashes, may be shortened with `truncate` attribute,
+///    which displays only the given number of first characters followed by
+///    `…` if the value was truncated:
+///    ```
+///     # #[macro_use] extern crate amplify_derive;
+///     #[derive(Display)]
+///     #[display("tx {txid} ({label})")]
+///     struct Tx {
+///         #[display(truncate = 8)]
+///         txid: &'static str,
+///         #[display(truncate = 8)]
+///         label: &'static str,
+///     }
+///     let tx = Tx { txid: "4a5e1e4baab89f3a32518a88c31bc87f", label: "coinbase" };
+///     assert_eq!(format!("{}", tx), "tx 4a5e1e4b… (coinbase)");
+///
+///     #[derive(Display)]
+///     #[display("block {0}")]
+///     struct Block(#[display(truncate = 6)] &'static str);
+///     assert_eq!(format!("{}", Block("000000000019d668")), "block 000000…");
+///
+///     #[derive(Display)]
+///     enum Ref {
+///         #[display("commit {0}")]
+///         Commit(#[display(truncate = 7)] String),
+///         #[display("tag {name}")]
+///         Tag {
+///             #[display(truncate = 5)]
+///             name: &'static str,
+///         },
+///     }
+///     let commit = Ref::Commit("0a3dbf1b2c4d".into());
+///     assert_eq!(format!("{}", commit), "commit 0a3dbf1…");
+///     assert_eq!(format!("{}", Ref::Tag { name: "v4.0.1-rc" }), "tag v4.0.…");
+///    ```
+///    Internal fields may be marked with `#[display(skip)]`: referencing them
+///    in format strings results in a compile-time error, just like referencing
+///    unknown fields does. Positional placeholders `{}` are resolved to the
+///    remaining fields in the order of their declaration:
+///    ```
+///     # #[macro_use] extern crate amplify_derive;
+///     #[derive(Display)]
+///     #[display("{} ({:#x})")]
+///     struct Output(&'static str, #[display(skip)] usize, u64);
+///
+///     #[derive(Display)]
+///     #[display("{name}", warn_unused)]
+///     struct Peer {
+///         name: &'static str,
+///         #[display(skip)]
+///         cache: Vec<u8>,
+///     }
+///
+///     assert_eq!(format!("{}", Output("change", 1, 4096)), "change (0x1000)");
+///     assert_eq!(format!("{}", Peer { name: "alice", cache: vec![] }), "alice");
+///    ```
+///    Errors about unknown placeholders point to the placeholder inside the
+///    format string when the compiler supports this, and to the whole string
+///    otherwise. Adding `warn_unused` argument, like in
+///    `#[display("{name}", warn_unused)]`, makes the compiler warn about the
+///    fields which are neither used by the format strings nor skipped.
 /// 4. Support for alternative formatting with `alt` parameter:
 ///    ```
 ///     # #[macro_use] extern crate amplify_derive;
@@ -109,6 +368,80 @@ use syn::DeriveInput;
 ///     struct Point { x: u32, y: u32 }
 ///     assert_eq!(format!("{}", Point { x: 0, y: 1 }), "(0, 1)");
 ///     assert_eq!(format!("{:#}", Point { x: 0, y: 1 }), "0:1");
+///
+///     // alternative format may use fields not present in the main one
+///     #[derive(Display)]
+///     enum Key {
+///         #[display("{0}", alt = "{0:#x} ({1})")]
+///         Id(u32, &'static str),
+///         #[display("{name}", alt = "{name} ({id:#x})")]
+///         Named { id: u32, name: &'static str },
+///     }
+///     assert_eq!(format!("{}", Key::Id(16, "root")), "16");
+///     assert_eq!(format!("{:#}", Key::Id(16, "root")), "0x10 (root)");
+///     assert_eq!(format!("{:#}", Key::Named { id: 16, name: "root" }), "root (0x10)");
+///    ```
+///    Format strings may also reach into nested fields and call methods
+///    taking no arguments, like in `{outer.inner}` or `{0.len()}`. Since
+///    the macro can't distinguish a field from a method, method calls must
+///    always be written with explicit parentheses:
+///    ```
+///     # #[macro_use] extern crate amplify_derive;
+///     struct Inner { id: u8 }
+///
+///     #[derive(Display)]
+///     #[display("{outer.id:02}: {items.len()} items", alt = "{items.len()}")]
+///     struct Node { outer: Inner, items: Vec<u8> }
+///
+///     #[derive(Display)]
+///     #[display("{0.1}/{0.0}")]
+///     struct Pair((u8, u8));
+///
+///     let node = Node { outer: Inner { id: 7 }, items: vec![1, 2] };
+///     assert_eq!(format!("{}", node), "07: 2 items");
+///     assert_eq!(format!("{:#}", node), "2");
+///     assert_eq!(format!("{}", Pair((1, 2))), "2/1");
+///
+///     #[derive(Display)]
+///     enum Shape {
+///         #[display("circle at {center.0}x{center.1}")]
+///         Circle { center: (u8, u8) },
+///         #[display("polygon of {0.len()} points")]
+///         Polygon(Vec<(u8, u8)>),
+///     }
+///     assert_eq!(format!("{}", Shape::Circle { center: (1, 2) }), "circle at 1x2");
+///     assert_eq!(format!("{}", Shape::Polygon(vec![(0, 0); 3])), "polygon of 3 points");
+///    ```
+///    Like with `format!`, the format string may be followed by arbitrary
+///    expressions, which are evaluated with `self` in scope and are referenced
+///    by positional placeholders. Named fields may still be referenced by
+///    their names:
+///    ```
+///     # #[macro_use] extern crate amplify_derive;
+///     #[derive(Display)]
+///     #[display("{} ({}) {id}", self.name.to_uppercase(), self.items.len())]
+///     struct Order { id: u32, name: String, items: Vec<u8> }
+///
+///     #[derive(Display)]
+///     enum Item {
+///         #[display("{name}: {}", self.price())]
+///         Priced { name: &'static str, cents: u32 },
+///         #[display("{0}", "free")]
+///         Free,
+///     }
+///     impl Item {
+///         fn price(&self) -> String {
+///             match self {
+///                 Item::Priced { cents, .. } => format!("${}.{:02}", cents / 100, cents % 100),
+///                 Item::Free => String::from("0"),
+///             }
+///         }
+///     }
+///
+///     let order = Order { id: 5, name: String::from("pens"), items: vec![1, 2] };
+///     assert_eq!(format!("{}", order), "PENS (2) 5");
+///     assert_eq!(format!("{}", Item::Priced { name: "pen", cents: 150 }), "pen: $1.50");
+///     assert_eq!(format!("{}", Item::Free), "free");
 ///    ```
 /// 5. Use of doc comments for descrition representation. In this case doc
 ///    comments may also contain formatting like in the case 3:
@@ -152,6 +485,65 @@ use syn::DeriveInput;
 ///    ```
 ///    You can also mix in this mode with other fors of display tags on a
 ///    specific options; in this case doc comments are ignored
+///
+///    Doc comments may reference both named and unnamed fields. With
+///    `#[display(doc_comments, first_line)]` only the first line of the
+///    comment is used, and the rest of it remains a regular documentation:
+///    ```
+///     # #[macro_use] extern crate amplify_derive;
+///     #[derive(Display)]
+///     #[display(doc_comments, first_line)]
+///     enum ProtocolError {
+///         /// peer {peer} sent message of unknown type {msg_type:#x}.
+///         ///
+///         /// Such messages are ignored, but the peer is not banned.
+///         UnknownMessage { peer: u16, msg_type: u8 },
+///         /// invalid checksum {0}
+///         /// (which is a critical error)
+///         Checksum(u32),
+///     }
+///
+///     assert_eq!(
+///         format!("{}", ProtocolError::UnknownMessage { peer: 3, msg_type: 16 }),
+///         "peer 3 sent message of unknown type 0x10."
+///     );
+///     assert_eq!(format!("{}", ProtocolError::Checksum(7)), "invalid checksum 7");
+///    ```
+///    Only fields referenced by the format strings are required to implement
+///    formatting traits, so other fields may be of any type. Variants without
+///    doc comments are displayed in the same way as without `display`
+///    attribute:
+///    ```
+///     # #[macro_use] extern crate amplify_derive;
+///     struct Callback(fn(u8) -> u8);
+///
+///     /// handler {name}
+///     #[derive(Display)]
+///     #[display(doc_comments)]
+///     struct Handler {
+///         name: &'static str,
+///         callback: Callback,
+///     }
+///
+///     #[derive(Display)]
+///     #[display(doc_comments)]
+///     enum Hook {
+///         /// hook {name}
+///         Named { name: &'static str, callback: Callback },
+///         #[display("anonymous hook {0}", alt = "hook #{0}")]
+///         Anonymous(u8, Callback),
+///         Raw(Callback),
+///     }
+///
+///     let callback = || Callback(|x| x);
+///     let handler = Handler { name: "print", callback: callback() };
+///     assert_eq!(format!("{}", handler), "handler print");
+///     let named = Hook::Named { name: "log", callback: callback() };
+///     assert_eq!(format!("{}", named), "hook log");
+///     assert_eq!(format!("{}", Hook::Anonymous(2, callback())), "anonymous hook 2");
+///     assert_eq!(format!("{:#}", Hook::Anonymous(2, callback())), "hook #2");
+///     assert_eq!(format!("{}", Hook::Raw(callback())), "Raw(..)");
+///    ```
 /// 6. Support of unit structs and newtypes:
 ///    ```
 ///     # #[macro_use] extern crate amplify_derive;
@@ -196,6 +588,364 @@ use syn::DeriveInput;
 ///     assert_eq!(format!("{}", Event::Load(Message::ChangeColor(0, 255, 0))),
 ///         "LOAD(changecolor(0, 255, 0))");
 ///    ```
+///    Other case transformations of the variant name are `snake_case`,
+///    `kebab-case`, `camelCase` and `PascalCase` (`UPPERCASE` is a synonym
+///    for `uppercase`). The same names put in braces may be used as
+///    placeholders inside format strings. Enum-level attribute sets the
+///    default for all variants, which may be overridden per variant:
+///    ```
+///     # #[macro_use] extern crate amplify_derive;
+///     #[derive(Display)]
+///     #[display(kebab-case)]
+///     enum Command {
+///         ListAll,
+///         #[display(snake_case)]
+///         HTTPServer,
+///         #[display("{camelCase}: {0}")]
+///         ShowItem(u8),
+///         #[display("<{PascalCase}>")]
+///         show_raw,
+///         // escaped braces are kept as they are
+///         #[display("{{snake_case}} {snake_case}: {0}")]
+///         ShowHelp(u8),
+///     }
+///
+///     assert_eq!(format!("{}", Command::ListAll), "list-all");
+///     assert_eq!(format!("{}", Command::HTTPServer), "http_server");
+///     assert_eq!(format!("{}", Command::ShowItem(5)), "showItem: 5");
+///     assert_eq!(format!("{}", Command::show_raw), "<ShowRaw>");
+///     assert_eq!(format!("{}", Command::ShowHelp(1)), "{snake_case} show_help: 1");
+///    ```
+///    Similarly to serde, `#[display(rename_all = "kebab-case")]` enum-level
+///    attribute renames all variants which do not have their own display
+///    attribute; `SCREAMING_SNAKE_CASE` and `SCREAMING-KEBAB-CASE` are also
+///    supported:
+///    ```
+///     # #[macro_use] extern crate amplify_derive;
+///     #[derive(Display)]
+///     #[display(rename_all = "SCREAMING_SNAKE_CASE")]
+///     enum Network {
+///         MainNet,
+///         TestNet3,
+///         #[display("regtest")]
+///         RegTest,
+///         Custom(u32),
+///     }
+///
+///     assert_eq!(format!("{}", Network::MainNet), "MAIN_NET");
+///     assert_eq!(format!("{}", Network::TestNet3), "TEST_NET3");
+///     assert_eq!(format!("{}", Network::RegTest), "regtest");
+///     assert_eq!(format!("{}", Network::Custom(1)), "CUSTOM(..)");
+///    ```
+///    Enum-level format string serves as a template inherited by all variants
+///    which do not have their own display attribute. Its placeholders are
+///    resolved for each variant individually, and `{variant}` placeholder
+///    expands to the variant name:
+///    ```
+///     # #[macro_use] extern crate amplify_derive;
+///     #[derive(Display)]
+///     #[display("{variant}: {msg}")]
+///     enum Failure {
+///         Timeout { msg: &'static str, secs: u8 },
+///         Rejected { msg: String },
+///         #[display("unknown failure")]
+///         Unknown,
+///     }
+///
+///     assert_eq!(
+///         format!("{}", Failure::Timeout { msg: "no reply", secs: 5 }),
+///         "Timeout: no reply"
+///     );
+///     assert_eq!(
+///         format!("{}", Failure::Rejected { msg: String::from("bad nonce") }),
+///         "Rejected: bad nonce"
+///     );
+///     assert_eq!(format!("{}", Failure::Unknown), "unknown failure");
+///    ```
+///    The variant name may be transformed with a case given after the format
+///    string, while variants may opt out of the template by using just their
+///    own cased name. `{_variant_}` is an alias for `{variant}` placeholder:
+///    ```
+///     # #[macro_use] extern crate amplify_derive;
+///     #[derive(Display)]
+///     #[display("{_variant_}:{0}", lowercase)]
+///     enum Query {
+///         Get(u32),
+///         Delete(u32),
+///         #[display(lowercase)]
+///         List,
+///         // escaped braces are kept as they are
+///         #[display("{{variant}} {variant}:{0}")]
+///         Find(u32),
+///     }
+///
+///     assert_eq!(format!("{}", Query::Get(5)), "get:5");
+///     assert_eq!(format!("{}", Query::Delete(7)), "delete:7");
+///     assert_eq!(format!("{}", Query::List), "list");
+///     assert_eq!(format!("{}", Query::Find(9)), "{variant} Find:9");
+///
+///     #[derive(Display)]
+///     #[display("{{variant}}={variant}/{0}", uppercase)]
+///     enum Flag {
+///         Set(u8),
+///     }
+///     assert_eq!(format!("{}", Flag::Set(1)), "{variant}=SET/1");
+///    ```
+///    Format strings of enum variants may use `{discriminant}` placeholder,
+///    which is replaced with the value of the variant discriminant (explicit
+///    or implicit one):
+///    ```
+///     # #[macro_use] extern crate amplify_derive;
+///     #[derive(Display)]
+///     #[display("{snake_case} ({discriminant:#04x})")]
+///     #[repr(u8)]
+///     enum OpCode {
+///         Push = 0x10,
+///         Pop,
+///         #[display("nop/{discriminant}")]
+///         Nop = 0x90,
+///     }
+///
+///     assert_eq!(format!("{}", OpCode::Push), "push (0x10)");
+///     assert_eq!(format!("{}", OpCode::Pop), "pop (0x11)");
+///     assert_eq!(format!("{}", OpCode::Nop), "nop/144");
+///    ```
+/// 8. Generation of [`Debug`] implementation with the same formatting by
+///    adding `#[debug]` attribute to the type. The format string may be
+///    overridden with `#[debug("...")]` given to the type or to specific enum
+///    variants:
+///    ```
+///     # #[macro_use] extern crate amplify_derive;
+///     #[derive(Display)]
+///     #[display("{0} sat")]
+///     #[debug]
+///     struct Amount(u64);
+///
+///     #[derive(Display)]
+///     #[display(lowercase)]
+///     #[debug]
+///     enum Id {
+///         #[debug("Id::Short({0:#x})")]
+///         Short(u16),
+///         Long(u64),
+///     }
+///
+///     assert_eq!(format!("{:?}", Amount(5)), "5 sat");
+///     assert_eq!(format!("{}", Id::Short(16)), "short(16)");
+///     assert_eq!(format!("{:?}", Id::Short(16)), "Id::Short(0x10)");
+///     assert_eq!(format!("{:?}", Id::Long(16)), "long(16)");
+///    ```
+///    Similarly, `fmt_all` flag given to the type-level attribute, like in
+///    `#[display("{0}.{1}", fmt_all)]`, generates [`LowerHex`], [`UpperHex`],
+///    [`Octal`] and [`Binary`] implementations using the same format strings,
+///    where placeholders not specifying formatting trait explicitly use the
+///    implemented one:
+///    ```
+///     # #[macro_use] extern crate amplify_derive;
+///     #[derive(Clone, Copy, Display)]
+///     #[display("{major}.{minor:02}", fmt_all)]
+///     struct Version {
+///         major: u8,
+///         minor: u8,
+///     }
+///
+///     let version = Version { major: 10, minor: 3 };
+///     assert_eq!(format!("{}", version), "10.03");
+///     assert_eq!(format!("{:x}", version), "a.03");
+///     assert_eq!(format!("{:X}", version), "A.03");
+///     assert_eq!(format!("{:o}", version), "12.03");
+///     assert_eq!(format!("{:b}", version), "1010.11");
+///    ```
+///    For fieldless enums `static_str` flag additionally generates inherent
+///    `as_str` const function and `ALL_NAMES` table with the displayed names
+///    of all variants; each variant must then be displayed as a string literal:
+///    ```
+///     # #[macro_use] extern crate amplify_derive;
+///     #[derive(Display)]
+///     #[display(rename_all = "kebab-case", static_str)]
+///     enum Network {
+///         Mainnet,
+///         #[display("testnet3")]
+///         Testnet,
+///         SigNet,
+///     }
+///
+///     assert_eq!(Network::Testnet.as_str(), "testnet3");
+///     assert_eq!(format!("{}", Network::SigNet), Network::SigNet.as_str());
+///     assert_eq!(Network::ALL_NAMES, &["mainnet", "testnet3", "sig-net"]);
+///    ```
+/// 9. Unions are supported only with an explicit type-level attribute, since
+///    reading union fields is unsafe and can't be done by the derived code.
+///    The format string can't reference fields, but may be followed by
+///    expressions; alternatively, a formatting function may be used:
+///    ```
+///     # #[macro_use] extern crate amplify_derive;
+///     use std::fmt::{self, Formatter};
+///
+///     #[derive(Clone, Copy, Display)]
+///     #[display("raw:{:#010x}", unsafe { self.bits })]
+///     #[repr(C)]
+///     union Value {
+///         bits: u32,
+///         float: f32,
+///     }
+///
+///     #[derive(Clone, Copy, Display)]
+///     #[display(with = fmt_number)]
+///     union Number {
+///         int: i64,
+///         uint: u64,
+///     }
+///     fn fmt_number(number: &Number, f: &mut Formatter) -> fmt::Result {
+///         write!(f, "{}", unsafe { number.int })
+///     }
+///
+///     assert_eq!(format!("{}", Value { bits: 0xff }), "raw:0x000000ff");
+///     assert_eq!(format!("{}", Number { int: -5 }), "-5");
+///    ```
+/// 10. Serialization of the value with `serde` using `#[display(json)]`,
+///     `#[display(json_pretty)]`, `#[display(yaml)]` or `#[display(toml)]`
+///     (requires `serde_json`, `serde_yaml` or `toml` feature; the user crate
+///     must depend on the respective crate). Serialization errors are
+///     reported as [`core::fmt::Error`]:
+#[cfg_attr(feature = "serde_json", doc = "    ```")]
+#[cfg_attr(not(feature = "serde_json"), doc = "    ```ignore")]
+///     # #[macro_use] extern crate amplify_derive;
+///     use serde::Serialize;
+///
+///     #[derive(Serialize, Display)]
+///     #[display(json)]
+///     struct Config {
+///         name: String,
+///         port: u16,
+///     }
+///     let config = Config { name: String::from("node"), port: 9735 };
+///     assert_eq!(config.to_string(), r#"{"name":"node","port":9735}"#);
+///     ```
+#[cfg_attr(feature = "serde_yaml", doc = "    ```")]
+#[cfg_attr(not(feature = "serde_yaml"), doc = "    ```ignore")]
+///     # #[macro_use] extern crate amplify_derive;
+///     use serde::Serialize;
+///
+///     #[derive(Serialize, Display)]
+///     #[display(yaml)]
+///     struct Config {
+///         name: String,
+///         port: u16,
+///     }
+///     let config = Config { name: String::from("node"), port: 9735 };
+///     assert_eq!(config.to_string(), "name: node\nport: 9735\n");
+///     ```
+///     Structures with a single byte-like field (implementing `AsRef<[u8]>`)
+///     may be displayed as text encoding of the bytes with
+///     `#[display(base58)]`, `#[display(base32)]` (unpadded) or
+///     `#[display(base64)]` (requires the feature with the same name; the user
+///     crate must depend on `bs58` crate for base58 and on `data-encoding`
+///     crate for the other encodings):
+#[cfg_attr(all(feature = "base58", feature = "base64"), doc = "    ```")]
+#[cfg_attr(not(all(feature = "base58", feature = "base64")), doc = "    ```ignore")]
+///     # #[macro_use] extern crate amplify_derive;
+///     #[derive(Clone, Copy, PartialEq, Eq, Debug, Display, FromStr)]
+///     #[display(base58)]
+///     struct ContractId([u8; 32]);
+///
+///     #[derive(Clone, PartialEq, Eq, Debug, Display, FromStr)]
+///     #[display(base64)]
+///     struct Blob {
+///         data: Vec<u8>,
+///     }
+///
+///     let id = ContractId([1u8; 32]);
+///     assert_eq!(id.to_string(), "4vJ9JU1bJJE96FWSJKvHsmmFADCg4gpZQff4P3bkLKi");
+///     assert_eq!(id.to_string().parse(), Ok(id));
+///     assert!("0OIl".parse::<ContractId>().is_err());
+///
+///     let blob = Blob { data: b"amplify".to_vec() };
+///     assert_eq!(blob.to_string(), "YW1wbGlmeQ==");
+///     assert_eq!(format!("{:>14}", blob), "  YW1wbGlmeQ==");
+///     assert_eq!("YW1wbGlmeQ==".parse(), Ok(blob));
+///     ```
+#[cfg_attr(feature = "base32", doc = "    ```")]
+#[cfg_attr(not(feature = "base32"), doc = "    ```ignore")]
+///     # #[macro_use] extern crate amplify_derive;
+///     #[derive(Clone, Copy, PartialEq, Eq, Debug, Display, FromStr)]
+///     #[display(base32)]
+///     struct Checksum([u8; 4]);
+///
+///     let checksum = Checksum([0xde, 0xad, 0xbe, 0xef]);
+///     assert_eq!(checksum.to_string(), "32W353Y");
+///     assert_eq!("32W353Y".parse(), Ok(checksum));
+///     ```
+/// 11. Generic types get bounds on the types of the fields used by the format
+///     strings, requiring the formatting traits given by the placeholders.
+///     The bounds may be replaced with `bound = "..."` argument of the
+///     type-level attribute:
+///     ```
+///     # #[macro_use] extern crate amplify_derive;
+///     use std::fmt::Debug;
+///
+///     #[derive(Display)]
+///     #[display("{0}-{1:x}")]
+///     struct Pair<T, U>(T, U);
+///
+///     #[derive(Display)]
+///     #[display("{value:?}", bound = "T: Debug")]
+///     struct Tagged<T, F> {
+///         value: Option<T>,
+///         callback: F,
+///     }
+///
+///     assert_eq!(format!("{}", Pair("a", 255u8)), "a-ff");
+///     let tagged = Tagged { value: Some(5), callback: |x: u8| x };
+///     assert_eq!(format!("{}", tagged), "Some(5)");
+///     ```
+/// 12. Messages may be looked up in a catalog, for instance with
+///     translations, given by `catalog = path` argument of the type-level
+///     attribute. The function is called with the type name (or
+///     `Type::Variant` for enums) as a key and with the names and values of
+///     the fields not marked with `#[display(skip)]`, which must implement
+///     [`Display`]. When it returns `None`, the value is displayed as if the
+///     catalog was absent:
+///     ```
+///     # #[macro_use] extern crate amplify_derive;
+///     use std::fmt::Display;
+///
+///     fn german(key: &str, args: &[(&str, &dyn Display)]) -> Option<String> {
+///         match key {
+///             "Failure::NotFound" => Some(format!("{} nicht gefunden", args[0].1)),
+///             "Failure::Timeout" => Some(format!("Zeitüberschreitung nach {}s", args[0].1)),
+///             "Balance" => Some(format!("Kontostand: {} {}", args[0].1, args[1].1)),
+///             _ => None,
+///         }
+///     }
+///
+///     #[derive(Display)]
+///     #[display(catalog = german)]
+///     enum Failure {
+///         #[display("{0} not found")]
+///         NotFound(String),
+///         #[display("timeout after {secs}s")]
+///         Timeout { secs: u8 },
+///         #[display("unknown failure")]
+///         Unknown,
+///     }
+///
+///     #[derive(Display)]
+///     #[display("balance: {0} {1}", catalog = german)]
+///     struct Balance(u64, &'static str, #[display(skip)] u32);
+///
+///     let failure = Failure::NotFound("config".to_owned());
+///     assert_eq!(format!("{}", failure), "config nicht gefunden");
+///     assert_eq!(format!("{}", Failure::Timeout { secs: 5 }), "Zeitüberschreitung nach 5s");
+///     assert_eq!(format!("{}", Failure::Unknown), "unknown failure");
+///     assert_eq!(format!("{}", Balance(5, "sat", 0)), "Kontostand: 5 sat");
+///     ```
+///
+/// [`Debug`]: core::fmt::Debug
+/// [`LowerHex`]: core::fmt::LowerHex
+/// [`UpperHex`]: core::fmt::UpperHex
+/// [`Octal`]: core::fmt::Octal
+/// [`Binary`]: core::fmt::Binary
 /// # Example
 ///
 /// Advanced use with enums:
@@ -255,6 +1005,62 @@ use syn::DeriveInput;
 /// assert_eq!(format!("{}", Tuple(5)), format!("{}", Tuple2(5)))
 /// ```
 ///
+/// Formats consisting of a single field placeholder, as well as string
+/// literals, respect width, fill, alignment and precision given by the caller:
+/// ```
+/// # #[macro_use] extern crate amplify_derive;
+/// #[derive(Display)]
+/// #[display(inner)]
+/// struct Amount(f64);
+///
+/// #[derive(Display)]
+/// enum State {
+///     Active,
+///     #[display("{0}")]
+///     Custom(&'static str),
+/// }
+///
+/// assert_eq!(format!("{:>8.2}", Amount(1.5)), "    1.50");
+/// assert_eq!(format!("[{:<8}]", State::Active), "[Active  ]");
+/// assert_eq!(format!("[{:^8}]", State::Custom("new")), "[  new   ]");
+/// ```
+///
+/// The alternate flag is passed to the field only by `inner` and by
+/// alternative formats like `alt = "{0:#}"`; a single placeholder without
+/// alternative format displays the field without the flag:
+/// ```
+/// # #[macro_use] extern crate amplify_derive;
+/// #[derive(Display)]
+/// #[display("{0}", alt = "{0:#x}")]
+/// struct Byte(u8);
+///
+/// #[derive(Display)]
+/// #[display("{0}")]
+/// struct Plain(Byte);
+///
+/// #[derive(Display)]
+/// #[display("{0}", alt = "{0:#}")]
+/// struct Alternate(Byte);
+///
+/// #[derive(Display)]
+/// #[display(inner)]
+/// struct Inner(Byte);
+///
+/// #[derive(Display)]
+/// enum Value {
+///     #[display("{0}")]
+///     Plain(Byte),
+///     #[display(inner)]
+///     Inner(Byte),
+/// }
+///
+/// assert_eq!(format!("{:#}", Plain(Byte(255))), "255");
+/// assert_eq!(format!("{:#}", Alternate(Byte(255))), "0xff");
+/// assert_eq!(format!("{:#}", Inner(Byte(255))), "0xff");
+/// assert_eq!(format!("{:#}", Value::Plain(Byte(255))), "255");
+/// assert_eq!(format!("{:#}", Value::Inner(Byte(255))), "0xff");
+/// ```
+///
 /// Using inner enum variant representation, defaulting to the variant name
 /// if the variant does not have inner data:
 /// ```
@@ -277,7 +1083,36 @@ use syn::DeriveInput;
 ///     "127.0.0.1"
 /// );
 /// ```
-#[proc_macro_derive(Display, attributes(display))]
+///
+/// `inner` may also be given to a single-field variant, delegating display to
+/// the field with all formatter flags. When given to the enum, variants with
+/// multiple fields use the default representation:
+/// ```
+/// # #[macro_use] extern crate amplify_derive;
+/// #[derive(Clone, Copy, Debug, Display)]
+/// #[display(lowercase)]
+/// enum Amount {
+///     #[display(inner)]
+///     Sats { value: u64 },
+///     #[display(inner)]
+///     Btc(f64),
+///     Unknown,
+/// }
+///
+/// #[derive(Clone, Copy, Debug, Display)]
+/// #[display(inner)]
+/// enum Coord {
+///     X(i32),
+///     Point(i32, i32),
+/// }
+///
+/// assert_eq!(format!("[{:>5}]", Amount::Sats { value: 42 }), "[   42]");
+/// assert_eq!(format!("{:.3}", Amount::Btc(0.1)), "0.100");
+/// assert_eq!(format!("{}", Amount::Unknown), "unknown");
+/// assert_eq!(format!("{:+}", Coord::X(5)), "+5");
+/// assert_eq!(format!("{}", Coord::Point(1, 2)), "Point(..)");
+/// ```
+#[proc_macro_derive(Display, attributes(display, debug, amplify_crate))]
 pub fn derive_display(input: TokenStream) -> TokenStream {
     let derive_input = parse_macro_input!(input as DeriveInput);
     display::inner(derive_input)
@@ -285,18 +1120,187 @@ pub fn derive_display(input: TokenStream) -> TokenStream {
         .into()
 }
 
-/// Error derive macro works to the full extend only when other derive macros
-/// are used. With `#[derive(Display)]` and `[display(doc_comments)]` it uses
-/// doc comments for generating error descriptions; with `#[derive(From)]` it
-/// may automatically implement transofrations from other error types.
+/// Derives [`FromStr`] parsing strings produced by the [`Display`] derive
+/// using the same `#[display(...)]` attributes, ensuring that both
+/// implementations round trip.
+///
+/// Parsing is supported for display formats which are string literals (in
+/// case of unit structs and fieldless enum variants) or contain a single
+/// placeholder for the only field of a newtype or a variant, like
+/// `#[display("id:{0}")]` or `#[display(inner)]`. Case transformations and
+/// `rename_all` are supported as well; other formats produce a compile-time
+/// error. Fields are parsed with their own [`FromStr`] implementation.
+/// Structures displayed with `base58`, `base32` or `base64` encodings are
+/// parsed by decoding the string and converting the bytes into the field type
+/// with [`TryFrom`]`<Vec<u8>>`.
+///
+/// The derive may be used without [`Display`] derive as well: in this case
+/// fieldless enum variants are parsed from their names, and newtype variants
+/// without `#[display]` attribute are tried to be parsed with the inner type
+/// [`FromStr`] implementation, in the order of their declaration.
+///
+/// The error type is generated by the macro, named after the type with
+/// `ParseError` suffix (like `ModeParseError` for `Mode`), and contains the
+/// string which failed to parse.
+///
+/// [`FromStr`]: core::str::FromStr
 ///
 /// # Example
 ///
 /// ```
 /// # #[macro_use] extern crate amplify_derive;
-/// #[derive(Debug, Display, Error)]
-/// #[display(doc_comments)]
-/// enum Error {
+/// # extern crate alloc;
+/// #[derive(Clone, Copy, PartialEq, Eq, Debug, Display, FromStr)]
+/// #[display(rename_all = "kebab-case")]
+/// enum Mode {
+///     ReadOnly,
+///     #[display("rw")]
+///     ReadWrite,
+///     #[display("port:{0}")]
+///     Port(u16),
+/// }
+///
+/// #[derive(Clone, Copy, PartialEq, Eq, Debug, Display, FromStr)]
+/// #[display(inner)]
+/// struct Height(u32);
+///
+/// for mode in [Mode::ReadOnly, Mode::ReadWrite, Mode::Port(8080)] {
+///     assert_eq!(mode.to_string().parse(), Ok(mode));
+/// }
+/// assert_eq!("read-only".parse(), Ok(Mode::ReadOnly));
+/// assert_eq!("port:x".parse::<Mode>(), Err(ModeParseError(String::from("port:x"))));
+/// assert_eq!("42".parse(), Ok(Height(42)));
+/// ```
+///
+/// Standalone use for identifiers which may be given either by name or by a
+/// number:
+///
+/// ```
+/// # #[macro_use] extern crate amplify_derive;
+/// # extern crate alloc;
+/// type Result<T> = std::result::Result<T, ChainParseError>;
+///
+/// #[derive(Clone, Copy, PartialEq, Eq, Debug, FromStr)]
+/// #[display(rename_all = "lowercase")]
+/// enum Chain {
+///     Mainnet,
+///     Testnet,
+///     Custom(u32),
+/// }
+///
+/// fn parse(s: &str) -> Result<Chain> { s.parse() }
+///
+/// assert_eq!(parse("testnet"), Ok(Chain::Testnet));
+/// assert_eq!(parse("42"), Ok(Chain::Custom(42)));
+/// let err = parse("signet").unwrap_err();
+/// assert_eq!(err.to_string(), "unrecognized Chain value `signet`");
+/// ```
+#[proc_macro_derive(FromStr, attributes(display, amplify_crate))]
+pub fn derive_from_str(input: TokenStream) -> TokenStream {
+    let derive_input = parse_macro_input!(input as DeriveInput);
+    display::inner_from_str(derive_input)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
+
+/// Derives `variant_name` method returning static name of the enum variant,
+/// which does not include any variant data. This is useful for metrics
+/// labels and structured logging, where [`Display`] output may contain
+/// dynamic data.
+///
+/// The name respects `#[display]` attributes: variants displayed as a string
+/// literal are named with that literal, and case transformations (given
+/// either with `rename_all` or with a case like `#[display(lowercase)]`) are
+/// applied to the variant identifier. Variants having other display formats
+/// are named after their identifier.
+///
+/// # Example
+///
+/// ```
+/// # #[macro_use] extern crate amplify_derive;
+/// #[derive(Display, VariantName)]
+/// #[display(rename_all = "snake_case")]
+/// enum Request {
+///     GetInfo,
+///     #[display("tx")]
+///     SendTransaction(Vec<u8>),
+///     #[display("block #{height}")]
+///     GetBlock { height: u32 },
+/// }
+///
+/// assert_eq!(Request::GetInfo.variant_name(), "get_info");
+/// assert_eq!(Request::SendTransaction(vec![0]).variant_name(), "tx");
+/// let request = Request::GetBlock { height: 1 };
+/// assert_eq!(request.to_string(), "block #1");
+/// assert_eq!(request.variant_name(), "get_block");
+///
+/// #[derive(VariantName)]
+/// #[display(uppercase)]
+/// enum Level {
+///     Info,
+///     Warn(String),
+/// }
+///
+/// assert_eq!(Level::Warn(String::from("low disk")).variant_name(), "WARN");
+/// ```
+#[proc_macro_derive(VariantName, attributes(display, amplify_crate))]
+pub fn derive_variant_name(input: TokenStream) -> TokenStream {
+    let derive_input = parse_macro_input!(input as DeriveInput);
+    display::inner_variant_name(derive_input)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
+
+/// Implements `serde` `Serialize` and `Deserialize` traits using [`Display`]
+/// and [`FromStr`] implementations of the type, such that it is serialized as
+/// a plain string (the pattern known as `serde_with::DisplayFromStr`). This is
+/// convenient for identifiers and other types already using `Display`,
+/// `FromStr` or `Wrapper` derives.
+///
+/// Requires `serde` feature; the user crate must depend on `serde`.
+/// Deserialization errors are reported with the [`FromStr`] error text.
+///
+/// # Example
+///
+#[cfg_attr(feature = "serde", doc = "```")]
+#[cfg_attr(not(feature = "serde"), doc = "```ignore")]
+/// # #[macro_use] extern crate amplify_derive;
+/// type Result<T> = std::result::Result<T, serde_json::Error>;
+///
+/// #[derive(Clone, PartialEq, Eq, Debug, Display, FromStr, DisplayFromStrSerde)]
+/// #[display(inner)]
+/// struct NodeId(u64);
+///
+/// fn load(json: &str) -> Result<NodeId> { serde_json::from_str(json) }
+///
+/// let json = serde_json::to_string(&NodeId(42)).unwrap();
+/// assert_eq!(json, r#""42""#);
+/// assert_eq!(load(&json).unwrap(), NodeId(42));
+/// let err = load(r#""node""#).unwrap_err();
+/// assert!(err.to_string().starts_with("unrecognized NodeId value `node`"));
+/// ```
+///
+/// [`FromStr`]: core::str::FromStr
+#[proc_macro_derive(DisplayFromStrSerde, attributes(amplify_crate))]
+pub fn derive_display_from_str_serde(input: TokenStream) -> TokenStream {
+    let derive_input = parse_macro_input!(input as DeriveInput);
+    serde_str::inner(derive_input)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
+
+/// Error derive macro works to the full extend only when other derive macros
+/// are used. With `#[derive(Display)]` and `[display(doc_comments)]` it uses
+/// doc comments for generating error descriptions; with `#[derive(From)]` it
+/// may automatically implement transofrations from other error types.
+///
+/// # Example
+///
+/// ```
+/// # #[macro_use] extern crate amplify_derive;
+/// #[derive(Debug, Display, Error)]
+/// #[display(doc_comments)]
+/// enum Error {
 ///     /// I/O operation error
 ///     Io,
 ///     /// Math overflow
@@ -309,7 +1313,414 @@ pub fn derive_display(input: TokenStream) -> TokenStream {
 /// assert_eq!(format!("{}", Error::Overflow), "Math overflow");
 /// assert_eq!(format!("{}", Error::ZeroDivision(2)), "Zero division with 2");
 /// ```
-#[proc_macro_derive(Error)]
+///
+/// A field marked with `#[source]` attribute, or a field named `source`, is
+/// returned by [`std::error::Error::source`]. The field may be an error type,
+/// a boxed error trait object or an `Option` of them:
+///
+/// ```
+/// # #[macro_use] extern crate amplify_derive;
+/// use std::error::Error as _;
+/// use std::io;
+///
+/// #[derive(Debug, Display, Error)]
+/// #[display("failed to load config")]
+/// struct ConfigError {
+///     source: io::Error,
+/// }
+///
+/// #[derive(Debug, Display, Error)]
+/// #[display(doc_comments)]
+/// enum Error {
+///     /// configuration error
+///     Config(#[source] ConfigError),
+///     /// plugin {name} failed
+///     Plugin {
+///         name: &'static str,
+///         #[source]
+///         cause: Option<Box<dyn std::error::Error + Send + Sync>>,
+///     },
+///     /// timeout
+///     Timeout,
+/// }
+///
+/// let io = io::Error::new(io::ErrorKind::NotFound, "no file");
+/// let err = Error::Config(ConfigError { source: io });
+/// let source = err.source().unwrap();
+/// assert_eq!(source.to_string(), "failed to load config");
+/// assert_eq!(source.source().unwrap().to_string(), "no file");
+///
+/// let cause = Box::<dyn std::error::Error + Send + Sync>::from("crashed");
+/// let err = Error::Plugin { name: "tor", cause: Some(cause) };
+/// assert_eq!(err.source().unwrap().to_string(), "crashed");
+/// assert!(Error::Plugin { name: "tor", cause: None }.source().is_none());
+/// assert!(Error::Timeout.source().is_none());
+/// ```
+///
+/// A field named `source` must implement `Error` as well; for fields having
+/// other types the type (or the enum variant) should be marked with
+/// `#[error(no_source)]`:
+///
+/// ```
+/// # #[macro_use] extern crate amplify_derive;
+/// use std::error::Error as _;
+///
+/// #[derive(Debug, Display, Error)]
+/// #[display("{source} failed")]
+/// #[error(no_source)]
+/// struct ServiceError {
+///     source: String,
+/// }
+///
+/// let err = ServiceError { source: "storage".to_owned() };
+/// assert_eq!(err.to_string(), "storage failed");
+/// assert!(err.source().is_none());
+/// ```
+///
+/// Together with `#[derive(From)]` the single field of a variant converted
+/// with `#[from]` attribute becomes the error source automatically:
+///
+/// ```
+/// # #[macro_use] extern crate amplify_derive;
+/// use std::error::Error as _;
+/// use std::num::ParseIntError;
+///
+/// #[derive(Debug, Display, From, Error)]
+/// #[display(doc_comments)]
+/// enum Error {
+///     /// invalid number
+///     #[from]
+///     Number(ParseIntError),
+///     /// invalid utf-8 string
+///     Utf8(#[from] std::str::Utf8Error),
+///     /// value is out of range
+///     Range,
+/// }
+///
+/// let err = Error::from("x".parse::<u8>().unwrap_err());
+/// assert_eq!(err.source().unwrap().to_string(), "invalid digit found in string");
+/// let err = Error::from(std::str::from_utf8(&[0xff]).unwrap_err());
+/// assert!(err.source().is_some());
+/// assert!(Error::Range.source().is_none());
+/// ```
+///
+/// Such a field must implement `Error`. Conversions from other types, like
+/// strings or numbers, require `#[error(no_source)]` given to the variant or
+/// to the whole enum, which disables the automatic source detection (fields
+/// marked with `#[source]` are still used):
+///
+/// ```
+/// # #[macro_use] extern crate amplify_derive;
+/// use std::error::Error as _;
+///
+/// #[derive(Debug, Display, From, Error)]
+/// #[display(doc_comments)]
+/// enum Error {
+///     /// invalid number
+///     Number(#[from] std::num::ParseIntError),
+///     /// {0}
+///     #[error(no_source)]
+///     Msg(#[from] String),
+///     /// error code {0}
+///     #[error(no_source)]
+///     Code(#[from] u8),
+/// }
+///
+/// assert!(Error::from("x".parse::<u8>().unwrap_err()).source().is_some());
+/// let err = Error::from("failure".to_owned());
+/// assert_eq!(err.to_string(), "failure");
+/// assert!(err.source().is_none());
+/// assert!(Error::from(5u8).source().is_none());
+/// ```
+///
+/// Numeric error codes may be given with `#[error(code = ...)]` attribute to
+/// enum variants (or to the enum, providing code for the variants without
+/// their own one), generating `code` method. With `exit_code` argument of the
+/// type-level attribute the codes are also converted into
+/// [`std::process::ExitCode`]:
+///
+#[cfg_attr(feature = "std", doc = "```")]
+#[cfg_attr(not(feature = "std"), doc = "```ignore")]
+/// # #[macro_use] extern crate amplify_derive;
+/// use std::process::ExitCode;
+///
+/// #[derive(Debug, Display, Error)]
+/// #[display(doc_comments)]
+/// #[error(code = 1, exit_code)]
+/// enum CliError {
+///     /// configuration file is missing
+///     #[error(code = 78)]
+///     NoConfig,
+///     /// permission denied for {0}
+///     #[error(code = 77)]
+///     Permission(String),
+///     /// internal failure
+///     Internal,
+/// }
+///
+/// assert_eq!(CliError::NoConfig.code(), 78);
+/// assert_eq!(CliError::Permission(String::from("/etc")).code(), 77);
+/// assert_eq!(CliError::Internal.code(), 1);
+/// assert_eq!(ExitCode::from(&CliError::NoConfig), ExitCode::from(78));
+/// ```
+///
+/// Errors are converted into [`std::io::Error`] when `io_kind` argument is
+/// given to the type or to some of enum variants; variants without it use
+/// `ErrorKind::Other`, or the kind given to the enum. With `from_io` argument
+/// the enum is also converted from [`std::io::Error`]: unit variants are
+/// matched by their kinds, and all other errors are wrapped into the variant
+/// marked with `io_other`:
+///
+#[cfg_attr(feature = "std", doc = "```")]
+#[cfg_attr(not(feature = "std"), doc = "```ignore")]
+/// # #[macro_use] extern crate amplify_derive;
+/// use std::io;
+///
+/// #[derive(Debug, Display, Error)]
+/// #[display(doc_comments)]
+/// #[error(from_io)]
+/// enum StoreError {
+///     /// record not found
+///     #[error(io_kind = NotFound)]
+///     NotFound,
+///     /// access to {0} is denied
+///     #[error(io_kind = PermissionDenied)]
+///     Denied(String),
+///     /// I/O error: {0}
+///     #[error(io_other)]
+///     Io(io::Error),
+/// }
+///
+/// let err = io::Error::from(StoreError::Denied(String::from("db")));
+/// assert_eq!(err.kind(), io::ErrorKind::PermissionDenied);
+/// assert_eq!(err.to_string(), "access to db is denied");
+///
+/// let err = StoreError::from(io::Error::from(io::ErrorKind::NotFound));
+/// assert!(matches!(err, StoreError::NotFound));
+/// let err = StoreError::from(io::Error::new(io::ErrorKind::TimedOut, "timeout"));
+/// assert_eq!(err.to_string(), "I/O error: timeout");
+/// ```
+///
+/// With `#[amplify_crate(no_std)]` attribute, or when the `std` feature of
+/// this crate is disabled, [`core::error::Error`] is implemented instead, and the
+/// conversion into `String` is not generated. Since `core::error::Error` is
+/// stable only since Rust 1.81, with older compilers the trait implementation
+/// is skipped in this mode. Features requiring `std` (backtraces, exit codes
+/// and `io::Error` conversions) are not available in this mode:
+///
+/// ```
+/// # #[macro_use] extern crate amplify_derive;
+/// use core::error::Error as _;
+///
+/// #[derive(Debug, Display, Error)]
+/// #[display("sensor is not responding")]
+/// #[amplify_crate(no_std)]
+/// struct SensorTimeout;
+///
+/// #[derive(Debug, Display, Error)]
+/// #[display(doc_comments)]
+/// #[amplify_crate(no_std)]
+/// enum DeviceError {
+///     /// sensor failure
+///     Sensor(#[source] SensorTimeout),
+///     /// device is busy
+///     Busy,
+/// }
+///
+/// let err = DeviceError::Sensor(SensorTimeout);
+/// assert_eq!(err.source().unwrap().to_string(), "sensor is not responding");
+/// ```
+///
+/// With `backtrace` feature of this crate a field of type
+/// [`std::backtrace::Backtrace`] (or an `Option` of it) marked with
+/// `#[backtrace]` attribute is provided by `Error::provide`. This requires
+/// nightly compiler with `error_generic_member_access` feature enabled:
+///
+#[cfg_attr(all(feature = "backtrace", nightly), doc = "```")]
+#[cfg_attr(not(all(feature = "backtrace", nightly)), doc = "```ignore")]
+/// #![feature(error_generic_member_access)]
+/// # #[macro_use] extern crate amplify_derive;
+/// use std::backtrace::Backtrace;
+///
+/// #[derive(Debug, Display, Error)]
+/// #[display("connection lost")]
+/// struct ConnectionLost {
+///     #[backtrace]
+///     trace: Backtrace,
+/// }
+///
+/// #[derive(Debug, Display, Error)]
+/// #[display(doc_comments)]
+/// enum SyncError {
+///     /// peer is gone
+///     PeerGone(#[backtrace] Option<Backtrace>),
+/// }
+///
+/// let err = ConnectionLost { trace: Backtrace::capture() };
+/// assert!(std::error::request_ref::<Backtrace>(&err).is_some());
+/// let err = SyncError::PeerGone(Some(Backtrace::capture()));
+/// assert!(std::error::request_ref::<Backtrace>(&err).is_some());
+/// let err = SyncError::PeerGone(None);
+/// assert!(std::error::request_ref::<Backtrace>(&err).is_none());
+/// ```
+///
+/// With `#[error(constructors)]` attribute enum variants get inherent
+/// constructors named after the variants in snake case, which take all the
+/// fields (converting them with [`Into`]) except `#[backtrace]` fields, which
+/// are captured. A field marked with `#[error(context)]` is initialized with
+/// its default value and may be set with the generated `context` method:
+///
+/// ```
+/// # #[macro_use] extern crate amplify_derive;
+/// use std::io;
+///
+/// #[derive(Debug, Display, Error)]
+/// #[display(doc_comments)]
+/// #[error(constructors)]
+/// enum ConfigError {
+///     /// unable to read {path} while {context}: {source}
+///     Io {
+///         path: String,
+///         source: io::Error,
+///         #[error(context)]
+///         context: String,
+///     },
+///     /// missing value for {0}
+///     MissingValue(String),
+/// }
+///
+/// let err = ConfigError::io("/etc/node.toml", io::Error::from(io::ErrorKind::NotFound))
+///     .context("loading settings");
+/// assert_eq!(
+///     err.to_string(),
+///     "unable to read /etc/node.toml while loading settings: entity not found"
+/// );
+/// assert_eq!(ConfigError::missing_value("port").to_string(), "missing value for port");
+/// ```
+///
+/// `#[error(kind)]` attribute generates fieldless enum with the same variants
+/// and `Kind` suffix in its name, which is displayed and parsed as the
+/// variant names, and `kind` method returning the kind of the error:
+///
+#[cfg_attr(feature = "std", doc = "```")]
+#[cfg_attr(not(feature = "std"), doc = "```ignore")]
+/// # #[macro_use] extern crate amplify_derive;
+/// type Result<T> = std::result::Result<T, RpcError>;
+///
+/// #[derive(Debug, Display, Error)]
+/// #[display(doc_comments)]
+/// #[error(kind)]
+/// enum RpcError {
+///     /// method {0} is not found
+///     NotFound(String),
+///     /// request {id} timed out
+///     Timeout { id: u64 },
+///     /// server is overloaded
+///     Overloaded,
+/// }
+///
+/// fn call(id: u64) -> Result<()> { Err(RpcError::Timeout { id }) }
+///
+/// let err = call(5).unwrap_err();
+/// assert_eq!(err.kind(), RpcErrorKind::Timeout);
+/// assert_eq!(RpcError::NotFound(String::from("ping")).kind().to_string(), "NotFound");
+/// assert_eq!("Overloaded".parse(), Ok(RpcErrorKind::Overloaded));
+/// assert!("Unknown".parse::<RpcErrorKind>().is_err());
+/// ```
+///
+/// For generic types the derived implementations require the type itself to
+/// implement `Debug` and `Display`, and source fields depending on the type
+/// parameters to implement `Error`. These bounds can be replaced with custom
+/// ones using `#[error(bound = "...")]` attribute:
+///
+/// ```
+/// # #[macro_use] extern crate amplify_derive;
+/// use std::error::Error as _;
+/// use std::fmt::{self, Debug, Display, Formatter};
+///
+/// #[derive(Debug, Display, Error)]
+/// #[display("transport failure: {0}")]
+/// struct TransportError<E>(#[source] E);
+///
+/// #[derive(Debug, Error)]
+/// #[error(bound = "T: Debug")]
+/// struct Rejected<T>(T);
+///
+/// impl<T: Debug> Display for Rejected<T> {
+///     fn fmt(&self, f: &mut Formatter) -> fmt::Result { write!(f, "rejected {:?}", self.0) }
+/// }
+///
+/// let err = TransportError(std::io::Error::from(std::io::ErrorKind::TimedOut));
+/// assert_eq!(err.to_string(), "transport failure: timed out");
+/// assert_eq!(err.source().unwrap().to_string(), "timed out");
+/// assert_eq!(Rejected(vec![1u8]).to_string(), "rejected [1]");
+/// ```
+///
+/// `#[error(aggregate)]` attribute turns a newtype over `Vec` of errors into
+/// an accumulator of multiple errors. It generates `Display` (so it must not
+/// be derived) listing the errors with their numbers, `FromIterator` and
+/// `Extend` for the inner error type, and `is_empty` and `into_result`
+/// methods:
+///
+#[cfg_attr(feature = "std", doc = "```")]
+#[cfg_attr(not(feature = "std"), doc = "```ignore")]
+/// # #[macro_use] extern crate amplify_derive;
+/// #[derive(Debug, Display, Error)]
+/// #[display(doc_comments)]
+/// enum FieldError {
+///     /// field {0} is required
+///     Missing(&'static str),
+///     /// field {0} is too long
+///     TooLong(&'static str),
+/// }
+///
+/// #[derive(Debug, Default, Error)]
+/// #[error(aggregate)]
+/// struct ValidationErrors(Vec<FieldError>);
+///
+/// type Result<T> = std::result::Result<T, ValidationErrors>;
+///
+/// fn validate(errors: ValidationErrors) -> Result<()> { errors.into_result() }
+///
+/// let mut errors = ValidationErrors::default();
+/// assert!(errors.is_empty());
+/// errors.extend(vec![FieldError::Missing("name"), FieldError::TooLong("email")]);
+/// assert_eq!(errors.to_string(), "1. field name is required\n2. field email is too long");
+/// assert!(validate(errors).is_err());
+///
+/// let errors: ValidationErrors = None.into_iter().collect();
+/// assert!(validate(errors).is_ok());
+/// ```
+///
+/// With `anyhow` feature of this crate `#[error(anyhow)]` attribute generates
+/// `into_anyhow` method converting the error into `anyhow::Error` and
+/// `downcast_variant` function recovering the typed error from it. The crate
+/// using the attribute must depend on `anyhow`:
+///
+#[cfg_attr(feature = "anyhow", doc = "```")]
+#[cfg_attr(not(feature = "anyhow"), doc = "```ignore")]
+/// # #[macro_use] extern crate amplify_derive;
+/// #[derive(Debug, Display, Error)]
+/// #[display(doc_comments)]
+/// #[error(anyhow)]
+/// enum StorageError {
+///     /// database is locked
+///     Locked,
+///     /// record {0} is not found
+///     NotFound(u64),
+/// }
+///
+/// let err: anyhow::Error = StorageError::NotFound(7).into_anyhow();
+/// let err = err.context("loading user");
+/// assert_eq!(err.to_string(), "loading user");
+/// assert!(matches!(StorageError::downcast_variant(&err), Some(StorageError::NotFound(7))));
+///
+/// fn open() -> anyhow::Result<()> { Err(StorageError::Locked)? }
+/// let err = open().unwrap_err();
+/// assert_eq!(err.to_string(), "database is locked");
+/// assert!(matches!(StorageError::downcast_variant(&err), Some(StorageError::Locked)));
+/// ```
+#[proc_macro_derive(Error, attributes(error, source, backtrace, amplify_crate))]
 pub fn derive_error(input: TokenStream) -> TokenStream {
     let derive_input = parse_macro_input!(input as DeriveInput);
     error::inner(derive_input)
@@ -317,6 +1728,49 @@ pub fn derive_error(input: TokenStream) -> TokenStream {
         .into()
 }
 
+/// Derives [`macro@Display`], [`macro@Error`] and [`macro@From`] at once,
+/// making definition of a new error type a single annotation.
+///
+/// Unless the type has its own `#[display]` attribute, it is displayed using
+/// doc comments, as with `#[display(doc_comments)]`. All attributes of the
+/// bundled derives are supported; fields converted with bare `#[from]`
+/// attribute are used as error sources.
+///
+/// # Example
+///
+/// ```
+/// # #[macro_use] extern crate amplify_derive;
+/// use std::error::Error as _;
+/// use std::{fmt, io};
+///
+/// #[derive(Debug, ErrorBundle)]
+/// enum StorageError {
+///     /// I/O error: {0}
+///     #[from]
+///     Io(io::Error),
+///     /// record {0} is not found
+///     NotFound(u64),
+///     /// formatting failure
+///     Fmt(#[from] fmt::Error),
+/// }
+///
+/// let err = StorageError::from(io::Error::from(io::ErrorKind::NotFound));
+/// assert_eq!(err.to_string(), "I/O error: entity not found");
+/// assert!(err.source().is_some());
+/// assert_eq!(StorageError::NotFound(5).to_string(), "record 5 is not found");
+/// assert!(StorageError::from(fmt::Error).source().is_some());
+/// ```
+#[proc_macro_derive(
+    ErrorBundle,
+    attributes(display, debug, error, source, backtrace, from, into, amplify_crate)
+)]
+pub fn derive_error_bundle(input: TokenStream) -> TokenStream {
+    let derive_input = parse_macro_input!(input as DeriveInput);
+    error::inner_bundle(derive_input)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
+
 /// Implements [`From`] trait for the whole entity and/or its separate fields.
 /// Works well with `#[derive(Error)]` and, in many cases may require
 /// [`Default`] implementation (for details, pls see Examples below)
@@ -366,6 +1820,364 @@ pub fn derive_error(input: TokenStream) -> TokenStream {
 /// pub struct Wrapper(u32, i16);
 /// ```
 ///
+/// The source value may be transformed with a function given in `map`
+/// argument before it is stored in the field; the function result is
+/// converted into the field type with [`Into`]:
+///
+/// ```
+/// # #[macro_use] extern crate amplify_derive;
+/// use std::path::Path;
+///
+/// fn file_name(path: &Path) -> String {
+///     path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default()
+/// }
+///
+/// #[derive(Debug, PartialEq, From)]
+/// #[from(&Path, map = file_name)]
+/// #[from(&str, map = str::to_lowercase)]
+/// pub struct FileName(String);
+///
+/// assert_eq!(FileName::from(Path::new("/etc/hosts")), FileName(String::from("hosts")));
+/// assert_eq!(FileName::from("README.md"), FileName(String::from("readme.md")));
+/// ```
+///
+/// In tuple structures and variants the attribute may be given to any field,
+/// with all other fields initialized with their default values in their
+/// positions:
+///
+/// ```
+/// # #[macro_use] extern crate amplify_derive;
+/// #[derive(Debug, PartialEq, From)]
+/// pub struct Color(u8, #[from] u16, u8);
+///
+/// #[derive(Debug, PartialEq, From)]
+/// pub enum Shape {
+///     Circle(u32),
+///     Rect(u32, #[from] (u32, u32), bool),
+/// }
+///
+/// assert_eq!(Color::from(500u16), Color(0, 500, 0));
+/// assert_eq!(Shape::from((3, 4)), Shape::Rect(0, (3, 4), false));
+/// ```
+///
+/// The fields which are not converted are initialized with [`Default`]
+/// implementation of the structure, or, for enum variants, with [`Default`]
+/// implementations of the field types. If some of these fields are marked with
+/// `#[from(default = expr)]` or `#[from(default)]` attribute, the fields are
+/// initialized individually with the given expression or with their own
+/// default values, so the structure doesn't need to implement [`Default`].
+/// String literals given as the default expression are converted into the
+/// field type:
+///
+/// ```
+/// # #[macro_use] extern crate amplify_derive;
+/// use std::time::Duration;
+///
+/// #[derive(Debug, PartialEq, From)]
+/// pub struct Request {
+///     #[from]
+///     url: String,
+///     #[from(default = Duration::from_secs(30))]
+///     timeout: Duration,
+///     #[from(default)]
+///     retries: u8,
+///     #[from(default = "GET")]
+///     method: String,
+///     #[from(default = -0.5)]
+///     priority: f32,
+/// }
+///
+/// assert_eq!(Request::from(String::from("https://example.com")), Request {
+///     url: String::from("https://example.com"),
+///     timeout: Duration::from_secs(30),
+///     retries: 0,
+///     method: String::from("GET"),
+///     priority: -0.5,
+/// });
+/// ```
+///
+/// With `fields` argument the conversion from another structure moves each
+/// of its fields into the same-named field, converting them with [`Into`].
+/// A field may take the value from a differently named field of the source
+/// with `#[from(rename = other_name)]` or be initialized with its default
+/// value with `#[from(default)]`:
+///
+/// ```
+/// # #[macro_use] extern crate amplify_derive;
+/// pub struct UserDto {
+///     pub login: String,
+///     pub mail: String,
+///     pub age: u8,
+/// }
+///
+/// #[derive(Debug, PartialEq, From)]
+/// #[from(UserDto, fields)]
+/// pub struct User {
+///     login: String,
+///     #[from(rename = mail)]
+///     email: String,
+///     age: u32,
+///     #[from(default)]
+///     verified: bool,
+/// }
+///
+/// let dto = UserDto {
+///     login: String::from("alice"),
+///     mail: String::from("alice@example.com"),
+///     age: 33,
+/// };
+/// assert_eq!(User::from(dto), User {
+///     login: String::from("alice"),
+///     email: String::from("alice@example.com"),
+///     age: 33,
+///     verified: false,
+/// });
+/// ```
+///
+/// Types holding borrowed data may be converted from borrowed sources. Elided
+/// lifetimes of the source type are bound to the lifetime parameter of the
+/// type, if it has only one, and other lifetimes of the source type are added
+/// to the generated implementation:
+///
+/// ```
+/// # #[macro_use] extern crate amplify_derive;
+/// use std::borrow::Cow;
+///
+/// fn count(data: &[u8]) -> usize { data.len() }
+///
+/// #[derive(Debug, PartialEq, From)]
+/// pub enum Token<'a> {
+///     #[from]
+///     Text(Cow<'a, str>),
+///     #[from(&str)]
+///     Word(&'a str),
+///     #[from(&'b [u8], map = count)]
+///     Len(usize),
+/// }
+///
+/// let text = String::from("hello");
+/// assert_eq!(Token::from(text.as_str()), Token::Word("hello"));
+/// assert_eq!(Token::from(Cow::Borrowed("hi")), Token::Text(Cow::Borrowed("hi")));
+/// assert_eq!(Token::from(&b"abc"[..]), Token::Len(3));
+/// ```
+///
+/// Conversions depending on optional dependencies or platforms may be
+/// conditionally compiled with `cfg(...)` argument, like
+/// `#[from(tokio::io::Error, cfg(feature = "async"))]`, which puts the
+/// generated implementation under the same `#[cfg]` attribute:
+///
+/// ```
+/// # #[macro_use] extern crate amplify_derive;
+/// #[derive(Debug, PartialEq, From)]
+/// pub enum Signal {
+///     #[from(i32, cfg(unix))]
+///     Posix(i64),
+///     #[from(i32, cfg(not(unix)))]
+///     Other(i64),
+/// }
+///
+/// # #[cfg(unix)]
+/// assert_eq!(Signal::from(9), Signal::Posix(9));
+/// # #[cfg(not(unix))]
+/// assert_eq!(Signal::from(9), Signal::Other(9));
+/// ```
+///
+/// With `ref` argument, like `#[from(ref)]` or `#[from(SourceTy, ref)]`, the
+/// conversion from a reference to the source type is also implemented, which
+/// clones the value before converting it:
+///
+/// ```
+/// # #[macro_use] extern crate amplify_derive;
+/// #[derive(Clone, Debug, PartialEq)]
+/// pub struct Config {
+///     pub name: String,
+/// }
+///
+/// #[derive(Debug, PartialEq, From)]
+/// pub enum Source {
+///     #[from(ref)]
+///     Config(Config),
+///     #[from(&'static str, ref)]
+///     Path(String),
+/// }
+///
+/// let config = Config { name: String::from("node") };
+/// assert_eq!(Source::from(&config), Source::Config(config.clone()));
+/// assert_eq!(Source::from(config.clone()), Source::Config(config));
+/// assert_eq!(Source::from(&"/etc"), Source::Path(String::from("/etc")));
+/// ```
+///
+/// `#[from(generic)]` attribute given to a single-field entity converts from
+/// any type which may be converted into the field type with [`Into`]. Such
+/// conversion can't be combined with any other conversion of the same type:
+///
+/// ```
+/// # #[macro_use] extern crate amplify_derive;
+/// use std::borrow::Cow;
+///
+/// #[derive(Debug, PartialEq, From)]
+/// #[from(generic)]
+/// pub struct Alias(String);
+///
+/// assert_eq!(Alias::from("alice"), Alias(String::from("alice")));
+/// assert_eq!(Alias::from(String::from("bob")), Alias(String::from("bob")));
+/// assert_eq!(Alias::from(Cow::Borrowed("carol")), Alias(String::from("carol")));
+/// ```
+///
+/// Fields of `Box`, `Arc` or `Rc` types may be converted from the pointed
+/// type with `#[from(boxed)]` attribute, or from another type put into the
+/// pointer with `boxed` argument, like `#[from(SourceTy, boxed)]`. This also
+/// works for pointers to trait objects:
+///
+/// ```
+/// # #[macro_use] extern crate amplify_derive;
+/// use std::error::Error as StdError;
+/// use std::num::ParseIntError;
+/// use std::sync::Arc;
+/// use std::{fmt, io};
+///
+/// #[derive(Debug, From)]
+/// pub enum Error {
+///     #[from(boxed)]
+///     Io(Box<io::Error>),
+///
+///     #[from(fmt::Error, boxed)]
+///     #[from(ParseIntError, boxed)]
+///     Other(Arc<dyn StdError + Send + Sync>),
+/// }
+///
+/// assert!(matches!(Error::from(io::Error::from(io::ErrorKind::Other)), Error::Io(_)));
+/// match Error::from("x".parse::<u8>().unwrap_err()) {
+///     Error::Other(err) => assert_eq!(err.to_string(), "invalid digit found in string"),
+///     _ => unreachable!(),
+/// }
+/// ```
+///
+/// `#[from(tuple)]` attribute given to a structure or enum variant converts
+/// a tuple of all its field types, in the order of the fields, not requiring
+/// [`Default`] implementation:
+///
+/// ```
+/// # #[macro_use] extern crate amplify_derive;
+/// #[derive(Debug, PartialEq, From)]
+/// #[from(tuple)]
+/// pub struct Range {
+///     start: u64,
+///     end: u64,
+/// }
+///
+/// #[derive(Debug, PartialEq, From)]
+/// pub enum Entry {
+///     #[from(tuple)]
+///     Pair(String, u32),
+///     #[from(tuple)]
+///     Triple { key: String, value: u32, ttl: u8 },
+/// }
+///
+/// assert_eq!(Range::from((1, 5)), Range { start: 1, end: 5 });
+/// assert_eq!(Entry::from((String::from("a"), 1)), Entry::Pair(String::from("a"), 1));
+/// assert_eq!(Entry::from((String::from("b"), 2, 3)), Entry::Triple {
+///     key: String::from("b"),
+///     value: 2,
+///     ttl: 3
+/// });
+/// ```
+///
+/// The reverse conversions are generated with `#[into]` attribute, given to a
+/// single-field struct or variant, or to a specific field. The type is
+/// converted into the field type, or, if the attribute has an argument, into
+/// the given type using [`Into`] on the field value. Since an enum contains
+/// the field only in one of its variants, for enums [`TryFrom`] is
+/// implemented instead, returning the original value when the variant does
+/// not match:
+///
+/// ```
+/// # #[macro_use] extern crate amplify_derive;
+/// use std::convert::TryFrom;
+///
+/// type Result<T> = std::result::Result<T, Value>;
+///
+/// #[derive(From)]
+/// #[into]
+/// pub struct Height(u32);
+///
+/// #[derive(From)]
+/// pub struct Peer {
+///     #[into(String)]
+///     name: &'static str,
+///     #[into]
+///     port: u16,
+/// }
+///
+/// #[derive(Debug, PartialEq, From)]
+/// pub enum Value {
+///     #[from]
+///     #[into]
+///     Int(i64),
+///     Text {
+///         #[into]
+///         text: String,
+///     },
+/// }
+///
+/// assert_eq!(u32::from(Height(5)), 5);
+/// assert_eq!(String::from(Peer { name: "alice", port: 9735 }), "alice");
+/// assert_eq!(u16::from(Peer { name: "alice", port: 9735 }), 9735);
+/// fn text(value: Value) -> Result<String> { String::try_from(value) }
+///
+/// assert_eq!(i64::try_from(Value::from(7)), Ok(7));
+/// assert_eq!(text(Value::from(7)), Err(Value::Int(7)));
+/// ```
+///
+/// Conversion from [`core::convert::Infallible`], required by some generic
+/// APIs, is generated with `#[from(infallible)]` attribute, which may be given
+/// to enums as well:
+///
+/// ```
+/// # #[macro_use] extern crate amplify_derive;
+/// use std::convert::{Infallible, TryFrom};
+///
+/// #[derive(Debug, From)]
+/// #[from(infallible)]
+/// pub enum ParseError {
+///     #[from]
+///     Int(std::num::ParseIntError),
+/// }
+///
+/// fn parse<T: TryFrom<u64>>(value: u64) -> Result<T, ParseError>
+/// where ParseError: From<T::Error> {
+///     Ok(T::try_from(value)?)
+/// }
+///
+/// assert_eq!(parse::<u64>(5).unwrap(), 5);
+/// let _: fn(Infallible) -> ParseError = ParseError::from;
+/// ```
+///
+/// Generic types get conversions bounded only by what each of them requires:
+/// a conversion into a generic field requires the source type to be
+/// convertible into the field type, while conversions into other variants do
+/// not put any bounds onto the type parameters:
+///
+/// ```
+/// # #[macro_use] extern crate amplify_derive;
+/// # use std::sync::Mutex;
+/// #[derive(Debug, From)]
+/// pub enum Error<T> {
+///     #[from(String)]
+///     #[from(&'static str)]
+///     Parse(T),
+///
+///     #[from]
+///     Io(std::io::Error),
+/// }
+///
+/// let err: Error<String> = "invalid".into();
+/// assert!(matches!(err, Error::Parse(msg) if msg == "invalid"));
+/// // `Mutex<u8>` can't be constructed from `String`, but can hold I/O errors
+/// let err = Error::<Mutex<u8>>::from(std::io::Error::from(std::io::ErrorKind::Other));
+/// assert!(matches!(err, Error::Io(_)));
+/// ```
+///
 /// If you use rust nightly and `#![feature(never_type)]` for [`!`], you can
 /// even do the following:
 /// ```ignore
@@ -381,62 +2193,268 @@ pub fn derive_error(input: TokenStream) -> TokenStream {
 ///     NeverType,
 /// }
 ///
-/// # fn main () {
-/// # }
+/// # fn main () {
+/// # }
+/// ```
+///
+/// # Errors
+///
+/// Each source type may be converted only once, so using the same type in
+/// two variants is an error naming the type and pointing at both of its uses,
+/// which suggests keeping the conversion into the first variant:
+/// ```compile_fail
+/// # #[macro_use] extern crate amplify_derive;
+/// #[derive(From)]
+/// enum Error {
+///     #[from(std::io::Error)]
+///     Io,
+///     #[from(std::io::Error)]
+///     Network,
+/// }
+/// ```
+#[proc_macro_derive(From, attributes(from, into, amplify_crate))]
+pub fn derive_from(input: TokenStream) -> TokenStream {
+    let derive_input = parse_macro_input!(input as DeriveInput);
+    from::inner(derive_input)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
+
+/// Implements [`TryFrom`] trait for the whole entity and/or its separate
+/// fields, converting the source type into the field type with [`TryInto`].
+///
+/// The attribute `#[try_from(SourceTy)]` is given to a single-field struct, a
+/// single-field enum variant or to a specific field, like with `From` derive.
+/// Conversion errors are returned as `TryInto::Error` of the source type, or
+/// may be converted into another error type given with `error` argument:
+/// `#[try_from(SourceTy, error = ErrorTy)]`. Other fields must implement
+/// [`Default`].
+///
+/// # Example
+///
+/// ```
+/// # #[macro_use] extern crate amplify_derive;
+/// use std::convert::TryFrom;
+/// use std::num::TryFromIntError;
+///
+/// #[derive(Debug, PartialEq, Eq)]
+/// struct PortError;
+///
+/// type Result<T> = std::result::Result<T, PortError>;
+///
+/// impl From<TryFromIntError> for PortError {
+///     fn from(_: TryFromIntError) -> Self { PortError }
+/// }
+///
+/// #[derive(Debug, PartialEq, Eq, TryFrom)]
+/// #[try_from(u32, error = PortError)]
+/// #[try_from(i64)]
+/// struct Port(u16);
+///
+/// #[derive(Debug, PartialEq, Eq, TryFrom)]
+/// enum Limit {
+///     #[try_from(u64)]
+///     Bytes(usize),
+///     Records {
+///         #[try_from(i32)]
+///         count: u8,
+///         unbounded: bool,
+///     },
+/// }
+///
+/// fn port(value: u32) -> Result<Port> { Port::try_from(value) }
+///
+/// assert_eq!(port(8080), Ok(Port(8080)));
+/// assert_eq!(port(70000), Err(PortError));
+/// assert!(Port::try_from(-1i64).is_err());
+/// assert_eq!(Limit::try_from(1024u64), Ok(Limit::Bytes(1024)));
+/// assert_eq!(Limit::try_from(5i32), Ok(Limit::Records { count: 5, unbounded: false }));
+/// assert!(Limit::try_from(300i32).is_err());
+/// ```
+#[proc_macro_derive(TryFrom, attributes(try_from, amplify_crate))]
+pub fn derive_try_from(input: TokenStream) -> TokenStream {
+    let derive_input = parse_macro_input!(input as DeriveInput);
+    from::inner_try(derive_input)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
+
+/// Trait `amplify::AsAny` allows simple conversion of any type into a
+/// generic "thick" pointer `&dyn Any` (see [`::core::any::Any`]), that can be
+/// later converted back to the original type with a graceful failing for all
+/// other conversions. `AsAny` derive macro allows to implement this trait for
+/// arbitrary time without much hussle:
+///
+/// # Example
+///
+/// ```
+/// # #[macro_use] extern crate amplify_derive;
+/// extern crate amplify;
+/// use amplify::AsAny;
+///
+/// #[derive(AsAny, Copy, Clone, PartialEq, Eq, Debug)]
+/// struct Point {
+///     pub x: u64,
+///     pub y: u64,
+/// }
+///
+/// #[derive(AsAny, PartialEq, Debug)]
+/// struct Circle {
+///     pub radius: f64,
+///     pub center: Point,
+/// }
+///
+/// let mut point = Point { x: 1, y: 2 };
+/// let point_ptr = point.as_any();
+///
+/// let mut circle = Circle {
+///     radius: 18.,
+///     center: point,
+/// };
+/// let circle_ptr = circle.as_any();
+///
+/// assert_eq!(point_ptr.downcast_ref(), Some(&point));
+/// assert_eq!(circle_ptr.downcast_ref(), Some(&circle));
+/// assert_eq!(circle_ptr.downcast_ref::<Point>(), None);
+///
+/// let p = point_ptr.downcast_ref::<Point>().unwrap();
+/// assert_eq!(p.x, 1)
+/// ```
+#[proc_macro_derive(AsAny, attributes(amplify_crate))]
+pub fn derive_as_any(input: TokenStream) -> TokenStream {
+    let derive_input = parse_macro_input!(input as DeriveInput);
+    as_any::inner(derive_input)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
+
+/// Implements trait `amplify::AsAnyMut`, complementing [`macro@AsAny`] with
+/// mutable and owned conversions into [`::core::any::Any`]: `as_any_mut`
+/// method returning `&mut dyn Any` and `into_any` converting `Box<Self>` into
+/// `Box<dyn Any>`, such that values stored in heterogeneous collections can be
+/// mutated or taken out after the downcast.
+///
+/// Path to the crate providing the trait may be changed with
+/// `#[amplify_crate(path)]` attribute, which is required for amplify versions
+/// not having `AsAnyMut` trait.
+///
+/// # Example
+///
+/// ```
+/// # #[macro_use] extern crate amplify_derive;
+/// # extern crate alloc;
+/// use std::any::Any;
+///
+/// mod registry {
+///     # use std::any::Any;
+///     pub trait AsAnyMut {
+///         fn as_any_mut(&mut self) -> &mut dyn Any;
+///         fn into_any(self: Box<Self>) -> Box<dyn Any>;
+///     }
+/// }
+/// use registry::AsAnyMut;
+///
+/// #[derive(AsAnyMut, PartialEq, Debug)]
+/// #[amplify_crate(registry)]
+/// struct Counter(u32);
+///
+/// let mut items: Vec<Box<dyn AsAnyMut>> = vec![Box::new(Counter(0))];
+/// if let Some(counter) = items[0].as_any_mut().downcast_mut::<Counter>() {
+///     counter.0 += 1;
+/// }
+/// let counter = items.remove(0).into_any().downcast::<Counter>().unwrap();
+/// assert_eq!(*counter, Counter(1));
+/// ```
+#[proc_macro_derive(AsAnyMut, attributes(amplify_crate))]
+pub fn derive_as_any_mut(input: TokenStream) -> TokenStream {
+    let derive_input = parse_macro_input!(input as DeriveInput);
+    as_any::inner_mut(derive_input)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
+
+/// Derives enumeration of all variants for enums with fieldless variants:
+/// `ALL` constant array containing all variants in their declaration order,
+/// `COUNT` constant with the number of variants and `iter()` function
+/// returning iterator over the variants.
+///
+/// # Errors
+///
+/// Structures, unions and enums having variants with fields are not
+/// supported.
+///
+/// # Example
+///
+/// ```
+/// # #[macro_use] extern crate amplify_derive;
+/// #[derive(Copy, Clone, PartialEq, Eq, Debug, Display, EnumIter)]
+/// #[display(lowercase)]
+/// enum Level {
+///     Error,
+///     Warn,
+///     Info,
+/// }
+///
+/// const LEVELS: [Level; Level::COUNT] = Level::ALL;
+/// assert_eq!(LEVELS, [Level::Error, Level::Warn, Level::Info]);
+/// let names = Level::iter().map(|level| level.to_string()).collect::<Vec<_>>();
+/// assert_eq!(names, ["error", "warn", "info"]);
 /// ```
-#[proc_macro_derive(From, attributes(from))]
-pub fn derive_from(input: TokenStream) -> TokenStream {
+#[proc_macro_derive(EnumIter, attributes(amplify_crate))]
+pub fn derive_enum_iter(input: TokenStream) -> TokenStream {
     let derive_input = parse_macro_input!(input as DeriveInput);
-    from::inner(derive_input)
+    enum_iter::inner(derive_input)
         .unwrap_or_else(|e| e.to_compile_error())
         .into()
 }
 
-/// Trait `amplify::AsAny` allows simple conversion of any type into a
-/// generic "thick" pointer `&dyn Any` (see [`::core::any::Any`]), that can be
-/// later converted back to the original type with a graceful failing for all
-/// other conversions. `AsAny` derive macro allows to implement this trait for
-/// arbitrary time without much hussle:
+/// Derives conversions between fieldless enums with integer representation
+/// (given with `#[repr(u8)]`-like attribute) and their discriminant values:
+/// - `from_repr` const function returning `None` for unknown values;
+/// - [`TryFrom`] for the representation type, with the error type generated
+///   by the macro, named after the type with `ReprError` suffix (like
+///   `CommandReprError` for `Command`), and containing the unknown value;
+/// - [`From`] for the representation type, returning discriminant of the
+///   variant.
+///
+/// The error type implements [`std::error::Error`]; with
+/// `#[amplify_crate(no_std)]` attribute, or when the `std` feature of this
+/// crate is disabled,
+/// [`core::error::Error`] is implemented instead (with Rust 1.81 or later;
+/// older compilers skip the implementation).
+///
+/// # Errors
+///
+/// Structures, unions and enums having variants with fields or not having
+/// integer representation are not supported.
 ///
 /// # Example
 ///
 /// ```
 /// # #[macro_use] extern crate amplify_derive;
-/// extern crate amplify;
-/// use amplify::AsAny;
+/// use std::convert::TryFrom;
 ///
-/// #[derive(AsAny, Copy, Clone, PartialEq, Eq, Debug)]
-/// struct Point {
-///     pub x: u64,
-///     pub y: u64,
-/// }
+/// type Result<T> = std::result::Result<T, CommandReprError>;
 ///
-/// #[derive(AsAny, PartialEq, Debug)]
-/// struct Circle {
-///     pub radius: f64,
-///     pub center: Point,
+/// #[derive(Copy, Clone, PartialEq, Eq, Debug, FromRepr)]
+/// #[repr(u8)]
+/// enum Command {
+///     Ping = 0x01,
+///     Pong,
+///     Close = 0xFF,
 /// }
 ///
-/// let mut point = Point { x: 1, y: 2 };
-/// let point_ptr = point.as_any();
-///
-/// let mut circle = Circle {
-///     radius: 18.,
-///     center: point,
-/// };
-/// let circle_ptr = circle.as_any();
-///
-/// assert_eq!(point_ptr.downcast_ref(), Some(&point));
-/// assert_eq!(circle_ptr.downcast_ref(), Some(&circle));
-/// assert_eq!(circle_ptr.downcast_ref::<Point>(), None);
+/// fn command(value: u8) -> Result<Command> { Command::try_from(value) }
 ///
-/// let p = point_ptr.downcast_ref::<Point>().unwrap();
-/// assert_eq!(p.x, 1)
+/// assert_eq!(Command::from_repr(2), Some(Command::Pong));
+/// assert_eq!(command(0xFF), Ok(Command::Close));
+/// assert_eq!(command(7), Err(CommandReprError(7)));
+/// assert_eq!(u8::from(Command::Ping), 1);
 /// ```
-#[proc_macro_derive(AsAny)]
-pub fn derive_as_any(input: TokenStream) -> TokenStream {
+#[proc_macro_derive(FromRepr, attributes(from_repr, amplify_crate))]
+pub fn derive_from_repr(input: TokenStream) -> TokenStream {
     let derive_input = parse_macro_input!(input as DeriveInput);
-    as_any::inner(derive_input)
+    repr::inner(derive_input)
         .unwrap_or_else(|e| e.to_compile_error())
         .into()
 }
@@ -460,7 +2478,8 @@ pub fn derive_as_any(input: TokenStream) -> TokenStream {
 ///
 /// Attribute takes a list of arguments in form of verbatim literals:
 /// - `as_copy`: derives methods returning copy of the field value. Will error
-///   at compile time on types which does not implement `Copy`
+///   at compile time on types which does not implement `Copy`. `copy` is a
+///   shorthand for it.
 /// - `as_clone`: derives methods returning cloned value; will conflict with
 ///   `as_copy`. Errors at compile time on types which does not implement
 ///   `Clone`.
@@ -473,7 +2492,35 @@ pub fn derive_as_any(input: TokenStream) -> TokenStream {
 ///
 /// **Can be used**: at type and field level
 ///
-/// **Defaults to**: `as_ref`
+/// **Defaults to**: `as_copy` for fields of primitive types (integers, floats,
+/// `bool` and `char`); `as_ref` for the rest of the fields. Providing any of
+/// the `copy`, `as_copy`, `as_clone`, `as_ref` or `all` arguments at the type
+/// or field level disables detection of the primitive types.
+///
+/// ### `#[getter(deref)]`
+/// Makes borrowing methods (`as_ref` and `as_mut`) to return the dereferenced
+/// form of the field value instead of the owned container type: `&str` for
+/// `String`, `&[T]` for `Vec<T>`, `&T` for `Box<T>`, `&Path` for `PathBuf`,
+/// `&OsStr` for `OsString` and `Option<&T>` for `Option<T>` (dereferencing
+/// `T` if it is one of the types above). At the field level, errors on other
+/// field types; at the type level, is ignored for them.
+///
+/// **Can be used**: at type and field level
+///
+/// ### `#[getter(const)]`
+/// Makes derived methods `const fn`, such that they can be used in const
+/// context. Applies only to the methods which can be const: returning copy or
+/// (not dereferenced) reference; other methods are derived as non-const.
+///
+/// **Can be used**: at type and field level
+///
+/// ### `#[getter(into_parts)]`
+/// Additionally derives `into_parts` method, consuming the structure and
+/// returning a tuple of all its field values, and `from_parts` constructor
+/// taking such tuple. Errors for structures with conditionally compiled
+/// fields.
+///
+/// **Can be used**: at type level
 ///
 /// ### `#[getter(skip)]`
 /// Skips derivation of a all gettter methods for this field
@@ -485,12 +2532,31 @@ pub fn derive_as_any(input: TokenStream) -> TokenStream {
 ///
 /// **Can be used**: at type level
 ///
+/// ### `#[getter(suffix = "...")]`
+/// Defines suffix added to all derived getter method names after the base
+/// name and before the method-specific suffix (like `_mut`).
+///
+/// **Defaults to**: none (no suffix added)
+///
+/// **Can be used**: at type level
+///
 /// ### `#[getter(base_name = "...")]`
 /// Defines base name for the getter method. Base name is prefixed with prefix
-/// from a type-level getter `prefix` attribute (if the one is specified) and
-/// suffix, which is method-specific (see `methods` argument description above).
+/// and suffixed with suffix from a type-level getter `prefix` and `suffix`
+/// attributes (if specified), followed by the method-specific suffix (see
+/// `methods` argument description above).
 ///
-/// **Defaults to**: field name
+/// **Defaults to**: field name (without `r#` prefix for raw identifiers);
+/// field index for tuple structs, such that getters are named `_0`, `_1` etc,
+/// or `get_0` if the type-level prefix is `get_`
+///
+/// **Can be used**: at field level
+///
+/// ### `#[getter(name = "...")]`
+/// Renames the getter method, which is useful when the field name clashes with
+/// other inherent methods. Unlike `base_name`, the name is not prefixed with
+/// type-level `prefix`; method-specific suffixes are still added. Can't be
+/// combined with `base_name`.
 ///
 /// **Can be used**: at field level
 ///
@@ -499,10 +2565,10 @@ pub fn derive_as_any(input: TokenStream) -> TokenStream {
 /// Enums and units are not supported; attempt to derive `Getters` on them will
 /// result in a compile-time error.
 ///
-/// Deriving getters on unit structs and structs with unnamed fields (tupe
-/// structs) is not supported (since it's meaningless), and results in a error.
+/// Deriving getters on unit structs is not supported (since it's
+/// meaningless), and results in a error.
 ///
-/// Additionally to these two cases, macro errors on argument inconsistencies,
+/// Additionally to these cases, macro errors on argument inconsistencies,
 /// as described in the argument-specific sections.
 ///
 /// # Examples
@@ -528,6 +2594,143 @@ pub fn derive_as_any(input: TokenStream) -> TokenStream {
 /// assert_eq!(one.field(), 0);
 /// ```
 ///
+/// Fields of primitive types are returned by value, unless specified
+/// otherwise; other `Copy` types, like small enums, may use `copy` argument:
+/// ```
+/// # #[macro_use] extern crate amplify_derive;
+/// #[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+/// enum Network {
+///     #[default]
+///     Mainnet,
+///     Testnet,
+/// }
+///
+/// #[derive(Getters, Default)]
+/// struct Peer {
+///     port: u16,
+///     #[getter(as_ref)]
+///     score: f64,
+///     #[getter(copy)]
+///     network: Network,
+/// }
+///
+/// let peer = Peer::default();
+/// assert_eq!(peer.port(), 0);
+/// assert_eq!(peer.score(), &0.0);
+/// assert_eq!(peer.network(), Network::Mainnet);
+/// ```
+///
+/// Field documentation and `#[cfg(...)]` attributes are copied to the derived
+/// methods (as well as `#[cfg_attr(..., doc...)]` attributes), such that
+/// conditionally compiled fields get conditionally compiled getters:
+/// ```
+/// # #[macro_use] extern crate amplify_derive;
+/// #[derive(Getters, Setters, Default)]
+/// struct Node {
+///     /// Node alias
+///     alias: String,
+///     #[cfg(feature = "tor")]
+///     onion: String,
+/// }
+///
+/// let mut node = Node::default();
+/// node.set_alias(String::from("alice"));
+/// assert_eq!(node.alias(), "alice");
+/// ```
+///
+/// Fields named with raw identifiers, like `r#type`, are supported as well;
+/// getters with the keyword names are given raw identifiers:
+/// ```
+/// # #[macro_use] extern crate amplify_derive;
+/// #[derive(Getters, Default)]
+/// #[getter(prefix = "get_", suffix = "_field")]
+/// struct Record {
+///     r#type: String,
+///     #[getter(name = "record_id")]
+///     id: u64,
+///     #[getter(skip)]
+///     cache: Vec<u8>,
+/// }
+///
+/// let record = Record::default();
+/// assert_eq!(record.get_type_field(), "");
+/// assert_eq!(record.record_id(), 0);
+/// ```
+///
+/// Const getters can be used in const context:
+/// ```
+/// # #[macro_use] extern crate amplify_derive;
+/// #[derive(Getters)]
+/// #[getter(const)]
+/// struct Limits {
+///     max_size: usize,
+///     name: &'static str,
+/// }
+///
+/// const LIMITS: Limits = Limits {
+///     max_size: 1024,
+///     name: "default",
+/// };
+/// const MAX_SIZE: usize = LIMITS.max_size();
+/// static NAME: &&str = LIMITS.name();
+///
+/// assert_eq!(MAX_SIZE, 1024);
+/// assert_eq!(*NAME, "default");
+/// ```
+///
+/// Opaque structures may be decomposed into their parts and constructed back:
+/// ```
+/// # #[macro_use] extern crate amplify_derive;
+/// #[derive(Getters, Clone, PartialEq, Eq, Debug)]
+/// #[getter(into_parts)]
+/// pub struct Keypair {
+///     #[getter(skip)]
+///     secret: [u8; 32],
+///     public: Vec<u8>,
+/// }
+///
+/// let pair = Keypair::from_parts(([1u8; 32], vec![2]));
+/// assert_eq!(pair.public(), &vec![2]);
+/// assert_eq!(pair.clone().into_parts(), ([1u8; 32], vec![2]));
+/// ```
+///
+/// Tuple structs get getters named after the field index:
+/// ```
+/// # #[macro_use] extern crate amplify_derive;
+/// #[derive(Getters, Default)]
+/// struct Outpoint([u8; 32], u32, #[getter(name = "script")] Vec<u8>);
+///
+/// let outpoint = Outpoint::default();
+/// assert_eq!(outpoint._0(), &[0u8; 32]);
+/// assert_eq!(outpoint._1(), 0);
+/// assert!(outpoint.script().is_empty());
+/// ```
+///
+/// Borrowing getters may return dereferenced types, not exposing the owned
+/// containers used by the structure:
+/// ```
+/// # #[macro_use] extern crate amplify_derive;
+/// #[derive(Getters, Default)]
+/// #[getter(deref)]
+/// struct Request {
+///     url: String,
+///     #[getter(as_ref, as_mut)]
+///     body: Vec<u8>,
+///     comment: Option<String>,
+///     timeout: Option<u64>,
+///     retries: u8,
+/// }
+///
+/// let mut request = Request::default();
+/// let _: &mut [u8] = request.body_mut();
+/// let url: &str = request.url();
+/// let body: &[u8] = request.body();
+/// let comment: Option<&str> = request.comment();
+/// let timeout: Option<&u64> = request.timeout();
+/// assert_eq!((url, body, comment, timeout), ("", &[][..], None, None));
+/// assert_eq!(request.retries(), 0);
+/// ```
+///
 /// Important, that field-level arguments to override struct-level arguments:
 /// ```
 /// # #[macro_use] extern crate amplify_derive;
@@ -542,84 +2745,348 @@ pub fn derive_as_any(input: TokenStream) -> TokenStream {
 ///     pub(self) field: u8,
 /// }
 ///
-/// let mut other = Other::default();
-/// assert_eq!(other.vec(), &Vec::<u8>::default());
-/// assert_eq!(other.defaults(), String::from(""));
+/// let mut other = Other::default();
+/// assert_eq!(other.vec(), &Vec::<u8>::default());
+/// assert_eq!(other.defaults(), String::from(""));
+/// ```
+///
+/// Advanced use: please pay attention that `as_mut` on a struct level is not
+/// removed by the use of `as_copy` at field level.
+///
+/// ```
+/// # #[macro_use] extern crate amplify_derive;
+/// #[derive(Getters, Default)]
+/// #[getter(as_mut, prefix = "get_")]
+/// struct One {
+///     /// Contains byte representation of the data
+///     #[getter(all, base_name = "bytes")]
+///     vec: Vec<u8>,
+///
+///     defaults: String,
+///
+///     #[getter(as_copy)]
+///     pub flag: bool,
+///
+///     #[getter(skip)]
+///     pub(self) field: u8,
+/// }
+///
+/// let mut one = One::default();
+/// assert_eq!(one.get_bytes_ref(), &Vec::<u8>::default());
+/// *one.get_bytes_mut() = vec![0, 1, 2];
+/// assert_eq!(one.get_defaults(), "");
+/// assert_eq!(one.get_defaults_mut(), "");
+/// assert_eq!(one.get_bytes(), vec![0, 1, 2]);
+/// assert_eq!(one.get_flag(), bool::default());
+/// assert_eq!(one.get_flag_mut(), &mut bool::default());
+/// let flag = one.get_flag_mut();
+/// *flag = true;
+/// assert_eq!(one.get_flag(), true);
+/// assert_eq!(one.flag, one.get_flag());
+/// // method does not exist: assert_eq!(one.get_field(), u8::default());
+/// ```
+///
+/// this will end up in the following generated code:
+/// ```
+/// # struct One {
+/// #    vec: Vec<u8>,
+/// #    pub flag: bool,
+/// #    pub(self) field: u8,
+/// # }
+///
+/// impl One {
+///     #[doc = "Method cloning [`One::vec`] field.\n"]
+///     #[doc = " Contains byte representation of the data"]
+///     #[inline]
+///     pub fn get_bytes(&self) -> Vec<u8> { self.vec.clone() }
+///
+///     #[doc = "Method borrowing [`One::vec`] field.\n"]
+///     #[doc = " Contains byte representation of the data"]
+///     #[inline]
+///     pub fn get_bytes_ref(&self) -> &Vec<u8> { &self.vec }
+///
+///     #[doc = "Method returning mutable borrow of [`One::vec`] field.\n"]
+///     #[doc = " Contains byte representation of the data"]
+///     #[inline]
+///     pub fn get_bytes_mut(&mut self) -> &mut Vec<u8> { &mut self.vec }
+///
+///     #[doc = "Method returning copy of [`One::flag`] field.\n"]
+///     #[inline]
+///     pub fn get_flag(&self) -> bool { self.flag }
+///
+///     #[doc = "Method returning mutable borrow of [`One::flag`] field.\n"]
+///     #[inline]
+///     pub fn get_flag_mut(&mut self) -> &mut bool { &mut self.flag }
+/// }
+/// ```
+#[proc_macro_derive(Getters, attributes(getter, amplify_crate))]
+pub fn derive_getters(input: TokenStream) -> TokenStream {
+    let derive_input = parse_macro_input!(input as DeriveInput);
+    getters::derive(derive_input)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
+
+/// Derives setter methods for structures, complementing [`macro@Getters`].
+/// By default, for each named field a method `set_<field>(&mut self, value)`
+/// is generated, taking value of the field type.
+///
+/// # Attribute `#[setter(...)]`
+///
+/// Macro is provided with `#[setter]` attribute, which may be used on both
+/// type and field level. See following sections describing its arguments
+///
+/// ## Arguments
+///
+/// ### `#[setter(into)]`
+/// Makes setter to accept any value convertible into the field type with
+/// `impl Into<T>` argument.
+///
+/// **Can be used**: at type and field level
+///
+/// ### `#[setter(chain)]`
+/// Makes setter to return `&mut Self`, such that calls to setters may be
+/// chained.
+///
+/// **Can be used**: at type and field level
+///
+/// ### `#[setter(vis = "...")]`
+/// Defines visibility of the derived methods, like `pub(crate)`. Field-level
+/// value overrides the type-level one.
+///
+/// **Defaults to**: `pub`
+///
+/// **Can be used**: at type and field level
+///
+/// ### `#[setter(skip)]`
+/// Skips derivation of the setter for this field
+///
+/// **Can be used**: at field level
+///
+/// ### `#[setter(prefix = "...")]`
+/// Defines prefix added to all derived setter method names.
+///
+/// **Defaults to**: `set_`
+///
+/// **Can be used**: at type level
+///
+/// ### `#[setter(base_name = "...")]`
+/// Defines base name for the setter method, which is prefixed with prefix
+/// (see above).
+///
+/// **Defaults to**: field name
+///
+/// **Can be used**: at field level
+///
+/// # Errors
+///
+/// Deriving setters on enums, unions, unit and tuple structs results in a
+/// compile-time error.
+///
+/// # Example
+///
+/// ```
+/// # #[macro_use] extern crate amplify_derive;
+/// #[derive(Getters, Setters, Default)]
+/// #[setter(chain)]
+/// struct Config {
+///     #[setter(into)]
+///     name: String,
+///     port: u16,
+///     #[setter(base_name = "verbosity", vis = "pub(crate)")]
+///     verbose: bool,
+///     #[setter(skip)]
+///     id: u64,
+/// }
+///
+/// let mut config = Config::default();
+/// config.set_name("localhost").set_port(8080).set_verbosity(true);
+/// assert_eq!(config.name(), "localhost");
+/// assert_eq!(config.port(), 8080);
+/// assert_eq!(config.verbose(), true);
+/// assert_eq!(config.id(), 0);
+/// ```
+///
+/// Raw identifiers are stripped of their `r#` prefix in the method name:
+///
+/// ```
+/// # #[macro_use] extern crate amplify_derive;
+/// #[derive(Setters, Default)]
+/// #[setter(prefix = "")]
+/// struct Token {
+///     r#type: u8,
+///     r#match: bool,
+/// }
+///
+/// let mut token = Token::default();
+/// token.r#type(5);
+/// token.r#match(true);
+/// assert_eq!(token.r#type, 5);
+/// assert!(token.r#match);
+///
+/// #[derive(Setters, Default)]
+/// struct Node {
+///     r#type: u8,
+/// }
+///
+/// let mut node = Node::default();
+/// node.set_type(1);
+/// assert_eq!(node.r#type, 1);
+/// ```
+#[proc_macro_derive(Setters, attributes(setter, amplify_crate))]
+pub fn derive_setters(input: TokenStream) -> TokenStream {
+    let derive_input = parse_macro_input!(input as DeriveInput);
+    setters::derive(derive_input)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
+
+/// Derives [`Default`] implementation, where the default value of each field
+/// may be given with `#[default(...)]` attribute, unlike the standard library
+/// derive, which always uses the field type default.
+///
+/// # Attribute `#[default(...)]`
+///
+/// Applied to a field, takes an expression giving the default value for the
+/// field. If the expression is a string literal, it is converted into the field
+/// type with [`Into`], such that it can be used for `String` fields. Fields
+/// without the attribute get their type default value.
+///
+/// Applied to an enum variant without arguments, marks the variant used as the
+/// default value. Unlike the standard library derive, the variant may have
+/// fields, which are initialized in the same way as the structure fields.
+///
+/// Types of fields defaulting to their type default value, which depend on the
+/// generic type parameters, are required to implement [`Default`] (unlike the
+/// standard library derive, requiring this from the type parameters
+/// themselves).
+///
+/// # Errors
+///
+/// Unions are not supported; enums are required to have exactly one variant
+/// marked with `#[default]`.
+///
+/// # Example
+///
 /// ```
+/// # #[macro_use] extern crate amplify_derive;
+/// #[derive(SmartDefault, PartialEq, Debug)]
+/// struct Config {
+///     #[default(9735)]
+///     port: u16,
+///     #[default("localhost")]
+///     host: String,
+///     #[default(u32::MAX / 2)]
+///     max_size: u32,
+///     #[default(-1.5)]
+///     offset: f64,
+///     verbose: bool,
+/// }
 ///
-/// Advanced use: please pay attention that `as_mut` on a struct level is not
-/// removed by the use of `as_copy` at field level.
+/// #[derive(SmartDefault, PartialEq, Debug)]
+/// enum Network {
+///     Mainnet,
+///     #[default]
+///     Regtest {
+///         #[default(18443)]
+///         port: u16,
+///         peers: Vec<String>,
+///     },
+/// }
 ///
+/// let config = Config::default();
+/// assert_eq!(config.port, 9735);
+/// assert_eq!(config.host, "localhost");
+/// assert_eq!(config.max_size, u32::MAX / 2);
+/// assert_eq!(config.offset, -1.5);
+/// assert!(!config.verbose);
+/// assert_eq!(Network::default(), Network::Regtest {
+///     port: 18443,
+///     peers: vec![]
+/// });
 /// ```
-/// # #[macro_use] extern crate amplify_derive;
-/// #[derive(Getters, Default)]
-/// #[getter(as_mut, prefix = "get_")]
-/// struct One {
-///     /// Contains byte representation of the data
-///     #[getter(all, base_name = "bytes")]
-///     vec: Vec<u8>,
+#[proc_macro_derive(SmartDefault, attributes(default, amplify_crate))]
+pub fn derive_smart_default(input: TokenStream) -> TokenStream {
+    let derive_input = parse_macro_input!(input as DeriveInput);
+    default::inner(derive_input)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
+
+/// Derives `pub fn new(...)` constructor for structures, taking values for all
+/// the structure fields in their declaration order (except of the fields
+/// marked with `#[new(default)]` or `#[new(skip = ...)]`).
 ///
-///     defaults: String,
+/// # Attribute `#[new(...)]`
 ///
-///     #[getter(as_copy)]
-///     pub flag: bool,
+/// ### `#[new(into)]`
+/// Makes constructor to accept any value convertible into the field type with
+/// `impl Into<T>` argument.
 ///
-///     #[getter(skip)]
-///     pub(self) field: u8,
-/// }
+/// **Can be used**: at type and field level
 ///
-/// let mut one = One::default();
-/// assert_eq!(one.get_bytes_ref(), &Vec::<u8>::default());
-/// *one.get_bytes_mut() = vec![0, 1, 2];
-/// assert_eq!(one.get_defaults(), "");
-/// assert_eq!(one.get_defaults_mut(), "");
-/// assert_eq!(one.get_bytes(), vec![0, 1, 2]);
-/// assert_eq!(one.get_flag(), bool::default());
-/// assert_eq!(one.get_flag_mut(), &mut bool::default());
-/// let flag = one.get_flag_mut();
-/// *flag = true;
-/// assert_eq!(one.get_flag(), true);
-/// assert_eq!(one.flag, one.get_flag());
-/// // method does not exist: assert_eq!(one.get_field(), u8::default());
-/// ```
+/// ### `#[new(vis = "...")]`
+/// Defines visibility of the constructor, like `pub(crate)`.
 ///
-/// this will end up in the following generated code:
-/// ```
-/// # struct One {
-/// #    vec: Vec<u8>,
-/// #    pub flag: bool,
-/// #    pub(self) field: u8,
-/// # }
+/// **Defaults to**: `pub`
 ///
-/// impl One {
-///     #[doc = "Method cloning [`One::vec`] field.\n"]
-///     #[doc = " Contains byte representation of the data"]
-///     #[inline]
-///     pub fn get_bytes(&self) -> Vec<u8> { self.vec.clone() }
+/// **Can be used**: at type level
 ///
-///     #[doc = "Method borrowing [`One::vec`] field.\n"]
-///     #[doc = " Contains byte representation of the data"]
-///     #[inline]
-///     pub fn get_bytes_ref(&self) -> &Vec<u8> { &self.vec }
+/// ### `#[new(default)]`
+/// Initializes the field with its type default value instead of taking it as
+/// a constructor argument.
 ///
-///     #[doc = "Method returning mutable borrow of [`One::vec`] field.\n"]
-///     #[doc = " Contains byte representation of the data"]
-///     #[inline]
-///     pub fn get_bytes_mut(&mut self) -> &mut Vec<u8> { &mut self.vec }
+/// **Can be used**: at field level
 ///
-///     #[doc = "Method returning copy of [`One::flag`] field.\n"]
-///     #[inline]
-///     pub fn get_flag(&self) -> bool { self.flag }
+/// ### `#[new(skip = expr)]`
+/// Initializes the field with the given expression instead of taking it as a
+/// constructor argument. String literals are converted into the field type,
+/// such that they can be used for `String` fields.
 ///
-///     #[doc = "Method returning mutable borrow of [`One::flag`] field.\n"]
-///     #[inline]
-///     pub fn get_flag_mut(&mut self) -> &mut bool { &mut self.flag }
+/// **Can be used**: at field level
+///
+/// # Errors
+///
+/// Enums and unions are not supported. Field-level `default`, `skip` and
+/// `into` arguments can't be combined with each other.
+///
+/// # Example
+///
+/// ```
+/// # #[macro_use] extern crate amplify_derive;
+/// # use std::collections::HashMap;
+/// #[derive(New)]
+/// struct Peer {
+///     #[new(into)]
+///     alias: String,
+///     port: u16,
+///     #[new(default)]
+///     features: HashMap<u16, bool>,
+///     #[new(skip = 3)]
+///     retries: u8,
+///     #[new(skip = -1.5)]
+///     offset: f64,
+///     #[new(skip = "none")]
+///     status: String,
 /// }
+///
+/// #[derive(New)]
+/// #[new(vis = "pub(crate)")]
+/// struct Id(u64, #[new(skip = "main")] &'static str);
+///
+/// let peer = Peer::new("alice", 9735);
+/// assert_eq!(peer.alias, "alice");
+/// assert_eq!(peer.port, 9735);
+/// assert!(peer.features.is_empty());
+/// assert_eq!(peer.retries, 3);
+/// assert_eq!(peer.offset, -1.5);
+/// assert_eq!(peer.status, "none");
+/// assert_eq!(Id::new(5).1, "main");
 /// ```
-#[proc_macro_derive(Getters, attributes(getter))]
-pub fn derive_getters(input: TokenStream) -> TokenStream {
+#[proc_macro_derive(New, attributes(new, amplify_crate))]
+pub fn derive_new(input: TokenStream) -> TokenStream {
     let derive_input = parse_macro_input!(input as DeriveInput);
-    getters::derive(derive_input)
+    new::inner(derive_input)
         .unwrap_or_else(|e| e.to_compile_error())
         .into()
 }
@@ -637,8 +3104,14 @@ pub fn derive_getters(input: TokenStream) -> TokenStream {
 /// * `amplify::Wrapper`
 /// * [`AsRef`]
 /// * [`core::borrow::Borrow`]
+///
 /// You may skip `AsRef` and `Borrow` implementations with `#[wrapper(NoRefs)]`.
 ///
+/// Generated code refers to `std` only for types not available in `core`
+/// (like `String` returned by `ToHex`). When the `std` feature of this crate
+/// is disabled, or `#[amplify_crate(no_std)]` is given, these types are taken
+/// from `alloc` instead, requiring `extern crate alloc` in the user crate.
+///
 /// You can implement additional derives, it they are implemented for the
 /// wrapped type, using `#[wrapper()]` proc macro:
 /// 1. Reference access to the inner type:
@@ -651,13 +3124,26 @@ pub fn derive_getters(input: TokenStream) -> TokenStream {
 ///    * `Debug` for implementing [`core::fmt::Debug`]
 ///    * `Display` for implementing [`core::fmt::Display`]
 ///    * `FromHex` for implementing [`amplify::hex::FromHex`]
+///    * `ToHex` for implementing [`amplify::hex::ToHex`] for wrappers of byte
+///      containers; types deriving `LowerHex` already have it implemented by
+///      amplify
 ///    * `LowerHex` for implementing [`core::fmt::LowerHex`]
 ///    * `UpperHex` for implementing [`core::fmt::UpperHex`]
+///    * `ByteHex` for implementing both [`core::fmt::LowerHex`] and
+///      [`core::fmt::UpperHex`] as hex encoding of the bytes of wrapped byte
+///      containers, like byte arrays, which do not implement hex formatting
+///      themselves
 ///    * `LowerExp` for implementing [`core::fmt::LowerExp`]
 ///    * `UpperExp` for implementing [`core::fmt::UpperExp`]
 ///    * `Octal` for implementing [`core::fmt::Octal`]
-/// 3. Indexed access to the inner type:
-///    * `Index` for implementing [`core::ops::Index`]`<usize>`
+/// 3. Comparison with the inner type:
+///    * `CmpInner` for implementing [`PartialEq`] and [`PartialOrd`] between
+///      the wrapper and the inner type in both directions; if the inner type
+///      is a generic type parameter, only the wrapper may be on the left side
+///      of the comparison
+/// 4. Indexed access to the inner type:
+///    * `Index` for implementing [`core::ops::Index`]`<usize>`; a custom index
+///      type may be provided as `Index(Position)`
 ///    * `IndexRange` for implementing
 ///      [`core::ops::Index`]`<`[`core::ops::Range`]`<usize>>`
 ///    * `IndexTo` for implementing
@@ -670,23 +3156,35 @@ pub fn derive_getters(input: TokenStream) -> TokenStream {
 ///      [`core::ops::Index`]`<`[`core::ops::RangeToInclusive`]`<usize>>`
 ///    * `IndexFull` for implementing
 ///      [`core::ops::Index`]`<`[`core::ops::RangeFrom`]`<usize>>`
-/// 4. Arithmetic operations:
+/// 5. Arithmetic operations:
 ///    * `Neg` for implementing [`core::ops::Neg`]
 ///    * `Add` for implementing [`core::ops::Add`]
 ///    * `Sub` for implementing [`core::ops::Sub`]
 ///    * `Mul` for implementing [`core::ops::Mul`]
 ///    * `Div` for implementing [`core::ops::Div`]
 ///    * `Rem` for implementing [`core::ops::Rem`]
-/// 5. Boolean and bit-wise operations:
+/// 6. Boolean and bit-wise operations:
 ///    * `Not` for implementing [`core::ops::Not`]
 ///    * `BitAnd` for implementing [`core::ops::BitAnd`]
 ///    * `BitOr` for implementing [`core::ops::BitOr`]
 ///    * `BitXor` for implementing [`core::ops::BitXor`]
 ///    * `Shl` for implementing [`core::ops::Shl`]
 ///    * `Shr` for implementing [`core::ops::Shr`]
+/// 7. Numeric traits from `num-traits` crate (requires `num-traits` feature;
+///    the user crate must depend on `num-traits`). These traits require
+///    arithmetic operations on the wrapper type itself: `Zero` requires `Add`,
+///    `One` requires `Mul`, and `Num` requires [`PartialEq`] and all of `Add`,
+///    `Sub`, `Mul`, `Div` and `Rem`, which must be derived or implemented as
+///    well:
+///    * `Zero` for implementing `num_traits::Zero`
+///    * `One` for implementing `num_traits::One`
+///    * `Pow` for implementing `num_traits::Pow<Rhs>` for all exponent types
+///      supported by the wrapped type
+///    * `Num` for implementing `num_traits::Num`
 ///
 /// There are shortcuts for derivations:
-/// * `#[wrapper(Hex)]` will derive both `LowerHex`, `UpperHex` and `FromHex`;
+/// * `#[wrapper(Hex)]` will derive both `LowerHex`, `UpperHex` and `FromHex`
+///   (`ToHex` is provided by amplify for all `LowerHex` types);
 /// * `#[wrapper(Exp)]` will derive both `LowerExp` and `UpperExp`;
 /// * `#[wrapper(NumberFmt)]` will derive all number formatting traits
 ///   (`LowerHex`, `UpperHex`, `LowerExp`, `UpperExp`, `Octal`);
@@ -698,12 +3196,30 @@ pub fn derive_getters(input: TokenStream) -> TokenStream {
 /// * `#[wrapper(BoolOps)]` will derive all boolean operations (`Not`, `BitAnd`,
 ///   `BitOr`, `BitXor`);
 /// * `#[wrapper(BitOps)]` will derive all boolean operations *and bit shifts*
-///   (`Not`, `BitAnd`, `BitOr`, `BitXor`, `Shl`, `Shr`).
+///   (`Not`, `BitAnd`, `BitOr`, `BitXor`, `Shl`, `Shr`);
+/// * `#[wrapper(NumOps)]` will derive all numeric traits (`Zero`, `One`,
+///   `Pow`, `Num`) together with the arithmetic operations they require
+///   (`Add`, `Sub`, `Mul`, `Div`, `Rem`).
+///
+/// Traits listed more than once across `#[wrapper(...)]` attributes are
+/// derived a single time, with a compiler warning pointing at the repetition.
+///
+/// Binary operations (`Add`, `Sub`, `Mul`, `Div`, `Rem`, `Shl`, `Shr`,
+/// `BitAnd`, `BitOr`, `BitXor`) return `Self` by default. The output type may
+/// be overridden with `#[wrapper(Sub(Output = Duration))]`; in this case the
+/// output type must implement [`From`] for the output of the operation on the
+/// wrapped type.
 ///
 /// Other traits, such as [`PartialEq`], [`Eq`], [`PartialOrd`], [`Ord`],
 /// [`Hash`] can be implemented using standard `#[derive]` attribute in the
 /// same manner as [`Default`], [`Debug`] and [`From`]
 ///
+/// Structures with multiple fields must mark the wrapped one with `#[wrap]`,
+/// unless all other fields are `PhantomData` markers. `Wrapper::from_inner` initializes the rest of the fields with
+/// [`Default::default`], unless a field provides its own value with
+/// `#[wrap_default(expr)]` attribute; thus such structures do not require
+/// [`From`] implementation for the inner type.
+///
 /// # Example
 ///
 /// Simple wrapper:
@@ -741,6 +3257,102 @@ pub fn derive_getters(input: TokenStream) -> TokenStream {
 /// assert_eq!(w.into_inner(), HashMap::<usize, Vec<u8>>::default());
 /// ```
 ///
+/// `PhantomData` fields do not require the wrapped field to be marked:
+/// ```
+/// # #[macro_use] extern crate amplify_derive;
+/// use std::marker::PhantomData;
+///
+/// #[derive(Wrapper, Clone, Copy, PartialEq, Eq, Debug)]
+/// struct Meters<Unit>(u64, PhantomData<Unit>);
+///
+/// let distance = <Meters<()> as amplify::Wrapper>::from_inner(5);
+/// assert_eq!(distance, Meters(5, PhantomData));
+/// ```
+///
+/// Numeric wrappers with `num-traits` feature:
+#[cfg_attr(feature = "num-traits", doc = "```")]
+#[cfg_attr(not(feature = "num-traits"), doc = "```ignore")]
+/// # #[macro_use] extern crate amplify_derive;
+/// use std::marker::PhantomData;
+///
+/// use num_traits::{Num, One, Pow, Zero};
+///
+/// #[derive(Wrapper, Clone, Copy, PartialEq, Eq, Debug)]
+/// #[wrapper(NumOps)]
+/// struct Sats(u64, PhantomData<()>);
+///
+/// #[derive(Wrapper, Clone, Copy, PartialEq, Debug)]
+/// #[wrapper(Zero, One, Pow, Add, Mul)]
+/// struct Ratio(f64);
+///
+/// assert!(Sats::zero().is_zero());
+/// assert_eq!(Sats::one() + Sats::one(), Sats(2, PhantomData));
+/// assert_eq!(Sats(3, PhantomData).pow(2u32), Sats(9, PhantomData));
+/// assert_eq!(Sats::from_str_radix("ff", 16), Ok(Sats(255, PhantomData)));
+/// assert_eq!(Ratio::one() + Ratio::zero(), Ratio(1.0));
+/// assert_eq!(Ratio(4.0).pow(0.5f64), Ratio(2.0));
+/// assert_eq!(Ratio(2.0).pow(-1i32), Ratio(0.5));
+/// ```
+///
+/// Multiple named fields with custom default values:
+/// ```
+/// # #[macro_use] extern crate amplify_derive;
+/// #[derive(Wrapper, Clone, PartialEq, Eq, Debug)]
+/// struct Tagged {
+///     #[wrap]
+///     value: u64,
+///     #[wrap_default("untagged")]
+///     tag: &'static str,
+///     revision: u16,
+/// }
+///
+/// let tagged = <Tagged as amplify::Wrapper>::from_inner(5);
+/// assert_eq!(tagged, Tagged { value: 5, tag: "untagged", revision: 0 });
+/// ```
+///
+/// Hex round-tripping for byte containers:
+/// ```
+/// # #[macro_use] extern crate amplify_derive;
+/// # extern crate alloc;
+/// use amplify::hex::{FromHex, ToHex};
+///
+/// #[derive(Wrapper, Clone, PartialEq, Eq, Debug, From)]
+/// #[wrapper(FromHex, ToHex)]
+/// struct Bytes(Vec<u8>);
+///
+/// let bytes = Bytes::from_hex("deadbeef").unwrap();
+/// assert_eq!(bytes.to_hex(), "deadbeef");
+/// ```
+///
+/// Comparison with the inner type:
+/// ```
+/// # #[macro_use] extern crate amplify_derive;
+/// #[derive(Wrapper, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, From)]
+/// #[wrapper(CmpInner)]
+/// struct Height(u32);
+///
+/// let height = Height(100);
+/// assert!(height == 100u32);
+/// assert!(100u32 == height);
+/// assert!(height < 200u32);
+/// assert!(50u32 < height);
+/// ```
+///
+/// Wrappers of a bare type parameter are compared with their inner type only
+/// from the wrapper side, and the parameter gets no [`From`] conversion out of
+/// the wrapper, since the orphan rules do not allow implementing foreign traits
+/// for a type parameter:
+/// ```
+/// # #[macro_use] extern crate amplify_derive;
+/// #[derive(Wrapper, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, From)]
+/// #[wrapper(CmpInner)]
+/// struct Gauge<T: Ord>(T);
+///
+/// let gauge = Gauge(7u8);
+/// assert!(gauge == 7u8);
+/// assert!(gauge > 5u8);
+/// ```
+///
 /// Wrappers for indexable types
 /// ```
 /// # #[macro_use] extern crate amplify_derive;
@@ -750,7 +3362,69 @@ pub fn derive_getters(input: TokenStream) -> TokenStream {
 /// #[wrapper(Index, RangeOps)]
 /// struct VecNewtype(Vec<u8>);
 /// ```
-#[proc_macro_derive(Wrapper, attributes(wrap, wrapper, amplify_crate))]
+///
+/// Wrappers for byte arrays with const generic length:
+/// ```
+/// # #[macro_use] extern crate amplify_derive;
+/// #[derive(Wrapper, WrapperMut, Clone, Copy, PartialEq, Eq, Debug, From)]
+/// #[wrapper(Index, RangeOps, AsSlice, BorrowSlice, ByteHex)]
+/// #[wrapper_mut(IndexMut, RangeMut, AsSliceMut, BorrowSliceMut)]
+/// struct Hash<const N: usize = 32>([u8; N]);
+///
+/// let mut hash = Hash::from([0xde, 0xad, 0xbe, 0xef]);
+/// hash[0] = 0xca;
+/// assert_eq!(&hash[1..], &[0xad, 0xbe, 0xef]);
+/// assert_eq!(format!("{:x}", hash), "caadbeef");
+/// assert_eq!(format!("{:#X}", hash), "0xCAADBEEF");
+/// assert_eq!(AsRef::<[u8]>::as_ref(&Hash::<32>::from([1; 32])).len(), 32);
+/// ```
+///
+/// Wrappers indexed by a domain-specific position type:
+/// ```
+/// # #[macro_use] extern crate amplify_derive;
+/// # use std::ops::{Index, IndexMut};
+/// #[derive(Copy, Clone)]
+/// struct Position {
+///     row: usize,
+///     col: usize,
+/// }
+///
+/// struct Matrix([[u8; 3]; 3]);
+///
+/// impl Index<Position> for Matrix {
+///     type Output = u8;
+///     fn index(&self, pos: Position) -> &u8 { &self.0[pos.row][pos.col] }
+/// }
+///
+/// impl IndexMut<Position> for Matrix {
+///     fn index_mut(&mut self, pos: Position) -> &mut u8 { &mut self.0[pos.row][pos.col] }
+/// }
+///
+/// #[derive(Wrapper, WrapperMut, From)]
+/// #[wrapper(NoRefs, Index(Position))]
+/// #[wrapper_mut(NoRefs, IndexMut(Position))]
+/// struct Grid(Matrix);
+///
+/// let mut grid = Grid(Matrix([[0; 3]; 3]));
+/// let pos = Position { row: 1, col: 2 };
+/// grid[pos] = 5;
+/// assert_eq!(grid[pos], 5);
+/// ```
+///
+/// Wrappers with non-`Self` operation output:
+/// ```
+/// # #[macro_use] extern crate amplify_derive;
+/// #[derive(Wrapper, Clone, Copy, PartialEq, Eq, Debug, From)]
+/// #[wrapper(Add)]
+/// struct Duration(u64);
+///
+/// #[derive(Wrapper, Clone, Copy, PartialEq, Eq, Debug, From)]
+/// #[wrapper(Sub(Output = Duration))]
+/// struct Timestamp(u64);
+///
+/// assert_eq!(Timestamp(10) - Timestamp(4), Duration(6));
+/// ```
+#[proc_macro_derive(Wrapper, attributes(wrap, wrap_default, wrapper, amplify_crate))]
 pub fn derive_wrapper(input: TokenStream) -> TokenStream {
     let derive_input = parse_macro_input!(input as DeriveInput);
     wrapper::inner(derive_input)
@@ -766,9 +3440,20 @@ pub fn derive_wrapper(input: TokenStream) -> TokenStream {
 /// * `amplify::WrapperMut`
 /// * [`AsMut`]
 /// * [`core::borrow::BorrowMut`]
+///
 /// You may skip `AsMut` and `BorrowMut` implementations with
 /// `#[wrapper_mut(NoRefs)]`.
 ///
+/// If the wrapped field is private and the type enforces some invariants on
+/// it, safe mutable access to the inner data may be undesirable. In this case
+/// `#[wrapper_mut(no_inner)]` skips `amplify::WrapperMut` implementation, and
+/// `#[wrapper_mut(unsafe_inner)]` replaces it with a hidden inherent
+/// `unsafe fn as_inner_mut`. Both modes skip `AsMut` and `BorrowMut`
+/// implementations, and deriving any trait providing mutable reference to the
+/// inner data (`DerefMut`, `AsMut`, `AsSliceMut`, `BorrowMut`,
+/// `BorrowSliceMut`) together with them is a compile-time error. The rest of
+/// mutable derivations are not affected.
+///
 /// You can implement additional derives, it they are implemented for the
 /// wrapped type, using `#[wrapper()]` proc macro:
 /// 1. Reference access to the inner type:
@@ -777,7 +3462,8 @@ pub fn derive_wrapper(input: TokenStream) -> TokenStream {
 ///    * `BorrowSliceMut` for implementing
 ///      [`core::borrow::BorrowMut`]`<[Self::Inner]>`
 /// 2. Indexed access to the inner type:
-///    * `IndexMut` for implementing [`core::ops::IndexMut`]`<usize>`
+///    * `IndexMut` for implementing [`core::ops::IndexMut`]`<usize>`; a custom
+///      index type may be provided as `IndexMut(Position)`
 ///    * `IndexRangeMut` for implementing
 ///      [`core::ops::IndexMut`]`<`[`core::ops::Range`]`<usize>>`
 ///    * `IndexToMut` for implementing
@@ -830,6 +3516,53 @@ pub fn derive_wrapper(input: TokenStream) -> TokenStream {
 /// #[wrapper_mut(MathAssign, BitAssign)]
 /// struct Int64(i64);
 /// ```
+///
+/// Type protecting its inner data invariants:
+/// ```
+/// # #[macro_use] extern crate amplify_derive;
+/// #[derive(Wrapper, WrapperMut, Clone, Copy, PartialEq, Eq, Debug, From)]
+/// #[wrapper_mut(unsafe_inner, AddAssign)]
+/// struct Even(u64);
+///
+/// let mut even = Even(2);
+/// even += Even(4);
+/// unsafe { *even.as_inner_mut() = 10 };
+/// assert_eq!(even, Even(10));
+/// ```
+///
+/// Protected inner data is never exposed through safe mutable references:
+/// ```compile_fail
+/// # #[macro_use] extern crate amplify_derive;
+/// #[derive(Wrapper, WrapperMut, Clone, Copy, PartialEq, Eq, Debug, From)]
+/// #[wrapper(Deref)]
+/// #[wrapper_mut(unsafe_inner)]
+/// struct Even(u64);
+///
+/// let mut even = Even(2);
+/// *AsMut::<u64>::as_mut(&mut even) = 3;
+/// ```
+///
+/// ```compile_fail
+/// # #[macro_use] extern crate amplify_derive;
+/// #[derive(Wrapper, WrapperMut, Clone, Copy, PartialEq, Eq, Debug, From)]
+/// #[wrapper(Deref)]
+/// #[wrapper_mut(no_inner, DerefMut)]
+/// struct Even(u64);
+/// ```
+///
+/// Mutable indexing without exposing references to the inner type:
+/// ```
+/// # #[macro_use] extern crate amplify_derive;
+/// #[derive(Wrapper, WrapperMut, From)]
+/// #[wrapper(NoRefs, Index, RangeOps)]
+/// #[wrapper_mut(NoRefs, IndexMut, RangeMut)]
+/// struct Buffer(Vec<u8>);
+///
+/// let mut buf = Buffer(vec![0; 4]);
+/// buf[1] = 7;
+/// buf[2..].copy_from_slice(&[8, 9]);
+/// assert_eq!(buf[..], [0, 7, 8, 9]);
+/// ```
 #[proc_macro_derive(WrapperMut, attributes(wrap, wrapper_mut, amplify_crate))]
 pub fn derive_wrapper_mut(input: TokenStream) -> TokenStream {
     let derive_input = parse_macro_input!(input as DeriveInput);
@@ -837,3 +3570,218 @@ pub fn derive_wrapper_mut(input: TokenStream) -> TokenStream {
         .unwrap_or_else(|e| e.to_compile_error())
         .into()
 }
+
+/// Creates wrapper for a pair of values, like intervals or rational numbers,
+/// which do not fit the single-field model of [`macro@Wrapper`] derive.
+///
+/// Generates inherent `from_inner`, `as_inner` and `into_inner` methods
+/// operating on the tuple of the wrapped values, and [`From`] conversion into
+/// such tuple. The structure must either have exactly two fields, or two of
+/// its fields must be marked with `#[wrap]` attribute; the rest of the fields
+/// are initialized by `from_inner` with the expression provided in their
+/// `#[wrap_default(expr)]` attribute or with [`Default::default`].
+///
+/// Component-wise arithmetic operations may be derived with
+/// `#[wrapper_pair(...)]` attribute listing `Add`, `Sub`, `Mul`, `Div`, `Rem`
+/// and `Neg` traits.
+///
+/// # Example
+///
+/// ```
+/// # #[macro_use] extern crate amplify_derive;
+/// use std::marker::PhantomData;
+///
+/// #[derive(Copy, Clone, PartialEq, Eq, Debug, WrapperPair)]
+/// #[wrapper_pair(Add, Sub, Neg)]
+/// struct Interval {
+///     start: i32,
+///     end: i32,
+/// }
+///
+/// let interval = Interval::from_inner((1, 5));
+/// assert_eq!(interval.as_inner(), (&1, &5));
+/// assert_eq!(interval + Interval::from_inner((1, 1)), Interval { start: 2, end: 6 });
+/// assert_eq!(-interval, Interval { start: -1, end: -5 });
+/// assert_eq!(<(i32, i32)>::from(interval), (1, 5));
+///
+/// #[derive(WrapperPair)]
+/// struct Ratio<U>(#[wrap] u64, #[wrap] u64, PhantomData<U>);
+///
+/// let ratio = Ratio::<()>::from_inner((2, 3));
+/// assert_eq!(ratio.into_inner(), (2, 3));
+/// ```
+#[proc_macro_derive(WrapperPair, attributes(wrap, wrap_default, wrapper_pair, amplify_crate))]
+pub fn derive_wrapper_pair(input: TokenStream) -> TokenStream {
+    let derive_input = parse_macro_input!(input as DeriveInput);
+    wrapper::inner_pair(derive_input)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
+
+/// Implements [`Deref`] to a single field of a structure, without the
+/// `Wrapper` trait, `From` conversions and other wrapper derivations. This is
+/// a lightweight alternative to `#[derive(Wrapper)]` for types which should
+/// not expose their inner data.
+///
+/// The target field is selected in the same way as by `Wrapper` derive: it
+/// must be marked with `#[deref]` attribute, unless it is the only field of
+/// the structure (not counting `PhantomData` fields).
+///
+/// # Errors
+///
+/// Enums, unions, unit structures and structures with multiple fields none
+/// of which (or more than one of which) is marked with `#[deref]` are not
+/// supported.
+///
+/// # Example
+///
+/// ```
+/// # #[macro_use] extern crate amplify_derive;
+/// #[derive(Deref, DerefMut)]
+/// struct Buffer {
+///     #[deref]
+///     data: Vec<u8>,
+///     dirty: bool,
+/// }
+///
+/// #[derive(Deref)]
+/// struct Name(String);
+///
+/// let mut buffer = Buffer { data: vec![1, 2], dirty: false };
+/// buffer.push(3);
+/// assert_eq!(buffer.len(), 3);
+/// assert!(!buffer.dirty);
+/// assert_eq!(Name(String::from("alice")).to_uppercase(), "ALICE");
+/// ```
+///
+/// [`Deref`]: core::ops::Deref
+#[proc_macro_derive(Deref, attributes(deref, amplify_crate))]
+pub fn derive_deref(input: TokenStream) -> TokenStream {
+    let derive_input = parse_macro_input!(input as DeriveInput);
+    wrapper::inner_deref(derive_input)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
+
+/// Implements [`DerefMut`] to the field selected with `#[deref]` attribute
+/// (or to the only field of the structure). Must be used together with
+/// [`macro@Deref`] derive; see its documentation for the details.
+///
+/// [`DerefMut`]: core::ops::DerefMut
+#[proc_macro_derive(DerefMut, attributes(deref, amplify_crate))]
+pub fn derive_deref_mut(input: TokenStream) -> TokenStream {
+    let derive_input = parse_macro_input!(input as DeriveInput);
+    wrapper::inner_deref_mut(derive_input)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
+
+/// Implements [`Index`] and [`IndexMut`] for a structure by forwarding them
+/// into its field of a map or slice type, marked with `#[index]` attribute
+/// (unless it is the only field of the structure). Unlike `Wrapper` derive,
+/// does not require the structure to be a newtype.
+///
+/// The traits are implemented for all index types supported by the field;
+/// [`IndexMut`] is available only when the field type implements it.
+///
+/// # Errors
+///
+/// Enums, unions, unit structures and structures with multiple fields none
+/// of which (or more than one of which) is marked with `#[index]` are not
+/// supported.
+///
+/// # Example
+///
+/// ```
+/// # #[macro_use] extern crate amplify_derive;
+/// use std::collections::BTreeMap;
+///
+/// #[derive(Default, IndexByField)]
+/// struct Registry {
+///     #[index]
+///     entries: BTreeMap<String, u32>,
+///     version: u8,
+/// }
+///
+/// #[derive(IndexByField)]
+/// struct Samples(Vec<i16>);
+///
+/// let mut registry = Registry::default();
+/// registry.entries.insert(String::from("alice"), 1);
+/// assert_eq!(registry["alice"], 1);
+///
+/// let mut samples = Samples(vec![1, 2, 3]);
+/// samples[0] = 5;
+/// assert_eq!(samples[0], 5);
+/// assert_eq!(&samples[1..], &[2, 3]);
+/// ```
+///
+/// [`Index`]: core::ops::Index
+/// [`IndexMut`]: core::ops::IndexMut
+#[proc_macro_derive(IndexByField, attributes(index, amplify_crate))]
+pub fn derive_index_by_field(input: TokenStream) -> TokenStream {
+    let derive_input = parse_macro_input!(input as DeriveInput);
+    wrapper::inner_index_by_field(derive_input)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
+
+/// Implements [`PartialEq`], [`Eq`], [`PartialOrd`] and [`Ord`] comparing
+/// values only by their key field, ignoring the rest of the data. This is
+/// useful for cache entries, graph nodes and other types identified by an id
+/// while carrying some payload.
+///
+/// The key field must be marked with `#[key]` attribute, unless it is the only
+/// field of the structure (not counting `PhantomData` fields). Use together with [`macro@HashByKey`] to keep
+/// hashing consistent with the equality.
+///
+/// # Errors
+///
+/// Enums, unions, unit structures and structures with multiple fields none
+/// of which (or more than one of which) is marked with `#[key]` are not
+/// supported.
+///
+/// # Example
+///
+/// ```
+/// # #[macro_use] extern crate amplify_derive;
+/// use std::collections::{BTreeSet, HashSet};
+///
+/// #[derive(Debug, CmpByKey, HashByKey)]
+/// struct Node {
+///     #[key]
+///     id: u32,
+///     label: String,
+/// }
+///
+/// let a = Node { id: 1, label: String::from("a") };
+/// let b = Node { id: 1, label: String::from("b") };
+/// let c = Node { id: 2, label: String::from("a") };
+/// assert_eq!(a, b);
+/// assert!(b < c);
+///
+/// let set = vec![a, b, c].into_iter().collect::<HashSet<_>>();
+/// assert_eq!(set.len(), 2);
+/// let set = set.into_iter().collect::<BTreeSet<_>>();
+/// assert_eq!(set.iter().map(|node| node.id).collect::<Vec<_>>(), vec![1, 2]);
+/// ```
+#[proc_macro_derive(CmpByKey, attributes(key, amplify_crate))]
+pub fn derive_cmp_by_key(input: TokenStream) -> TokenStream {
+    let derive_input = parse_macro_input!(input as DeriveInput);
+    by_key::inner_cmp(derive_input)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
+
+/// Implements [`Hash`] hashing only the key field of the structure, which is
+/// marked with `#[key]` attribute (or is the only field of the structure).
+/// Complements [`macro@CmpByKey`]; see its documentation for the details.
+///
+/// [`Hash`]: core::hash::Hash
+#[proc_macro_derive(HashByKey, attributes(key, amplify_crate))]
+pub fn derive_hash_by_key(input: TokenStream) -> TokenStream {
+    let derive_input = parse_macro_input!(input as DeriveInput);
+    by_key::inner_hash(derive_input)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
@@ -0,0 +1,173 @@
+// Rust language amplification derive library providing multiple generic trait
+// implementations, type wrappers, derive macros and other language enhancements
+//
+// Written in 2019-2020 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the MIT License
+// along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+use std::convert::TryInto;
+
+use amplify_syn::{AttrReq, ParametrizedAttr, ValueClass, mentions_params};
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use syn::{Data, DataStruct, DeriveInput, Error, Expr, LitStr, Member, Result, Visibility};
+
+use crate::util::field_value;
+
+pub(crate) fn inner(mut input: DeriveInput) -> Result<TokenStream2> {
+    let mut global_param = ParametrizedAttr::with("new", &input.attrs)?;
+    let global = NewDerive::try_from(&mut global_param, true)?;
+
+    let fields = match input.data {
+        Data::Struct(DataStruct { ref fields, .. }) => fields,
+        Data::Enum(_) => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "Deriving `New` is not supported in enums",
+            ));
+        }
+        Data::Union(_) => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "Deriving `New` is not supported in unions",
+            ));
+        }
+    };
+
+    let params = input
+        .generics
+        .type_params()
+        .map(|param| param.ident.clone())
+        .collect::<Vec<_>>();
+    let params = params.iter().collect::<Vec<_>>();
+
+    let mut args = vec![];
+    let mut values = vec![];
+    let mut bounds = vec![];
+    for (index, field) in fields.iter().enumerate() {
+        let mut local_param = ParametrizedAttr::with("new", &field.attrs)?;
+        let local = NewDerive::try_from(&mut local_param, false)?;
+        let member = field
+            .ident
+            .clone()
+            .map_or_else(|| Member::from(index), Member::from);
+        let ty = &field.ty;
+
+        let value = match (local.default, local.skip) {
+            (true, _) => {
+                if mentions_params(quote! { #ty }, &params) {
+                    bounds.push(quote! { #ty: ::core::default::Default });
+                }
+                quote! { ::core::default::Default::default() }
+            }
+            (false, Some(expr)) => field_value(&expr),
+            (false, None) => {
+                let arg = match member {
+                    Member::Named(ref name) => name.clone(),
+                    Member::Unnamed(ref index) => format_ident!("_{}", index.index),
+                };
+                if global.into || local.into {
+                    args.push(quote! { #arg: impl ::core::convert::Into<#ty> });
+                    quote! { #arg.into() }
+                } else {
+                    args.push(quote! { #arg: #ty });
+                    quote! { #arg }
+                }
+            }
+        };
+        values.push(quote! { #member: #value });
+    }
+
+    if !bounds.is_empty() {
+        let where_clause = input.generics.make_where_clause();
+        for bound in bounds {
+            where_clause.predicates.push(parse_quote! { #bound });
+        }
+    }
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let ident_name = &input.ident;
+    let vis = global.vis.unwrap_or_else(|| parse_quote! { pub });
+    let doc = format!("Constructs [`{}`] from the provided field values.", ident_name);
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics #ident_name #ty_generics #where_clause {
+            #[doc = #doc]
+            #[inline]
+            #vis fn new( #( #args ),* ) -> Self {
+                Self { #( #values ),* }
+            }
+        }
+    })
+}
+
+struct NewDerive {
+    pub vis: Option<Visibility>,
+    pub into: bool,
+    pub default: bool,
+    pub skip: Option<Expr>,
+}
+
+impl NewDerive {
+    fn try_from(attr: &mut ParametrizedAttr, global: bool) -> Result<NewDerive> {
+        let req = AttrReq::new().flag("into");
+        let req = if global {
+            req.optional("vis", ValueClass::str())
+        } else {
+            req.flag("default").optional("skip", ValueClass::Expr)
+        };
+
+        attr.check(req)?;
+
+        let vis = attr
+            .args
+            .get("vis")
+            .map(|a| a.clone().try_into())
+            .transpose()?
+            .map(|lit: LitStr| {
+                syn::parse_str::<Visibility>(&lit.value()).map_err(|_| {
+                    Error::new(
+                        lit.span(),
+                        "`#[new(vis = \"...\")]` requires a valid visibility, like `pub` or \
+                         `pub(crate)`",
+                    )
+                })
+            })
+            .transpose()?;
+
+        let new = NewDerive {
+            vis,
+            into: attr.args.contains_key("into"),
+            default: attr.args.contains_key("default"),
+            skip: attr
+                .args
+                .get("skip")
+                .map(|a| a.clone().try_into())
+                .transpose()?,
+        };
+
+        if new.default && new.skip.is_some() {
+            return Err(Error::new(
+                Span::call_site(),
+                "`default` and `skip` attributes can't be present together",
+            ));
+        }
+        if new.into && (new.default || new.skip.is_some()) {
+            return Err(Error::new(
+                Span::call_site(),
+                "`into` attribute can't be combined with `default` or `skip`, since the field is \
+                 not given to the constructor",
+            ));
+        }
+
+        Ok(new)
+    }
+}
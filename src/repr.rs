@@ -0,0 +1,155 @@
+// Rust language amplification derive library providing multiple generic trait
+// implementations, type wrappers, derive macros and other language enhancements
+//
+// Written in 2019-2020 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the MIT License
+// along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+use amplify_syn::{AttrReq, Warnings};
+use proc_macro2::TokenStream as TokenStream2;
+use syn::punctuated::Punctuated;
+use syn::{Attribute, Data, DataEnum, DeriveInput, Error, Fields, Ident, Meta, Result, Token};
+
+use crate::util::CrateSettings;
+
+const INT_TYPES: [&str; 12] =
+    ["u8", "u16", "u32", "u64", "u128", "usize", "i8", "i16", "i32", "i64", "i128", "isize"];
+
+pub(crate) fn inner(input: DeriveInput) -> Result<TokenStream2> {
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let ident_name = &input.ident;
+
+    // The only argument is deprecated `no_std`, applied with crate settings
+    AttrReq::new()
+        .flag("no_std")
+        .parse("from_repr", &input.attrs)?;
+
+    let variants = match input.data {
+        Data::Enum(DataEnum { ref variants, .. }) => variants,
+        _ => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "Deriving `FromRepr` is supported only for enums",
+            ));
+        }
+    };
+    if let Some(variant) = variants
+        .iter()
+        .find(|variant| !matches!(variant.fields, Fields::Unit))
+    {
+        return Err(Error::new_spanned(
+            variant,
+            "Deriving `FromRepr` is supported only for enums with fieldless variants",
+        ));
+    }
+    let repr = repr_type(&input.attrs).ok_or_else(|| {
+        Error::new_spanned(
+            &input.ident,
+            "Deriving `FromRepr` requires enum to have integer representation given with \
+             `#[repr(...)]` attribute, like `#[repr(u8)]`",
+        )
+    })?;
+
+    let idents = variants
+        .iter()
+        .map(|variant| &variant.ident)
+        .collect::<Vec<_>>();
+    let error_name = format_ident!("{}ReprError", ident_name);
+    let error_doc = format!(
+        "Error converting integer into [`{}`], containing the value which does not match any of \
+         the enum discriminants.",
+        ident_name
+    );
+    let from_repr_doc = format!(
+        "Constructs [`{}`] from its discriminant value, returning `None` if the value does not \
+         match any of the variants.",
+        ident_name
+    );
+    // Without `std` feature generated code relies only on `core`
+    let mut warnings = Warnings::new();
+    let error_mod = CrateSettings::with(&input.attrs)?
+        .with_derive_no_std(&input.attrs, "from_repr", &mut warnings)
+        .error_mod();
+    let error_impl = error_mod.map(|error_mod| {
+        quote! {
+            #[automatically_derived]
+            impl #error_mod::Error for #error_name {}
+        }
+    });
+    let vis = &input.vis;
+    let ident_str = ident_name.to_string();
+
+    Ok(quote! {
+        #warnings
+
+        #[doc = #error_doc]
+        #[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+        #vis struct #error_name(pub #repr);
+
+        #[automatically_derived]
+        impl ::core::fmt::Display for #error_name {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                write!(f, "invalid {} discriminant value {}", #ident_str, self.0)
+            }
+        }
+
+        #error_impl
+
+        #[automatically_derived]
+        impl #impl_generics #ident_name #ty_generics #where_clause {
+            #[doc = #from_repr_doc]
+            #[inline]
+            pub const fn from_repr(value: #repr) -> ::core::option::Option<Self> {
+                #( if value == Self::#idents as #repr {
+                    return ::core::option::Option::Some(Self::#idents);
+                } )*
+                ::core::option::Option::None
+            }
+        }
+
+        #[automatically_derived]
+        impl #impl_generics ::core::convert::TryFrom<#repr> for #ident_name #ty_generics #where_clause {
+            type Error = #error_name;
+
+            #[inline]
+            fn try_from(value: #repr) -> ::core::result::Result<Self, Self::Error> {
+                Self::from_repr(value).ok_or(#error_name(value))
+            }
+        }
+
+        #[automatically_derived]
+        impl #impl_generics ::core::convert::From<#ident_name #ty_generics> for #repr #where_clause {
+            #[inline]
+            fn from(value: #ident_name #ty_generics) -> Self {
+                value as #repr
+            }
+        }
+    })
+}
+
+/// Finds integer type given in `#[repr(...)]` attribute
+fn repr_type(attrs: &[Attribute]) -> Option<Ident> {
+    attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("repr"))
+        .filter_map(|attr| {
+            attr.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)
+                .ok()
+        })
+        .flatten()
+        .find_map(|meta| match meta {
+            Meta::Path(path) => path
+                .get_ident()
+                .filter(|ident| INT_TYPES.iter().any(|ty| *ident == ty))
+                .cloned(),
+            _ => None,
+        })
+}
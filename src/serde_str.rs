@@ -0,0 +1,71 @@
+// Rust language amplification derive library providing multiple generic trait
+// implementations, type wrappers, derive macros and other language enhancements
+//
+// Written in 2019-2020 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the MIT License
+// along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+use proc_macro2::TokenStream as TokenStream2;
+use syn::{DeriveInput, Error, GenericParam, Result};
+
+use crate::util::CrateSettings;
+
+pub(crate) fn inner(mut input: DeriveInput) -> Result<TokenStream2> {
+    if !cfg!(feature = "serde") {
+        return Err(Error::new_spanned(
+            &input.ident,
+            "deriving `DisplayFromStrSerde` requires `serde` feature of `amplify_derive`",
+        ));
+    }
+
+    // Generic types must be both displayable and parsable for the
+    // implementations to exist
+    let where_clause = input.generics.make_where_clause();
+    where_clause
+        .predicates
+        .push(parse_quote! { Self: ::core::fmt::Display + ::core::str::FromStr });
+    where_clause
+        .predicates
+        .push(parse_quote! { <Self as ::core::str::FromStr>::Err: ::core::fmt::Display });
+
+    let mut de_generics = input.generics.clone();
+    de_generics
+        .params
+        .insert(0, GenericParam::Lifetime(parse_quote! { 'de }));
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let (de_impl_generics, _, _) = de_generics.split_for_impl();
+    let ident_name = &input.ident;
+    let alloc_crate = CrateSettings::with(&input.attrs)?.alloc_crate();
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics ::serde::Serialize for #ident_name #ty_generics #where_clause {
+            fn serialize<S>(&self, serializer: S) -> ::core::result::Result<S::Ok, S::Error>
+            where
+                S: ::serde::Serializer,
+            {
+                serializer.collect_str(self)
+            }
+        }
+
+        #[automatically_derived]
+        impl #de_impl_generics ::serde::Deserialize<'de> for #ident_name #ty_generics #where_clause {
+            fn deserialize<D>(deserializer: D) -> ::core::result::Result<Self, D::Error>
+            where
+                D: ::serde::Deserializer<'de>,
+            {
+                let s = <#alloc_crate::string::String as ::serde::Deserialize>::deserialize(deserializer)?;
+                ::core::str::FromStr::from_str(&s).map_err(::serde::de::Error::custom)
+            }
+        }
+    })
+}
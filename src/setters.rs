@@ -0,0 +1,200 @@
+// Rust language amplification derive library providing multiple generic trait
+// implementations, type wrappers, derive macros and other language enhancements
+//
+// Written in 2019-2020 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the MIT License
+// along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+use std::convert::TryInto;
+
+use amplify_syn::{AttrReq, ParametrizedAttr, ValueClass};
+use proc_macro2::{Ident, Span, TokenStream as TokenStream2};
+use syn::spanned::Spanned;
+use syn::{Data, DataStruct, DeriveInput, Error, Field, Fields, LitStr, Result, Visibility};
+
+use crate::getters::method_ident;
+use crate::util::accessor_attrs;
+
+pub(crate) fn derive(input: DeriveInput) -> Result<TokenStream2> {
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let struct_name = &input.ident;
+
+    let mut global_param = ParametrizedAttr::with("setter", &input.attrs)?;
+    let global = SetterDerive::try_from(&mut global_param, true)?;
+
+    let methods = match input.data {
+        Data::Struct(data) => derive_struct_methods(data, struct_name, &global)?,
+        Data::Enum(_) => {
+            return Err(Error::new_spanned(&input, "Deriving setters is not supported in enums"));
+        }
+        Data::Union(_) => {
+            return Err(Error::new_spanned(&input, "Deriving setters is not supported in unions"));
+        }
+    };
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics #struct_name #ty_generics #where_clause {
+            #( #methods )*
+        }
+    })
+}
+
+#[derive(Clone)]
+struct SetterDerive {
+    pub prefix: Option<LitStr>,
+    pub base: Option<LitStr>,
+    pub vis: Option<Visibility>,
+    pub skip: bool,
+    pub into: bool,
+    pub chain: bool,
+}
+
+impl SetterDerive {
+    fn try_from(attr: &mut ParametrizedAttr, global: bool) -> Result<SetterDerive> {
+        let req = AttrReq::new()
+            .flag("into")
+            .flag("chain")
+            .optional("vis", ValueClass::str());
+        let req = if global {
+            req.optional("prefix", ValueClass::str())
+        } else {
+            req.flag("skip").optional("base_name", ValueClass::str())
+        };
+
+        attr.check(req)?;
+
+        let vis = attr
+            .args
+            .get("vis")
+            .map(|a| a.clone().try_into())
+            .transpose()?
+            .map(|lit: LitStr| {
+                syn::parse_str::<Visibility>(&lit.value()).map_err(|_| {
+                    Error::new(
+                        lit.span(),
+                        "`#[setter(vis = \"...\")]` requires a valid visibility, like `pub` or \
+                         `pub(crate)`",
+                    )
+                })
+            })
+            .transpose()?;
+
+        Ok(SetterDerive {
+            prefix: attr
+                .args
+                .get("prefix")
+                .map(|a| a.clone().try_into())
+                .transpose()?,
+            base: attr
+                .args
+                .get("base_name")
+                .map(|a| a.clone().try_into())
+                .transpose()?,
+            vis,
+            skip: attr.args.contains_key("skip"),
+            into: attr.args.contains_key("into"),
+            chain: attr.args.contains_key("chain"),
+        })
+    }
+
+    /// Combines type-level arguments with the field-level ones, which take
+    /// precedence.
+    fn merged(&self, local: SetterDerive) -> SetterDerive {
+        SetterDerive {
+            prefix: self.prefix.clone(),
+            base: local.base,
+            vis: local.vis.or_else(|| self.vis.clone()),
+            skip: local.skip,
+            into: self.into || local.into,
+            chain: self.chain || local.chain,
+        }
+    }
+
+    pub fn setter_fn_ident(&self, field_name: &Ident) -> Result<Ident> {
+        let prefix = self
+            .prefix
+            .as_ref()
+            .map(LitStr::value)
+            .unwrap_or_else(|| String::from("set_"));
+        let base = self
+            .base
+            .as_ref()
+            .map(LitStr::value)
+            .unwrap_or_else(|| field_name.to_string().trim_start_matches("r#").to_owned());
+        method_ident(&format!("{}{}", prefix, base), field_name.span())
+    }
+}
+
+fn derive_struct_methods(
+    data: DataStruct,
+    struct_name: &Ident,
+    global: &SetterDerive,
+) -> Result<Vec<TokenStream2>> {
+    match data.fields {
+        Fields::Named(ref fields) => fields
+            .named
+            .iter()
+            .filter_map(|field| derive_field_method(field, struct_name, global).transpose())
+            .collect(),
+        Fields::Unnamed(_) => Err(Error::new(
+            Span::call_site(),
+            "Deriving setters is not supported for tuple-bases structs",
+        )),
+        Fields::Unit => {
+            Err(Error::new(Span::call_site(), "Deriving setters is meaningless for unit structs"))
+        }
+    }
+}
+
+fn derive_field_method(
+    field: &Field,
+    struct_name: &Ident,
+    global: &SetterDerive,
+) -> Result<Option<TokenStream2>> {
+    let mut local_param = ParametrizedAttr::with("setter", &field.attrs)?;
+    let setter = global.merged(SetterDerive::try_from(&mut local_param, false)?);
+
+    if setter.skip {
+        return Ok(None);
+    }
+
+    let field_name = field
+        .ident
+        .as_ref()
+        .expect("named fields always have an identifier");
+    let fn_name = setter.setter_fn_ident(field_name)?;
+    let fn_doc = format!("Method setting [`{}::{}`] field.\n", struct_name, field_name);
+    let attrs = accessor_attrs(&field.attrs);
+    let vis = setter.vis.unwrap_or_else(|| parse_quote! { pub });
+    let ty = &field.ty;
+
+    let (arg_ty, value) = if setter.into {
+        (quote! { impl ::core::convert::Into<#ty> }, quote! { value.into() })
+    } else {
+        (quote! { #ty }, quote! { value })
+    };
+    let (ret_ty, ret) = if setter.chain {
+        (quote! { -> &mut Self }, quote! { self })
+    } else {
+        (quote! {}, quote! {})
+    };
+
+    Ok(Some(quote_spanned! { field.span() =>
+        #[doc = #fn_doc]
+        #( #attrs )*
+        #[inline]
+        #vis fn #fn_name(&mut self, value: #arg_ty) #ret_ty {
+            self.#field_name = #value;
+            #ret
+        }
+    }))
+}
@@ -15,9 +15,13 @@
 
 #![allow(dead_code)]
 
-use syn::punctuated::IntoIter;
+use amplify_syn::{LitValue, Warnings};
+use proc_macro2::{TokenStream as TokenStream2, TokenTree};
+use quote::ToTokens;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::{IntoIter, Punctuated};
 use syn::spanned::Spanned;
-use syn::{Attribute, DeriveInput, Ident, Lit, Meta, MetaNameValue, NestedMeta, Path, Result};
+use syn::{Attribute, Expr, ExprLit, Lit, Meta, MetaList, MetaNameValue, Path, Result, Token};
 
 /// Macro producing `Result::Err` with [`syn::Error`] containing span
 /// information from `$attr` (first) argument and formatted string describing
@@ -39,43 +43,195 @@ macro_rules! attr_err {
     };
 }
 
-pub(crate) fn get_amplify_crate(input: &DeriveInput) -> Path {
-    let name = "amplify_crate";
-    let example = "#[amplify_crate(amplify_crate_path)]";
-    let default = Path::from(Ident::new("amplify", input.span()));
+/// Argument of an attribute given in a list form, which may be either a
+/// literal, like in `#[display("format")]`, or a meta item, like in
+/// `#[display(inner)]` or `#[display(alt = "format")]`.
+#[allow(clippy::large_enum_variant)]
+pub(crate) enum NestedArg {
+    Meta(Meta),
+    Lit(Lit),
+}
 
-    let list = match attr_list(&input.attrs, name, example).ok().unwrap_or(None) {
-        Some(x) => x,
-        None => return default,
-    };
-    nested_one_path(&list, name, example)
-        .ok()
-        .unwrap_or(None)
-        .unwrap_or(default)
+impl Parse for NestedArg {
+    fn parse(input: ParseStream) -> Result<Self> {
+        if input.peek(Lit) {
+            input.parse().map(NestedArg::Lit)
+        } else {
+            input.parse().map(NestedArg::Meta)
+        }
+    }
+}
+
+impl ToTokens for NestedArg {
+    fn to_tokens(&self, tokens: &mut TokenStream2) {
+        match self {
+            NestedArg::Meta(meta) => meta.to_tokens(tokens),
+            NestedArg::Lit(lit) => lit.to_tokens(tokens),
+        }
+    }
+}
+
+/// Parses arguments of an attribute given in a list form, like
+/// `#[attr("literal", path, name = "value")]`.
+pub(crate) fn nested_args(list: &MetaList) -> Result<Punctuated<NestedArg, Token![,]>> {
+    list.parse_args_with(Punctuated::parse_terminated)
+}
+
+/// Selects field attributes which have to be copied to the accessor methods
+/// derived for the field: documentation, conditional compilation and
+/// conditional documentation (`#[cfg_attr(..., doc...)]`) attributes.
+pub(crate) fn accessor_attrs(attrs: &[Attribute]) -> Vec<&Attribute> {
+    attrs
+        .iter()
+        .filter(|attr| {
+            if attr.path().is_ident("doc") || attr.path().is_ident("cfg") {
+                return true;
+            }
+            if !attr.path().is_ident("cfg_attr") {
+                return false;
+            }
+            match attr.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated) {
+                Ok(list) => {
+                    list.len() > 1 && list.iter().skip(1).all(|meta| meta.path().is_ident("doc"))
+                }
+                Err(_) => false,
+            }
+        })
+        .collect()
+}
+
+/// Produces initializer out of the value given to a field attribute, like
+/// `#[default(-1.5)]` or `#[new(skip = "name")]`. String literals are converted
+/// into the field type, such that they can be used for `String` fields; other
+/// values, including negative numbers, are used as they are.
+pub(crate) fn field_value(expr: &Expr) -> TokenStream2 {
+    match LitValue::try_from(expr) {
+        Ok(LitValue::Str(_)) => quote! { ::core::convert::Into::into(#expr) },
+        _ => quote! { #expr },
+    }
+}
+
+/// Settings of the generated code shared by all derives, which are given with
+/// `#[amplify_crate(path, no_std)]` attribute.
+pub(crate) struct CrateSettings {
+    /// Path to the `amplify` crate. Defaults to the name under which the
+    /// crate is given in the dependencies.
+    pub amplify: Path,
+
+    /// Whether the generated code must rely only on `core` and `alloc`. Always
+    /// set when the `std` feature is disabled.
+    pub no_std: bool,
+}
+
+impl CrateSettings {
+    pub fn with(attrs: &[Attribute]) -> Result<CrateSettings> {
+        let mut amplify = None;
+        let mut no_std = !cfg!(feature = "std");
+        for attr in attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("amplify_crate"))
+        {
+            let args = attr.parse_args_with(Punctuated::<Path, Token![,]>::parse_terminated)?;
+            for path in args {
+                if path.is_ident("no_std") {
+                    no_std = true;
+                } else if amplify.is_some() {
+                    return Err(attr_err!(
+                        path,
+                        "amplify_crate",
+                        "crate path may be given only once",
+                        "#[amplify_crate(::facade::amplify, no_std)]"
+                    ));
+                } else {
+                    amplify = Some(path);
+                }
+            }
+        }
+        let amplify = match amplify {
+            Some(path) => path,
+            None => amplify_syn::crate_path(&[], "amplify_crate", "amplify")?,
+        };
+        Ok(CrateSettings { amplify, no_std })
+    }
+
+    /// Switches to `no_std` mode if the derive attribute of the given name has
+    /// its own `no_std` argument, like `#[wrapper(no_std)]`. Such arguments are
+    /// deprecated in favour of `#[amplify_crate(no_std)]`, so each of them
+    /// produces a warning.
+    pub fn with_derive_no_std(
+        mut self,
+        attrs: &[Attribute],
+        name: &str,
+        warnings: &mut Warnings,
+    ) -> CrateSettings {
+        let args = attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident(name))
+            .filter_map(|attr| match attr.meta {
+                Meta::List(ref list) => Some(list.tokens.clone()),
+                _ => None,
+            })
+            .flatten();
+        for arg in args {
+            match arg {
+                TokenTree::Ident(ident) if ident == "no_std" => {
+                    self.no_std = true;
+                    warnings.warn(
+                        ident,
+                        format!(
+                            "`#[{}(no_std)]` is deprecated, use `#[amplify_crate(no_std)]` instead",
+                            name
+                        ),
+                    );
+                }
+                _ => {}
+            }
+        }
+        self
+    }
+
+    /// Returns path to the crate providing heap-allocated types.
+    pub fn alloc_crate(&self) -> TokenStream2 {
+        if self.no_std {
+            quote! { ::alloc }
+        } else {
+            quote! { ::std }
+        }
+    }
+
+    /// Returns path to the module defining `Error` trait, if the trait is
+    /// available: in `no_std` mode it requires Rust 1.81 or later.
+    pub fn error_mod(&self) -> Option<TokenStream2> {
+        if !self.no_std {
+            Some(quote! { ::std::error })
+        } else if cfg!(core_error) {
+            Some(quote! { ::core::error })
+        } else {
+            None
+        }
+    }
 }
 
 pub fn attr_list<'a>(
     attrs: impl IntoIterator<Item = &'a Attribute>,
     ident: &str,
     example: &str,
-) -> Result<Option<IntoIter<NestedMeta>>> {
+) -> Result<Option<IntoIter<Meta>>> {
     for attr in attrs {
-        if attr.path.is_ident(ident) {
-            match attr.parse_meta() {
-                Ok(meta) => match meta {
-                    Meta::Path(_) => {
-                        return Err(attr_err!(ident, "unexpected path argument", example));
-                    }
-                    Meta::List(list) => return Ok(Some(list.nested.into_iter())),
-                    Meta::NameValue(_) => {
-                        return Err(attr_err!(
-                            ident,
-                            "unexpected `name=\"value\"` argument",
-                            example
-                        ));
-                    }
-                },
-                Err(_) => return Err(attr_err!(ident, "wrong format", example)),
+        if attr.path().is_ident(ident) {
+            match attr.meta {
+                Meta::Path(_) => {
+                    return Err(attr_err!(ident, "unexpected path argument", example));
+                }
+                Meta::List(ref list) => {
+                    return list
+                        .parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)
+                        .map(|list| Some(list.into_iter()))
+                        .map_err(|_| attr_err!(ident, "wrong format", example));
+                }
+                Meta::NameValue(_) => {
+                    return Err(attr_err!(ident, "unexpected `name=\"value\"` argument", example));
+                }
             }
         }
     }
@@ -89,22 +245,23 @@ pub fn attr_named_value<'a>(
     example: &str,
 ) -> Result<Option<Lit>> {
     for attr in attrs {
-        if attr.path.is_ident(ident) {
-            match attr.parse_meta() {
-                Ok(meta) => match meta {
-                    Meta::Path(_) => {
-                        return Err(attr_err!(ident, "unexpected path argument", example));
-                    }
-                    Meta::List(_) => {
-                        return Err(attr_err!(
-                            ident,
-                            "must have form `name=\"value\"`, not `name(value)`",
-                            example
-                        ));
-                    }
-                    Meta::NameValue(name_val) => return Ok(Some(name_val.lit)),
-                },
-                Err(_) => return Err(attr_err!(ident, "wrong format", example)),
+        if attr.path().is_ident(ident) {
+            match attr.meta {
+                Meta::Path(_) => {
+                    return Err(attr_err!(ident, "unexpected path argument", example));
+                }
+                Meta::List(_) => {
+                    return Err(attr_err!(
+                        ident,
+                        "must have form `name=\"value\"`, not `name(value)`",
+                        example
+                    ));
+                }
+                Meta::NameValue(MetaNameValue {
+                    value: Expr::Lit(ExprLit { ref lit, .. }),
+                    ..
+                }) => return Ok(Some(lit.clone())),
+                Meta::NameValue(_) => return Err(attr_err!(ident, "wrong format", example)),
             }
         }
     }
@@ -113,29 +270,19 @@ pub fn attr_named_value<'a>(
 }
 
 pub fn nested_one_meta(
-    list: &IntoIter<NestedMeta>,
+    list: &IntoIter<Meta>,
     attr_name: &str,
     example: &str,
 ) -> Result<Option<Meta>> {
     match list.len() {
         0 => Err(attr_err!(attr_name, "unexpected absence of argument", example)),
-        1 => match list
-            .clone()
-            .peekable()
-            .peek()
-            .expect("Core library iterator is broken")
-        {
-            NestedMeta::Meta(meta) => Ok(Some(meta.clone())),
-            NestedMeta::Lit(_) => {
-                Err(attr_err!(attr_name, "unexpected literal for type identifier is met", example))
-            }
-        },
+        1 => Ok(list.clone().next()),
         _ => Err(attr_err!(attr_name, "unexpected multiple type identifiers", example)),
     }
 }
 
 pub fn nested_one_path(
-    list: &IntoIter<NestedMeta>,
+    list: &IntoIter<Meta>,
     attr_name: &str,
     example: &str,
 ) -> Result<Option<Path>> {
@@ -148,7 +295,7 @@ pub fn nested_one_path(
 }
 
 pub fn nested_one_named_value(
-    list: &IntoIter<NestedMeta>,
+    list: &IntoIter<Meta>,
     attr_name: &str,
     example: &str,
 ) -> Result<Option<MetaNameValue>> {
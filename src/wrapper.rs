@@ -13,13 +13,20 @@
 // along with this software.
 // If not, see <https://opensource.org/licenses/MIT>.
 
+use std::collections::BTreeMap;
+
+use amplify_syn::{
+    DuplicatePolicy, SelectPolicy, SelectedField, Warnings, mentions_params, merge_attr_args,
+};
 use proc_macro2::TokenStream as TokenStream2;
+use quote::ToTokens;
+use syn::parse::{Parse, ParseStream};
 use syn::spanned::Spanned;
 use syn::{
-    Data, DeriveInput, Error, Fields, Index, Meta, MetaList, NestedMeta, Path, Result, Type,
+    Data, DeriveInput, Error, Expr, Fields, Generics, Ident, Member, Path, Result, Type, token,
 };
 
-use crate::util::get_amplify_crate;
+use crate::util::CrateSettings;
 
 const NAME: &str = "wrapper";
 const EXAMPLE: &str = r#"#[wrapper(LowerHex, Add)]"#;
@@ -27,14 +34,17 @@ const EXAMPLE: &str = r#"#[wrapper(LowerHex, Add)]"#;
 #[derive(Copy, Clone, PartialEq, Eq, Ord, PartialOrd, Debug)]
 enum Wrapper {
     NoRefs,
+    NoStd,
     // Formatting
     FromStr,
     Display,
     Debug,
     Octal,
     FromHex,
+    ToHex,
     LowerHex,
     UpperHex,
+    ByteHex,
     LowerExp,
     UpperExp,
     // References
@@ -43,6 +53,8 @@ enum Wrapper {
     AsSlice,
     Borrow,
     BorrowSlice,
+    // Comparison
+    CmpInner,
     // Indexes
     Index,
     IndexRange,
@@ -65,6 +77,11 @@ enum Wrapper {
     BitAnd,
     BitOr,
     BitXor,
+    // Numeric traits from `num-traits` crate
+    Zero,
+    One,
+    Pow,
+    Num,
     // Group operations
     Hex,
     Exp,
@@ -73,11 +90,14 @@ enum Wrapper {
     MathOps,
     BoolOps,
     BitOps,
+    NumOps,
 }
 
 #[derive(Copy, Clone, PartialEq, Eq, Ord, PartialOrd, Debug)]
 enum WrapperMut {
     NoRefs,
+    NoInner,
+    UnsafeInner,
     // References
     DerefMut,
     AsMut,
@@ -111,11 +131,59 @@ enum WrapperMut {
     BitAssign,
 }
 
+/// Single argument of `#[wrapper(...)]` or `#[wrapper_mut(...)]` attribute:
+/// either a bare trait name like `Add`, a trait name with a parametrized
+/// type override like `Sub(Output = Duration)`, or a trait name with a type
+/// argument like `Index(Position)`.
+struct WrapperArg {
+    path: Path,
+    paren: Option<token::Paren>,
+    param: Option<(Option<Ident>, Type)>,
+}
+
+impl Parse for WrapperArg {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let path = Path::parse_mod_style(input)?;
+        let mut paren = None;
+        let param = if input.peek(token::Paren) {
+            let content;
+            paren = Some(parenthesized!(content in input));
+            let name = if content.peek(Ident) && content.peek2(Token![=]) {
+                let name = content.parse()?;
+                content.parse::<Token![=]>()?;
+                Some(name)
+            } else {
+                None
+            };
+            Some((name, content.parse()?))
+        } else {
+            None
+        };
+        Ok(WrapperArg { path, paren, param })
+    }
+}
+
+impl ToTokens for WrapperArg {
+    fn to_tokens(&self, tokens: &mut TokenStream2) {
+        self.path.to_tokens(tokens);
+        if let (Some(paren), Some((name, ty))) = (&self.paren, &self.param) {
+            paren.surround(tokens, |tokens| match name {
+                Some(name) => quote! { #name = #ty }.to_tokens(tokens),
+                None => ty.to_tokens(tokens),
+            });
+        }
+    }
+}
+
 pub trait FromPath: Sized + Copy + Ord {
     const IDENT: &'static str;
     const NO_REFS: Self;
     fn default_set() -> Vec<Self>;
     fn is_not_ref(&self) -> bool;
+    fn is_mut_ref(&self) -> bool;
+    fn protects_inner(&self) -> bool;
+    fn is_binary_op(&self) -> bool;
+    fn is_index(&self) -> bool;
     fn from_path(path: &Path) -> Result<Option<Self>>;
     fn populate(self, list: &mut Vec<Self>);
 }
@@ -128,26 +196,60 @@ impl FromPath for Wrapper {
 
     fn is_not_ref(&self) -> bool { *self != Wrapper::AsRef && *self != Wrapper::Borrow }
 
+    fn is_mut_ref(&self) -> bool { false }
+
+    fn protects_inner(&self) -> bool { false }
+
+    fn is_binary_op(&self) -> bool {
+        matches!(
+            self,
+            Wrapper::Add |
+                Wrapper::Sub |
+                Wrapper::Mul |
+                Wrapper::Div |
+                Wrapper::Rem |
+                Wrapper::Shl |
+                Wrapper::Shr |
+                Wrapper::BitAnd |
+                Wrapper::BitOr |
+                Wrapper::BitXor
+        )
+    }
+
+    fn is_index(&self) -> bool { *self == Wrapper::Index }
+
     fn from_path(path: &Path) -> Result<Option<Self>> {
         path.segments.first().map_or(
             Err(attr_err!(path.span(), NAME, "must contain at least one identifier", EXAMPLE)),
             |segment| {
                 Ok(match segment.ident.to_string().as_str() {
+                    "Zero" | "One" | "Pow" | "Num" | "NumOps" if !cfg!(feature = "num-traits") => {
+                        return Err(attr_err!(
+                            path.span(),
+                            NAME,
+                            "numeric traits require `num-traits` feature of `amplify_derive`",
+                            EXAMPLE
+                        ));
+                    }
                     "FromStr" => Some(Wrapper::FromStr),
                     "Display" => Some(Wrapper::Display),
                     "Debug" => Some(Wrapper::Debug),
                     "Octal" => Some(Wrapper::Octal),
                     "FromHex" => Some(Wrapper::FromHex),
+                    "ToHex" => Some(Wrapper::ToHex),
                     "LowerHex" => Some(Wrapper::LowerHex),
                     "UpperHex" => Some(Wrapper::UpperHex),
+                    "ByteHex" => Some(Wrapper::ByteHex),
                     "LowerExp" => Some(Wrapper::LowerExp),
                     "UpperExp" => Some(Wrapper::UpperExp),
                     "NoRefs" => Some(Wrapper::NoRefs),
+                    "no_std" => Some(Wrapper::NoStd),
                     "AsRef" => Some(Wrapper::AsRef),
                     "AsSlice" => Some(Wrapper::AsSlice),
                     "Deref" => Some(Wrapper::Deref),
                     "Borrow" => Some(Wrapper::Borrow),
                     "BorrowSlice" => Some(Wrapper::BorrowSlice),
+                    "CmpInner" => Some(Wrapper::CmpInner),
                     "Index" => Some(Wrapper::Index),
                     "IndexRange" => Some(Wrapper::IndexRange),
                     "IndexFull" => Some(Wrapper::IndexFull),
@@ -167,6 +269,10 @@ impl FromPath for Wrapper {
                     "BitAnd" => Some(Wrapper::BitAnd),
                     "BitOr" => Some(Wrapper::BitOr),
                     "BitXor" => Some(Wrapper::BitXor),
+                    "Zero" => Some(Wrapper::Zero),
+                    "One" => Some(Wrapper::One),
+                    "Pow" => Some(Wrapper::Pow),
+                    "Num" => Some(Wrapper::Num),
 
                     "Hex" => Some(Wrapper::Hex),
                     "Exp" => Some(Wrapper::Exp),
@@ -175,6 +281,7 @@ impl FromPath for Wrapper {
                     "MathOps" => Some(Wrapper::MathOps),
                     "BoolOps" => Some(Wrapper::BoolOps),
                     "BitOps" => Some(Wrapper::BitOps),
+                    "NumOps" => Some(Wrapper::NumOps),
                     _ => None,
                 })
             },
@@ -219,6 +326,18 @@ impl FromPath for Wrapper {
                 Wrapper::Shl,
                 Wrapper::Shr,
             ] as &[_],
+            // Numeric traits require arithmetic operations on the type itself
+            Wrapper::NumOps => &[
+                Wrapper::Add,
+                Wrapper::Sub,
+                Wrapper::Mul,
+                Wrapper::Div,
+                Wrapper::Rem,
+                Wrapper::Zero,
+                Wrapper::One,
+                Wrapper::Pow,
+                Wrapper::Num,
+            ] as &[_],
             x => {
                 list.push(x);
                 &[] as &[_]
@@ -234,11 +353,29 @@ impl Wrapper {
         input: &DeriveInput,
         from: &Type,
         field: &TokenStream2,
+        param: Option<&Type>,
+        amplify_crate: &Path,
+        no_std: bool,
     ) -> TokenStream2 {
-        let impl_generics_params = input.generics.params.clone();
         let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
         let ident_name = &input.ident;
-        let amplify_crate = get_amplify_crate(input);
+        let alloc_crate = if no_std {
+            quote! { ::alloc }
+        } else {
+            quote! { ::std }
+        };
+
+        // Binary operations may return a type different from `Self`, which
+        // must be constructible with `From` out of the inner operation output.
+        // `Self` is constructed with `from_inner`, so the rest of the fields
+        // get their default values.
+        let output_ty = param.map_or_else(|| quote! { Self }, |ty| quote! { #ty });
+        let wrap_output = |res: TokenStream2| match param {
+            None => quote! { <Self as #amplify_crate::Wrapper>::from_inner(#res) },
+            Some(_) => quote! { ::core::convert::From::from(#res) },
+        };
+        // `Index` may use a custom index type instead of `usize`
+        let index_ty = param.map_or_else(|| quote! { usize }, |ty| quote! { #ty });
 
         match self {
             Wrapper::FromStr => quote! {
@@ -250,7 +387,7 @@ impl Wrapper {
                     #[inline]
                     fn from_str(s: &str) -> Result<Self, Self::Err> {
                         use ::core::str::FromStr;
-                        <#from as FromStr>::from_str(s).map(Self::from)
+                        <#from as FromStr>::from_str(s).map(<Self as #amplify_crate::Wrapper>::from_inner)
                     }
                 }
             },
@@ -295,7 +432,19 @@ impl Wrapper {
                             + ExactSizeIterator
                             + DoubleEndedIterator,
                     {
-                        <#from as #amplify_crate::hex::FromHex>::from_byte_iter(iter).map(Self::from)
+                        <#from as #amplify_crate::hex::FromHex>::from_byte_iter(iter)
+                            .map(<Self as #amplify_crate::Wrapper>::from_inner)
+                    }
+                }
+            },
+            Wrapper::ToHex => quote! {
+                #[automatically_derived]
+                impl #impl_generics #amplify_crate::hex::ToHex for #ident_name #ty_generics #where_clause
+                {
+                    #[inline]
+                    fn to_hex(&self) -> #alloc_crate::string::String {
+                        use #amplify_crate::hex::ToHex;
+                        self.#field.to_hex()
                     }
                 }
             },
@@ -319,6 +468,35 @@ impl Wrapper {
                     }
                 }
             },
+            Wrapper::ByteHex => quote! {
+                #[automatically_derived]
+                impl #impl_generics ::core::fmt::LowerHex for #ident_name #ty_generics #where_clause
+                {
+                    fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                        if f.alternate() {
+                            f.write_str("0x")?;
+                        }
+                        for byte in ::core::convert::AsRef::<[u8]>::as_ref(&self.#field) {
+                            write!(f, "{:02x}", byte)?;
+                        }
+                        Ok(())
+                    }
+                }
+
+                #[automatically_derived]
+                impl #impl_generics ::core::fmt::UpperHex for #ident_name #ty_generics #where_clause
+                {
+                    fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                        if f.alternate() {
+                            f.write_str("0x")?;
+                        }
+                        for byte in ::core::convert::AsRef::<[u8]>::as_ref(&self.#field) {
+                            write!(f, "{:02X}", byte)?;
+                        }
+                        Ok(())
+                    }
+                }
+            },
             Wrapper::LowerExp => quote! {
                 #[automatically_derived]
                 impl #impl_generics ::core::fmt::LowerExp for #ident_name #ty_generics #where_clause
@@ -388,20 +566,61 @@ impl Wrapper {
                     }
                 }
             },
-            Wrapper::Index => {
-                let where_clause = match where_clause {
-                    None => quote! { where },
-                    Some(_) => quote! { #where_clause },
+            Wrapper::CmpInner => {
+                // Orphan rules prohibit implementing foreign traits for a bare
+                // type parameter, so generic inner types are compared only
+                // from the wrapper side
+                let reverse = if is_type_param(from, &input.generics) {
+                    quote! {}
+                } else {
+                    quote! {
+                        #[automatically_derived]
+                        impl #impl_generics ::core::cmp::PartialEq<#ident_name #ty_generics> for #from #where_clause {
+                            #[inline]
+                            fn eq(&self, other: &#ident_name #ty_generics) -> bool {
+                                ::core::cmp::PartialEq::eq(self, &other.#field)
+                            }
+                        }
+
+                        #[automatically_derived]
+                        impl #impl_generics ::core::cmp::PartialOrd<#ident_name #ty_generics> for #from #where_clause {
+                            #[inline]
+                            fn partial_cmp(&self, other: &#ident_name #ty_generics) -> Option<::core::cmp::Ordering> {
+                                ::core::cmp::PartialOrd::partial_cmp(self, &other.#field)
+                            }
+                        }
+                    }
                 };
                 quote! {
                     #[automatically_derived]
-                    impl <#impl_generics_params> ::core::ops::Index<usize> for #ident_name #ty_generics #where_clause
+                    impl #impl_generics ::core::cmp::PartialEq<#from> for #ident_name #ty_generics #where_clause {
+                        #[inline]
+                        fn eq(&self, other: &#from) -> bool {
+                            ::core::cmp::PartialEq::eq(&self.#field, other)
+                        }
+                    }
+
+                    #[automatically_derived]
+                    impl #impl_generics ::core::cmp::PartialOrd<#from> for #ident_name #ty_generics #where_clause {
+                        #[inline]
+                        fn partial_cmp(&self, other: &#from) -> Option<::core::cmp::Ordering> {
+                            ::core::cmp::PartialOrd::partial_cmp(&self.#field, other)
+                        }
+                    }
+
+                    #reverse
+                }
+            }
+            Wrapper::Index => {
+                quote! {
+                    #[automatically_derived]
+                    impl #impl_generics ::core::ops::Index<#index_ty> for #ident_name #ty_generics #where_clause
                     {
-                        type Output = <#from as ::core::ops::Index<usize>>::Output;
+                        type Output = <#from as ::core::ops::Index<#index_ty>>::Output;
 
                         #[inline]
-                        fn index(&self, index: usize) -> &Self::Output {
-                            self.#field.index(index)
+                        fn index(&self, index: #index_ty) -> &Self::Output {
+                            ::core::ops::Index::index(&self.#field, index)
                         }
                     }
                 }
@@ -409,13 +628,13 @@ impl Wrapper {
             Wrapper::IndexRange => {
                 quote! {
                     #[automatically_derived]
-                    impl <#impl_generics_params> ::core::ops::Index<::core::ops::Range<usize>> for #ident_name #ty_generics #where_clause
+                    impl #impl_generics ::core::ops::Index<::core::ops::Range<usize>> for #ident_name #ty_generics #where_clause
                     {
                         type Output = <#from as ::core::ops::Index<::core::ops::Range<usize>>>::Output;
 
                         #[inline]
                         fn index(&self, index: ::core::ops::Range<usize>) -> &Self::Output {
-                            self.#field.index(index)
+                            ::core::ops::Index::index(&self.#field, index)
                         }
                     }
                 }
@@ -423,13 +642,13 @@ impl Wrapper {
             Wrapper::IndexFrom => {
                 quote! {
                     #[automatically_derived]
-                    impl <#impl_generics_params> ::core::ops::Index<::core::ops::RangeFrom<usize>> for #ident_name #ty_generics #where_clause
+                    impl #impl_generics ::core::ops::Index<::core::ops::RangeFrom<usize>> for #ident_name #ty_generics #where_clause
                     {
                         type Output = <#from as ::core::ops::Index<::core::ops::RangeFrom<usize>>>::Output;
 
                         #[inline]
                         fn index(&self, index: ::core::ops::RangeFrom<usize>) -> &Self::Output {
-                            self.#field.index(index)
+                            ::core::ops::Index::index(&self.#field, index)
                         }
                     }
                 }
@@ -437,13 +656,13 @@ impl Wrapper {
             Wrapper::IndexTo => {
                 quote! {
                     #[automatically_derived]
-                    impl <#impl_generics_params> ::core::ops::Index<::core::ops::RangeTo<usize>> for #ident_name #ty_generics #where_clause
+                    impl #impl_generics ::core::ops::Index<::core::ops::RangeTo<usize>> for #ident_name #ty_generics #where_clause
                     {
                         type Output = <#from as ::core::ops::Index<::core::ops::RangeTo<usize>>>::Output;
 
                         #[inline]
                         fn index(&self, index: ::core::ops::RangeTo<usize>) -> &Self::Output {
-                            self.#field.index(index)
+                            ::core::ops::Index::index(&self.#field, index)
                         }
                     }
                 }
@@ -451,13 +670,13 @@ impl Wrapper {
             Wrapper::IndexInclusive => {
                 quote! {
                     #[automatically_derived]
-                    impl <#impl_generics_params> ::core::ops::Index<::core::ops::RangeInclusive<usize>> for #ident_name #ty_generics #where_clause
+                    impl #impl_generics ::core::ops::Index<::core::ops::RangeInclusive<usize>> for #ident_name #ty_generics #where_clause
                     {
                         type Output = <#from as ::core::ops::Index<::core::ops::RangeInclusive<usize>>>::Output;
 
                         #[inline]
                         fn index(&self, index: ::core::ops::RangeInclusive<usize>) -> &Self::Output {
-                            self.#field.index(index)
+                            ::core::ops::Index::index(&self.#field, index)
                         }
                     }
                 }
@@ -465,13 +684,13 @@ impl Wrapper {
             Wrapper::IndexToInclusive => {
                 quote! {
                     #[automatically_derived]
-                    impl <#impl_generics_params> ::core::ops::Index<::core::ops::RangeToInclusive<usize>> for #ident_name #ty_generics #where_clause
+                    impl #impl_generics ::core::ops::Index<::core::ops::RangeToInclusive<usize>> for #ident_name #ty_generics #where_clause
                     {
                         type Output = <#from as ::core::ops::Index<::core::ops::RangeInclusive<usize>>>::Output;
 
                         #[inline]
                         fn index(&self, index: ::core::ops::RangeToInclusive<usize>) -> &Self::Output {
-                            self.#field.index(index)
+                            ::core::ops::Index::index(&self.#field, index)
                         }
                     }
                 }
@@ -479,13 +698,13 @@ impl Wrapper {
             Wrapper::IndexFull => {
                 quote! {
                     #[automatically_derived]
-                    impl <#impl_generics_params> ::core::ops::Index<::core::ops::RangeFull> for #ident_name #ty_generics #where_clause
+                    impl #impl_generics ::core::ops::Index<::core::ops::RangeFull> for #ident_name #ty_generics #where_clause
                     {
                         type Output = <#from as ::core::ops::Index<::core::ops::RangeFull>>::Output;
 
                         #[inline]
                         fn index(&self, index: ::core::ops::RangeFull) -> &Self::Output {
-                            self.#field.index(index)
+                            ::core::ops::Index::index(&self.#field, index)
                         }
                     }
                 }
@@ -514,134 +733,231 @@ impl Wrapper {
                     }
                 }
             },
-            Wrapper::Add => quote! {
-                #[automatically_derived]
-                impl #impl_generics ::core::ops::Add for #ident_name #ty_generics #where_clause
-                {
-                    type Output = Self;
+            Wrapper::Add => {
+                let res = wrap_output(quote! { ::core::ops::Add::add(self.#field, rhs.#field) });
+                quote! {
+                    #[automatically_derived]
+                    impl #impl_generics ::core::ops::Add for #ident_name #ty_generics #where_clause
+                    {
+                        type Output = #output_ty;
 
-                    #[inline]
-                    fn add(self, rhs: Self) -> Self {
-                        Self { #field: ::core::ops::Add::add(self.#field, rhs.#field) }
+                        #[inline]
+                        fn add(self, rhs: Self) -> Self::Output {
+                            #res
+                        }
                     }
                 }
-            },
-            Wrapper::Sub => quote! {
-                #[automatically_derived]
-                impl #impl_generics ::core::ops::Sub for #ident_name #ty_generics #where_clause
-                {
-                    type Output = Self;
+            }
+            Wrapper::Sub => {
+                let res = wrap_output(quote! { ::core::ops::Sub::sub(self.#field, rhs.#field) });
+                quote! {
+                    #[automatically_derived]
+                    impl #impl_generics ::core::ops::Sub for #ident_name #ty_generics #where_clause
+                    {
+                        type Output = #output_ty;
 
-                    #[inline]
-                    fn sub(self, rhs: Self) -> Self {
-                        Self { #field: ::core::ops::Sub::sub(self.#field, rhs.#field) }
+                        #[inline]
+                        fn sub(self, rhs: Self) -> Self::Output {
+                            #res
+                        }
                     }
                 }
-            },
-            Wrapper::Mul => quote! {
-                #[automatically_derived]
-                impl #impl_generics ::core::ops::Mul for #ident_name #ty_generics #where_clause
-                {
-                    type Output = Self;
+            }
+            Wrapper::Mul => {
+                let res = wrap_output(quote! { ::core::ops::Mul::mul(self.#field, rhs.#field) });
+                quote! {
+                    #[automatically_derived]
+                    impl #impl_generics ::core::ops::Mul for #ident_name #ty_generics #where_clause
+                    {
+                        type Output = #output_ty;
 
-                    #[inline]
-                    fn mul(self, rhs: Self) -> Self {
-                        Self { #field: ::core::ops::Mul::mul(self.#field, rhs.#field) }
+                        #[inline]
+                        fn mul(self, rhs: Self) -> Self::Output {
+                            #res
+                        }
                     }
                 }
-            },
-            Wrapper::Div => quote! {
-                #[automatically_derived]
-                impl #impl_generics ::core::ops::Div for #ident_name #ty_generics #where_clause
-                {
-                    type Output = Self;
+            }
+            Wrapper::Div => {
+                let res = wrap_output(quote! { ::core::ops::Div::div(self.#field, rhs.#field) });
+                quote! {
+                    #[automatically_derived]
+                    impl #impl_generics ::core::ops::Div for #ident_name #ty_generics #where_clause
+                    {
+                        type Output = #output_ty;
 
-                    #[inline]
-                    fn div(self, rhs: Self) -> Self {
-                        Self { #field: ::core::ops::Div::div(self.#field, rhs.#field) }
+                        #[inline]
+                        fn div(self, rhs: Self) -> Self::Output {
+                            #res
+                        }
                     }
                 }
-            },
-            Wrapper::Rem => quote! {
-                #[automatically_derived]
-                impl #impl_generics ::core::ops::Rem for #ident_name #ty_generics #where_clause
-                {
-                    type Output = Self;
+            }
+            Wrapper::Rem => {
+                let res = wrap_output(quote! { ::core::ops::Rem::rem(self.#field, rhs.#field) });
+                quote! {
+                    #[automatically_derived]
+                    impl #impl_generics ::core::ops::Rem for #ident_name #ty_generics #where_clause
+                    {
+                        type Output = #output_ty;
 
-                    #[inline]
-                    fn rem(self, rhs: Self) -> Self {
-                        Self { #field: ::core::ops::Rem::rem(self.#field, rhs.#field) }
+                        #[inline]
+                        fn rem(self, rhs: Self) -> Self::Output {
+                            #res
+                        }
                     }
                 }
-            },
-            Wrapper::Shl => quote! {
-                #[automatically_derived]
-                impl #impl_generics ::core::ops::Shl for #ident_name #ty_generics #where_clause
-                {
-                    type Output = Self;
+            }
+            Wrapper::Shl => {
+                let res = wrap_output(quote! { ::core::ops::Shl::shl(self.#field, rhs.#field) });
+                quote! {
+                    #[automatically_derived]
+                    impl #impl_generics ::core::ops::Shl for #ident_name #ty_generics #where_clause
+                    {
+                        type Output = #output_ty;
 
-                    #[inline]
-                    fn shl(self, rhs: Self) -> Self {
-                        Self { #field: ::core::ops::Shl::shl(self.#field, rhs.#field) }
+                        #[inline]
+                        fn shl(self, rhs: Self) -> Self::Output {
+                            #res
+                        }
                     }
                 }
-            },
-            Wrapper::Shr => quote! {
-                #[automatically_derived]
-                impl #impl_generics ::core::ops::Shr for #ident_name #ty_generics #where_clause
-                {
-                    type Output = Self;
+            }
+            Wrapper::Shr => {
+                let res = wrap_output(quote! { ::core::ops::Shr::shr(self.#field, rhs.#field) });
+                quote! {
+                    #[automatically_derived]
+                    impl #impl_generics ::core::ops::Shr for #ident_name #ty_generics #where_clause
+                    {
+                        type Output = #output_ty;
 
-                    #[inline]
-                    fn shr(self, rhs: Self) -> Self {
-                        Self { #field: ::core::ops::Shr::shr(self.#field, rhs.#field) }
+                        #[inline]
+                        fn shr(self, rhs: Self) -> Self::Output {
+                            #res
+                        }
                     }
                 }
-            },
-            Wrapper::BitAnd => quote! {
+            }
+            Wrapper::BitAnd => {
+                let res =
+                    wrap_output(quote! { ::core::ops::BitAnd::bitand(self.#field, rhs.#field) });
+                quote! {
+                    #[automatically_derived]
+                    impl #impl_generics ::core::ops::BitAnd for #ident_name #ty_generics #where_clause
+                    {
+                        type Output = #output_ty;
+
+                        #[inline]
+                        fn bitand(self, rhs: Self) -> Self::Output {
+                            #res
+                        }
+                    }
+                }
+            }
+            Wrapper::BitOr => {
+                let res =
+                    wrap_output(quote! { ::core::ops::BitOr::bitor(self.#field, rhs.#field) });
+                quote! {
+                    #[automatically_derived]
+                    impl #impl_generics ::core::ops::BitOr for #ident_name #ty_generics #where_clause
+                    {
+                        type Output = #output_ty;
+
+                        #[inline]
+                        fn bitor(self, rhs: Self) -> Self::Output {
+                            #res
+                        }
+                    }
+                }
+            }
+            Wrapper::BitXor => {
+                let res =
+                    wrap_output(quote! { ::core::ops::BitXor::bitxor(self.#field, rhs.#field) });
+                quote! {
+                    #[automatically_derived]
+                    impl #impl_generics ::core::ops::BitXor for #ident_name #ty_generics #where_clause
+                    {
+                        type Output = #output_ty;
+
+                        #[inline]
+                        fn bitxor(self, rhs: Self) -> Self::Output {
+                            #res
+                        }
+                    }
+                }
+            }
+            Wrapper::Zero => quote! {
                 #[automatically_derived]
-                impl #impl_generics ::core::ops::BitAnd for #ident_name #ty_generics #where_clause
+                impl #impl_generics ::num_traits::Zero for #ident_name #ty_generics #where_clause
                 {
-                    type Output = Self;
+                    #[inline]
+                    fn zero() -> Self {
+                        <Self as #amplify_crate::Wrapper>::from_inner(::num_traits::Zero::zero())
+                    }
 
                     #[inline]
-                    fn bitand(self, rhs: Self) -> Self {
-                        Self { #field: ::core::ops::BitAnd::bitand(self.#field, rhs.#field) }
+                    fn is_zero(&self) -> bool {
+                        ::num_traits::Zero::is_zero(&self.#field)
                     }
                 }
             },
-            Wrapper::BitOr => quote! {
+            Wrapper::One => quote! {
                 #[automatically_derived]
-                impl #impl_generics ::core::ops::BitOr for #ident_name #ty_generics #where_clause
+                impl #impl_generics ::num_traits::One for #ident_name #ty_generics #where_clause
                 {
-                    type Output = Self;
-
                     #[inline]
-                    fn bitor(self, rhs: Self) -> Self {
-                        Self { #field: ::core::ops::BitOr::bitor(self.#field, rhs.#field) }
+                    fn one() -> Self {
+                        <Self as #amplify_crate::Wrapper>::from_inner(::num_traits::One::one())
                     }
                 }
             },
-            Wrapper::BitXor => quote! {
+            Wrapper::Pow => {
+                // Exponent may be of any type supported by the wrapped type
+                let mut generics = input.generics.clone();
+                generics.params.push(parse_quote! { __Rhs });
+                generics
+                    .make_where_clause()
+                    .predicates
+                    .push(parse_quote! { #from: ::num_traits::Pow<__Rhs, Output = #from> });
+                let (impl_generics, _, where_clause) = generics.split_for_impl();
+                quote! {
+                    #[automatically_derived]
+                    impl #impl_generics ::num_traits::Pow<__Rhs> for #ident_name #ty_generics #where_clause
+                    {
+                        type Output = Self;
+
+                        #[inline]
+                        fn pow(self, rhs: __Rhs) -> Self {
+                            <Self as #amplify_crate::Wrapper>::from_inner(
+                                ::num_traits::Pow::pow(self.#field, rhs)
+                            )
+                        }
+                    }
+                }
+            }
+            Wrapper::Num => quote! {
                 #[automatically_derived]
-                impl #impl_generics ::core::ops::BitXor for #ident_name #ty_generics #where_clause
+                impl #impl_generics ::num_traits::Num for #ident_name #ty_generics #where_clause
                 {
-                    type Output = Self;
+                    type FromStrRadixErr = <#from as ::num_traits::Num>::FromStrRadixErr;
 
                     #[inline]
-                    fn bitxor(self, rhs: Self) -> Self {
-                        Self { #field: ::core::ops::BitXor::bitxor(self.#field, rhs.#field) }
+                    fn from_str_radix(s: &str, radix: u32) -> ::core::result::Result<Self, Self::FromStrRadixErr> {
+                        <#from as ::num_traits::Num>::from_str_radix(s, radix)
+                            .map(<Self as #amplify_crate::Wrapper>::from_inner)
                     }
                 }
             },
             Wrapper::NoRefs |
+            Wrapper::NoStd |
             Wrapper::Hex |
             Wrapper::Exp |
             Wrapper::NumberFmt |
             Wrapper::RangeOps |
             Wrapper::MathOps |
             Wrapper::BoolOps |
-            Wrapper::BitOps => unreachable!(),
+            Wrapper::BitOps |
+            Wrapper::NumOps => unreachable!(),
         }
     }
 }
@@ -654,12 +970,33 @@ impl FromPath for WrapperMut {
 
     fn is_not_ref(&self) -> bool { *self != WrapperMut::AsMut && *self != WrapperMut::BorrowMut }
 
+    fn is_mut_ref(&self) -> bool {
+        matches!(
+            self,
+            WrapperMut::DerefMut |
+                WrapperMut::AsMut |
+                WrapperMut::AsSliceMut |
+                WrapperMut::BorrowMut |
+                WrapperMut::BorrowSliceMut
+        )
+    }
+
+    fn protects_inner(&self) -> bool {
+        *self == WrapperMut::NoInner || *self == WrapperMut::UnsafeInner
+    }
+
+    fn is_binary_op(&self) -> bool { false }
+
+    fn is_index(&self) -> bool { *self == WrapperMut::IndexMut }
+
     fn from_path(path: &Path) -> Result<Option<Self>> {
         path.segments.first().map_or(
             Err(attr_err!(path.span(), NAME, "must contain at least one identifier", EXAMPLE)),
             |segment| {
                 Ok(match segment.ident.to_string().as_str() {
                     "NoRefs" => Some(WrapperMut::NoRefs),
+                    "no_inner" => Some(WrapperMut::NoInner),
+                    "unsafe_inner" => Some(WrapperMut::UnsafeInner),
                     "DerefMut" => Some(WrapperMut::DerefMut),
                     "AsMut" => Some(WrapperMut::AsMut),
                     "AsSliceMut" => Some(WrapperMut::AsSliceMut),
@@ -736,11 +1073,12 @@ impl WrapperMut {
         input: &DeriveInput,
         _from: &Type,
         field: &TokenStream2,
+        index: Option<&Type>,
+        amplify_crate: &Path,
     ) -> TokenStream2 {
-        let impl_generics_params = input.generics.params.clone();
         let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
         let ident_name = &input.ident;
-        let amplify_crate = get_amplify_crate(input);
+        let index_ty = index.map_or_else(|| quote! { usize }, |ty| quote! { #ty });
 
         match self {
             WrapperMut::DerefMut => quote! {
@@ -755,7 +1093,7 @@ impl WrapperMut {
             },
             WrapperMut::AsMut => quote! {
                 #[automatically_derived]
-                impl #impl_generics ::core::convert::AsMut<<#ident_name #impl_generics as #amplify_crate::Wrapper>::Inner> for #ident_name #ty_generics #where_clause {
+                impl #impl_generics ::core::convert::AsMut<<#ident_name #ty_generics as #amplify_crate::Wrapper>::Inner> for #ident_name #ty_generics #where_clause {
                     #[inline]
                     fn as_mut(&mut self) -> &mut <Self as #amplify_crate::Wrapper>::Inner {
                         &mut self.#field
@@ -774,7 +1112,7 @@ impl WrapperMut {
             },
             WrapperMut::BorrowMut => quote! {
                 #[automatically_derived]
-                impl #impl_generics ::core::borrow::BorrowMut<<#ident_name #impl_generics as #amplify_crate::Wrapper>::Inner> for #ident_name #ty_generics #where_clause {
+                impl #impl_generics ::core::borrow::BorrowMut<<#ident_name #ty_generics as #amplify_crate::Wrapper>::Inner> for #ident_name #ty_generics #where_clause {
                     #[inline]
                     fn borrow_mut(&mut self) -> &mut <Self as #amplify_crate::Wrapper>::Inner {
                         &mut self.#field
@@ -792,17 +1130,13 @@ impl WrapperMut {
                 }
             },
             WrapperMut::IndexMut => {
-                let where_clause = match where_clause {
-                    None => quote! { where },
-                    Some(_) => quote! { #where_clause },
-                };
                 quote! {
                     #[automatically_derived]
-                    impl <#impl_generics_params> ::core::ops::IndexMut<usize> for #ident_name #ty_generics #where_clause
+                    impl #impl_generics ::core::ops::IndexMut<#index_ty> for #ident_name #ty_generics #where_clause
                     {
                         #[inline]
-                        fn index_mut(&mut self, index: usize) -> &mut Self::Output {
-                            self.as_mut().index_mut(index)
+                        fn index_mut(&mut self, index: #index_ty) -> &mut Self::Output {
+                            ::core::ops::IndexMut::index_mut(&mut self.#field, index)
                         }
                     }
                 }
@@ -810,11 +1144,11 @@ impl WrapperMut {
             WrapperMut::IndexRangeMut => {
                 quote! {
                     #[automatically_derived]
-                    impl <#impl_generics_params> ::core::ops::IndexMut<::core::ops::Range<usize>> for #ident_name #ty_generics #where_clause
+                    impl #impl_generics ::core::ops::IndexMut<::core::ops::Range<usize>> for #ident_name #ty_generics #where_clause
                     {
                         #[inline]
                         fn index_mut(&mut self, index: ::core::ops::Range<usize>) -> &mut Self::Output {
-                            self.as_mut().index_mut(index)
+                            ::core::ops::IndexMut::index_mut(&mut self.#field, index)
                         }
                     }
                 }
@@ -822,11 +1156,11 @@ impl WrapperMut {
             WrapperMut::IndexFromMut => {
                 quote! {
                     #[automatically_derived]
-                    impl <#impl_generics_params> ::core::ops::IndexMut<::core::ops::RangeFrom<usize>> for #ident_name #ty_generics #where_clause
+                    impl #impl_generics ::core::ops::IndexMut<::core::ops::RangeFrom<usize>> for #ident_name #ty_generics #where_clause
                     {
                         #[inline]
                         fn index_mut(&mut self, index: ::core::ops::RangeFrom<usize>) -> &mut Self::Output {
-                            self.as_mut().index_mut(index)
+                            ::core::ops::IndexMut::index_mut(&mut self.#field, index)
                         }
                     }
                 }
@@ -834,11 +1168,11 @@ impl WrapperMut {
             WrapperMut::IndexToMut => {
                 quote! {
                     #[automatically_derived]
-                    impl <#impl_generics_params> ::core::ops::IndexMut<::core::ops::RangeTo<usize>> for #ident_name #ty_generics #where_clause
+                    impl #impl_generics ::core::ops::IndexMut<::core::ops::RangeTo<usize>> for #ident_name #ty_generics #where_clause
                     {
                         #[inline]
                         fn index_mut(&mut self, index: ::core::ops::RangeTo<usize>) -> &mut Self::Output {
-                            self.as_mut().index_mut(index)
+                            ::core::ops::IndexMut::index_mut(&mut self.#field, index)
                         }
                     }
                 }
@@ -846,11 +1180,11 @@ impl WrapperMut {
             WrapperMut::IndexInclusiveMut => {
                 quote! {
                     #[automatically_derived]
-                    impl <#impl_generics_params> ::core::ops::IndexMut<::core::ops::RangeInclusive<usize>> for #ident_name #ty_generics #where_clause
+                    impl #impl_generics ::core::ops::IndexMut<::core::ops::RangeInclusive<usize>> for #ident_name #ty_generics #where_clause
                     {
                         #[inline]
                         fn index_mut(&mut self, index: ::core::ops::RangeInclusive<usize>) -> &mut Self::Output {
-                            self.as_mut().index_mut(index)
+                            ::core::ops::IndexMut::index_mut(&mut self.#field, index)
                         }
                     }
                 }
@@ -858,11 +1192,11 @@ impl WrapperMut {
             WrapperMut::IndexToInclusiveMut => {
                 quote! {
                     #[automatically_derived]
-                    impl <#impl_generics_params> ::core::ops::IndexMut<::core::ops::RangeToInclusive<usize>> for #ident_name #ty_generics #where_clause
+                    impl #impl_generics ::core::ops::IndexMut<::core::ops::RangeToInclusive<usize>> for #ident_name #ty_generics #where_clause
                     {
                         #[inline]
                         fn index_mut(&mut self, index: ::core::ops::RangeToInclusive<usize>) -> &mut Self::Output {
-                            self.as_mut().index_mut(index)
+                            ::core::ops::IndexMut::index_mut(&mut self.#field, index)
                         }
                     }
                 }
@@ -870,11 +1204,11 @@ impl WrapperMut {
             WrapperMut::IndexFullMut => {
                 quote! {
                     #[automatically_derived]
-                    impl <#impl_generics_params> ::core::ops::IndexMut<::core::ops::RangeFull> for #ident_name #ty_generics #where_clause
+                    impl #impl_generics ::core::ops::IndexMut<::core::ops::RangeFull> for #ident_name #ty_generics #where_clause
                     {
                         #[inline]
                         fn index_mut(&mut self, index: ::core::ops::RangeFull) -> &mut Self::Output {
-                            self.as_mut().index_mut(index)
+                            ::core::ops::IndexMut::index_mut(&mut self.#field, index)
                         }
                     }
                 }
@@ -980,6 +1314,8 @@ impl WrapperMut {
                 }
             },
             WrapperMut::NoRefs |
+            WrapperMut::NoInner |
+            WrapperMut::UnsafeInner |
             WrapperMut::RangeMut |
             WrapperMut::MathAssign |
             WrapperMut::BoolAssign |
@@ -991,23 +1327,57 @@ impl WrapperMut {
 pub(crate) fn inner(input: DeriveInput) -> Result<TokenStream2> {
     let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
     let ident_name = &input.ident;
-    let amplify_crate = get_amplify_crate(&input);
+    let mut warnings = Warnings::new();
+    // Without `std` feature generated code relies only on `core` and `alloc`
+    let settings =
+        CrateSettings::with(&input.attrs)?.with_derive_no_std(&input.attrs, NAME, &mut warnings);
+    let amplify_crate = &settings.amplify;
+    let no_std = settings.no_std;
 
-    let (field, from) = get_params(&input)?;
+    let selected = get_params(&input)?;
+    let construct = get_constructor(&input, selected.index)?;
+    let (field, from) = (selected.member.to_token_stream(), selected.ty);
 
-    let wrappers = get_wrappers::<Wrapper>(&input)?;
+    let (mut wrappers, params) = get_wrappers::<Wrapper>(&input, &mut warnings)?;
+    wrappers.retain(|w| *w != Wrapper::NoStd);
+    // amplify provides blanket `ToHex` implementation for all `LowerHex` types
+    if wrappers.contains(&Wrapper::ToHex) && wrappers.contains(&Wrapper::LowerHex) {
+        return Err(attr_err!(
+            input.span(),
+            NAME,
+            "`ToHex` is already implemented by amplify for types deriving `LowerHex` (including \
+             `Hex` and `NumberFmt` groups)",
+            "#[wrapper(FromHex, ToHex)]"
+        ));
+    }
     let wrapper_derive = wrappers
         .iter()
-        .map(|w| w.into_token_stream2(&input, &from, &field));
+        .map(|w| w.into_token_stream2(&input, &from, &field, params.get(w), amplify_crate, no_std));
+    // A bare type parameter can't implement foreign `From` trait
+    let into_inner = if is_type_param(&from, &input.generics) {
+        quote! {}
+    } else {
+        quote! {
+            #[automatically_derived]
+            impl #impl_generics ::core::convert::From<#ident_name #ty_generics> for #from #where_clause {
+                #[inline]
+                fn from(wrapped: #ident_name #ty_generics) -> Self {
+                    wrapped.#field
+                }
+            }
+        }
+    };
 
     Ok(quote! {
+        #warnings
+
         #[automatically_derived]
         impl #impl_generics #amplify_crate::Wrapper for #ident_name #ty_generics #where_clause {
             type Inner = #from;
 
             #[inline]
             fn from_inner(inner: Self::Inner) -> Self {
-                Self::from(inner)
+                #construct
             }
 
             #[inline]
@@ -1021,13 +1391,7 @@ pub(crate) fn inner(input: DeriveInput) -> Result<TokenStream2> {
             }
         }
 
-        #[automatically_derived]
-        impl #impl_generics ::core::convert::From<#ident_name #ty_generics> for #from #where_clause {
-            #[inline]
-            fn from(wrapped: #ident_name #ty_generics) -> Self {
-                wrapped.#field
-            }
-        }
+        #into_inner
 
         #( #wrapper_derive )*
     })
@@ -1036,137 +1400,504 @@ pub(crate) fn inner(input: DeriveInput) -> Result<TokenStream2> {
 pub(crate) fn inner_mut(input: DeriveInput) -> Result<TokenStream2> {
     let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
     let ident_name = &input.ident;
-    let amplify_crate = get_amplify_crate(&input);
+    let settings = CrateSettings::with(&input.attrs)?;
+    let amplify_crate = &settings.amplify;
 
-    let (field, from) = get_params(&input)?;
+    let selected = get_params(&input)?;
+    let (field, from) = (selected.member.to_token_stream(), selected.ty);
 
-    let wrappers = get_wrappers::<WrapperMut>(&input)?;
+    let mut warnings = Warnings::new();
+    let (mut wrappers, params) = get_wrappers::<WrapperMut>(&input, &mut warnings)?;
+    let no_inner = wrappers.contains(&WrapperMut::NoInner);
+    let unsafe_inner = wrappers.contains(&WrapperMut::UnsafeInner);
+    if no_inner && unsafe_inner {
+        return Err(attr_err!(
+            input.span(),
+            WrapperMut::IDENT,
+            "`no_inner` and `unsafe_inner` can't be used together",
+            "#[wrapper_mut(no_inner)]"
+        ));
+    }
+    wrappers.retain(|w| *w != WrapperMut::NoInner && *w != WrapperMut::UnsafeInner);
     let wrapper_derive = wrappers
         .iter()
-        .map(|w| w.into_token_stream2(&input, &from, &field));
+        .map(|w| w.into_token_stream2(&input, &from, &field, params.get(w), amplify_crate));
+
+    // Types enforcing invariants on the wrapped data may opt out from exposing
+    // safe mutable access to it, keeping the rest of mutable derivations
+    let inner_mut = if no_inner {
+        quote! {}
+    } else if unsafe_inner {
+        quote! {
+            #[automatically_derived]
+            impl #impl_generics #ident_name #ty_generics #where_clause {
+                /// Returns a mutable reference to the inner representation
+                /// for the wrapper type.
+                ///
+                /// # Safety
+                ///
+                /// The caller must preserve all invariants maintained by the
+                /// wrapper type over its inner data.
+                #[doc(hidden)]
+                #[inline]
+                pub unsafe fn as_inner_mut(&mut self) -> &mut <Self as #amplify_crate::Wrapper>::Inner {
+                    &mut self.#field
+                }
+            }
+        }
+    } else {
+        quote! {
+            #[automatically_derived]
+            impl #impl_generics #amplify_crate::WrapperMut for #ident_name #ty_generics #where_clause {
+                #[inline]
+                fn as_inner_mut(&mut self) -> &mut <Self as #amplify_crate::Wrapper>::Inner {
+                    &mut self.#field
+                }
+            }
+        }
+    };
+
+    Ok(quote! {
+        #warnings
+
+        #inner_mut
+
+        #( #wrapper_derive )*
+    })
+}
+
+pub(crate) fn inner_deref(input: DeriveInput) -> Result<TokenStream2> {
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let ident_name = &input.ident;
+
+    let SelectedField {
+        member: field,
+        ty: target,
+        ..
+    } = select_field(&input, "`Deref`", "deref")?;
 
     Ok(quote! {
         #[automatically_derived]
-        impl #impl_generics #amplify_crate::WrapperMut for #ident_name #ty_generics #where_clause {
+        impl #impl_generics ::core::ops::Deref for #ident_name #ty_generics #where_clause {
+            type Target = #target;
+
             #[inline]
-            fn as_inner_mut(&mut self) -> &mut <Self as #amplify_crate::Wrapper>::Inner {
+            fn deref(&self) -> &Self::Target {
+                &self.#field
+            }
+        }
+    })
+}
+
+pub(crate) fn inner_deref_mut(input: DeriveInput) -> Result<TokenStream2> {
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let ident_name = &input.ident;
+
+    let SelectedField { member: field, .. } = select_field(&input, "`DerefMut`", "deref")?;
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics ::core::ops::DerefMut for #ident_name #ty_generics #where_clause {
+            #[inline]
+            fn deref_mut(&mut self) -> &mut Self::Target {
                 &mut self.#field
             }
         }
+    })
+}
 
-        #( #wrapper_derive )*
+pub(crate) fn inner_index_by_field(input: DeriveInput) -> Result<TokenStream2> {
+    let ident_name = &input.ident;
+    let (_, ty_generics, _) = input.generics.split_for_impl();
+
+    let SelectedField {
+        member: field, ty, ..
+    } = select_field(&input, "`IndexByField`", "index")?;
+
+    // Index type is taken from the field, so it gets its own generic parameter
+    let mut generics = input.generics.clone();
+    generics.params.push(parse_quote! { __Idx });
+    let mut generics_mut = generics.clone();
+    amplify_syn::add_bounds(&mut generics, [(
+        ty.clone(),
+        parse_quote! { ::core::ops::Index<__Idx> },
+    )]);
+    amplify_syn::add_bounds(&mut generics_mut, [(
+        ty.clone(),
+        parse_quote! { ::core::ops::IndexMut<__Idx> },
+    )]);
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+    let (impl_generics_mut, _, where_clause_mut) = generics_mut.split_for_impl();
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics ::core::ops::Index<__Idx> for #ident_name #ty_generics #where_clause {
+            type Output = <#ty as ::core::ops::Index<__Idx>>::Output;
+
+            #[inline]
+            fn index(&self, index: __Idx) -> &Self::Output {
+                &self.#field[index]
+            }
+        }
+
+        #[automatically_derived]
+        impl #impl_generics_mut ::core::ops::IndexMut<__Idx> for #ident_name #ty_generics #where_clause_mut {
+            #[inline]
+            fn index_mut(&mut self, index: __Idx) -> &mut Self::Output {
+                &mut self.#field[index]
+            }
+        }
     })
 }
 
-fn get_params(input: &DeriveInput) -> Result<(TokenStream2, Type)> {
-    let data = match input.data {
-        Data::Struct(ref data) => data,
-        Data::Enum(_) => {
-            return Err(Error::new_spanned(input, "Deriving wrapper is not supported in enums"));
+pub(crate) fn inner_pair(input: DeriveInput) -> Result<TokenStream2> {
+    let fields = match input.data {
+        Data::Struct(ref data) => &data.fields,
+        _ => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "Deriving `WrapperPair` is supported only for structs",
+            ));
         }
-        //strict_encode_inner_enum(&input, &data),
-        Data::Union(_) => {
-            return Err(Error::new_spanned(input, "Deriving wrapper is not supported in unions"));
+    };
+    let members = fields
+        .iter()
+        .enumerate()
+        .map(|(index, field)| {
+            let member = field
+                .ident
+                .clone()
+                .map_or_else(|| Member::from(index), Member::from);
+            (member, field)
+        })
+        .collect::<Vec<_>>();
+    let marked = members
+        .iter()
+        .filter(|(_, field)| field.attrs.iter().any(|attr| attr.path().is_ident("wrap")))
+        .collect::<Vec<_>>();
+    let pair = match (members.len(), marked.len()) {
+        (2, 0) => vec![&members[0], &members[1]],
+        (_, 2) => marked,
+        _ => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "`WrapperPair` requires structure to have exactly two fields, or to mark two of \
+                 its fields with `#[wrap]` attribute",
+            ));
         }
     };
+    let (first, first_ty) = (&pair[0].0, &pair[0].1.ty);
+    let (second, second_ty) = (&pair[1].0, &pair[1].1.ty);
 
-    let field;
-    let mut from;
-    match data.fields {
-        Fields::Named(ref fields) => {
-            let mut source = None;
-            from = fields.named[0].ty.clone();
-            for field in &fields.named {
-                for attr in &field.attrs {
-                    if attr.path.is_ident("wrap") {
-                        if source.is_some() {
-                            return Err(Error::new_spanned(
-                                attr,
-                                "Only a single field may be wrapped",
-                            ));
-                        }
-                        source = Some(field.ident.clone().expect("we know it's named"));
-                        from = field.ty.clone();
-                    }
-                }
-            }
-            if source.is_none() && fields.named.len() > 1 {
+    // Fields which are not wrapped are initialized in the same way as by
+    // `Wrapper` derive
+    let mut values = Vec::with_capacity(members.len());
+    for (member, field) in &members {
+        let default = field
+            .attrs
+            .iter()
+            .find(|attr| attr.path().is_ident("wrap_default"));
+        values.push(match (member, default) {
+            (member, None) if member == first => quote! { #member: first },
+            (member, None) if member == second => quote! { #member: second },
+            (member, Some(attr)) if member == first || member == second => {
                 return Err(Error::new_spanned(
-                    fields,
-                    "When the structure has multiple fields you must point out the one you will \
-                     wrap by using `#[wrap]` attribute",
+                    attr,
+                    "`#[wrap_default]` can't be used on the wrapped field",
                 ));
             }
-            let source = source
-                .unwrap_or_else(|| fields.named[0].ident.clone().expect("we know it's named"));
-            field = quote! { #source };
+            (member, Some(attr)) => {
+                let expr = attr.parse_args::<Expr>()?;
+                quote! { #member: #expr }
+            }
+            (member, None) => quote! { #member: ::core::default::Default::default() },
+        });
+    }
+
+    let ops = pair_ops(&input, first_ty, second_ty, first, second)?;
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let ident_name = &input.ident;
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics #ident_name #ty_generics #where_clause {
+            /// Constructs the type from the pair of its inner values.
+            #[inline]
+            pub fn from_inner((first, second): (#first_ty, #second_ty)) -> Self {
+                Self { #( #values ),* }
+            }
+
+            /// Returns references to the pair of inner values.
+            #[inline]
+            pub fn as_inner(&self) -> (&#first_ty, &#second_ty) {
+                (&self.#first, &self.#second)
+            }
+
+            /// Unwraps the pair of inner values.
+            #[inline]
+            pub fn into_inner(self) -> (#first_ty, #second_ty) {
+                (self.#first, self.#second)
+            }
         }
-        Fields::Unnamed(ref fields) => {
-            let mut source = None;
-            from = fields.unnamed[0].ty.clone();
-            for (index, field) in fields.unnamed.iter().enumerate() {
-                for attr in &field.attrs {
-                    if attr.path.is_ident("wrap") {
-                        if source.is_some() {
-                            return Err(Error::new_spanned(
-                                attr,
-                                "Only a single field may be wrapped",
-                            ));
-                        }
-                        let i = Index::from(index);
-                        source = Some(quote! { #i });
-                        from = field.ty.clone();
+
+        #[automatically_derived]
+        impl #impl_generics ::core::convert::From<#ident_name #ty_generics> for (#first_ty, #second_ty) #where_clause {
+            #[inline]
+            fn from(wrapped: #ident_name #ty_generics) -> Self {
+                wrapped.into_inner()
+            }
+        }
+
+        #ops
+    })
+}
+
+/// Produces component-wise arithmetic operations requested with
+/// `#[wrapper_pair(...)]` attribute.
+fn pair_ops(
+    input: &DeriveInput,
+    first_ty: &Type,
+    second_ty: &Type,
+    first: &Member,
+    second: &Member,
+) -> Result<TokenStream2> {
+    let ops =
+        merge_attr_args("wrapper_pair", &input.attrs, DuplicatePolicy::Error, |op: &Ident| {
+            Some(op.to_string())
+        })?;
+    if let Some(op) = ops
+        .iter()
+        .find(|op| !["Add", "Sub", "Mul", "Div", "Rem", "Neg"].contains(&op.to_string().as_str()))
+    {
+        return Err(attr_err!(
+            op,
+            "unsupported operation; only `Add`, `Sub`, `Mul`, `Div`, `Rem` and `Neg` may be \
+             derived"
+        ));
+    }
+
+    let params = input
+        .generics
+        .type_params()
+        .map(|param| param.ident.clone())
+        .collect::<Vec<_>>();
+    let params = params.iter().collect::<Vec<_>>();
+    let mut impls = vec![];
+    for op in ops {
+        let method = Ident::new(&op.to_string().to_lowercase(), op.span());
+        let mut generics = input.generics.clone();
+        for ty in [first_ty, second_ty] {
+            if mentions_params(quote! { #ty }, &params) {
+                generics
+                    .make_where_clause()
+                    .predicates
+                    .push(parse_quote! { #ty: ::core::ops::#op<Output = #ty> });
+            }
+        }
+        let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+        let ident_name = &input.ident;
+        impls.push(if op == "Neg" {
+            quote! {
+                #[automatically_derived]
+                impl #impl_generics ::core::ops::Neg for #ident_name #ty_generics #where_clause {
+                    type Output = Self;
+
+                    #[inline]
+                    fn neg(self) -> Self {
+                        Self::from_inner((
+                            ::core::ops::Neg::neg(self.#first),
+                            ::core::ops::Neg::neg(self.#second),
+                        ))
                     }
                 }
             }
-            if source.is_none() && fields.unnamed.len() > 1 {
-                return Err(Error::new_spanned(
-                    fields,
-                    "When the structure has multiple fields you must point out the one you will \
-                     wrap by using `#[wrap]` attribute",
-                ));
+        } else {
+            quote! {
+                #[automatically_derived]
+                impl #impl_generics ::core::ops::#op for #ident_name #ty_generics #where_clause {
+                    type Output = Self;
+
+                    #[inline]
+                    fn #method(self, rhs: Self) -> Self {
+                        Self::from_inner((
+                            ::core::ops::#op::#method(self.#first, rhs.#first),
+                            ::core::ops::#op::#method(self.#second, rhs.#second),
+                        ))
+                    }
+                }
             }
-            field = source.unwrap_or(quote! { 0 });
+        });
+    }
+
+    Ok(quote! { #( #impls )* })
+}
+
+/// Checks whether the type is a type parameter of the generics (or a reference
+/// to it), which can't be a `Self` type of foreign trait implementations.
+fn is_type_param(ty: &Type, generics: &Generics) -> bool {
+    match ty {
+        Type::Reference(reference) => is_type_param(&reference.elem, generics),
+        Type::Paren(paren) => is_type_param(&paren.elem, generics),
+        Type::Group(group) => is_type_param(&group.elem, generics),
+        Type::Path(path) if path.qself.is_none() => path
+            .path
+            .get_ident()
+            .map_or(false, |ident| generics.type_params().any(|param| &param.ident == ident)),
+        _ => false,
+    }
+}
+
+fn get_params(input: &DeriveInput) -> Result<SelectedField> {
+    select_field(input, "wrapper", "wrap")
+}
+
+/// Selects the field marked with `#[<marker>]` attribute, or the only field
+/// of the structure not counting `PhantomData` fields.
+pub(crate) fn select_field(
+    input: &DeriveInput,
+    derive: &str,
+    marker: &str,
+) -> Result<SelectedField> {
+    let fields = match input.data {
+        Data::Struct(ref data) => &data.fields,
+        Data::Enum(_) => {
+            return Err(Error::new_spanned(
+                input,
+                format!("Deriving {} is not supported in enums", derive),
+            ));
         }
-        Fields::Unit => {
+        Data::Union(_) => {
             return Err(Error::new_spanned(
                 input,
-                "Deriving wrapper is meaningless for unit structs",
+                format!("Deriving {} is not supported in unions", derive),
             ));
         }
     };
-    Ok((field, from))
+    if let Fields::Unit = fields {
+        return Err(Error::new_spanned(
+            input,
+            format!("Deriving {} is meaningless for unit structs", derive),
+        ));
+    }
+    amplify_syn::select_field(fields, marker, SelectPolicy { skip_phantom: true })
 }
 
-fn get_wrappers<T: FromPath>(input: &DeriveInput) -> Result<Vec<T>> {
+/// Produces expression constructing the wrapper from the `inner` value; fields
+/// which are not wrapped are initialized with the expression provided in their
+/// `#[wrap_default(expr)]` attribute or with [`Default::default`].
+fn get_constructor(input: &DeriveInput, wrapped: usize) -> Result<TokenStream2> {
+    let fields = match input.data {
+        Data::Struct(ref data) => &data.fields,
+        _ => unreachable!("non-struct data are rejected by get_params"),
+    };
+
+    let mut values = Vec::with_capacity(fields.len());
+    for (index, field) in fields.iter().enumerate() {
+        let wrapped = index == wrapped;
+        let default = field
+            .attrs
+            .iter()
+            .find(|attr| attr.path().is_ident("wrap_default"));
+        values.push(match (wrapped, default) {
+            (true, None) => quote! { inner },
+            (true, Some(attr)) => {
+                return Err(Error::new_spanned(
+                    attr,
+                    "`#[wrap_default]` can't be used on the wrapped field",
+                ));
+            }
+            (false, Some(attr)) => {
+                let expr = attr.parse_args::<Expr>()?;
+                quote! { #expr }
+            }
+            (false, None) => quote! { ::core::default::Default::default() },
+        });
+    }
+
+    Ok(match fields {
+        Fields::Named(_) => {
+            let names = fields.iter().map(|field| &field.ident);
+            quote! { Self { #( #names: #values ),* } }
+        }
+        _ => quote! { Self( #( #values ),* ) },
+    })
+}
+
+fn get_wrappers<T: FromPath>(
+    input: &DeriveInput,
+    warnings: &mut Warnings,
+) -> Result<(Vec<T>, BTreeMap<T, Type>)> {
     let mut wrappers = T::default_set();
-    const WRAPPER_DERIVE_ERR: &str = "Wrapper attributes must be in a form of type list";
-    for attr in input
-        .attrs
-        .iter()
-        .filter(|attr| attr.path.is_ident(T::IDENT))
-    {
-        match attr
-            .parse_meta()
-            .map_err(|_| attr_err!(attr, WRAPPER_DERIVE_ERR))?
-        {
-            Meta::List(MetaList { nested, .. }) => {
-                for meta in nested {
-                    match meta {
-                        NestedMeta::Meta(Meta::Path(path)) => {
-                            T::from_path(&path)?
-                                .ok_or_else(|| attr_err!(path, "Unrecognized wrapper parameter"))?
-                                .populate(&mut wrappers);
-                        }
-                        _ => return Err(attr_err!(meta, WRAPPER_DERIVE_ERR)),
-                    }
-                }
+    let mut requested = Vec::<String>::new();
+    let mut mut_refs = Vec::<Path>::new();
+    let mut params = BTreeMap::new();
+    // Parametrized traits may be given only once, while bare trait names may
+    // repeat
+    let args =
+        merge_attr_args(T::IDENT, &input.attrs, DuplicatePolicy::Error, |arg: &WrapperArg| {
+            arg.param
+                .as_ref()
+                .map(|_| arg.path.to_token_stream().to_string())
+        })?;
+    for WrapperArg { path, param, .. } in args {
+        let wrapper = T::from_path(&path)?
+            .ok_or_else(|| attr_err!(path, "Unrecognized wrapper parameter"))?;
+        // Repeated traits are derived only once
+        let name = path.to_token_stream().to_string();
+        let repeated = requested.contains(&name);
+        if repeated {
+            warnings.warn(&path, format!("wrapper trait `{}` is requested more than once", name));
+        } else {
+            requested.push(name);
+        }
+        if let Some((None, index)) = param {
+            if !wrapper.is_index() {
+                return Err(attr_err!(
+                    path,
+                    "only index traits may be parametrized with the index type"
+                ));
+            }
+            params.insert(wrapper, index);
+        } else if let Some((Some(name), output)) = param {
+            if name != "Output" {
+                return Err(attr_err!(name, "the only supported trait parameter is `Output`"));
+            }
+            if !wrapper.is_binary_op() {
+                return Err(attr_err!(
+                    path,
+                    "`Output` type may be overridden only for binary operations"
+                ));
             }
-            _ => return Err(attr_err!(attr, WRAPPER_DERIVE_ERR)),
+            params.insert(wrapper, output);
+        }
+        if wrapper.is_mut_ref() {
+            mut_refs.push(path.clone());
+        }
+        if !repeated {
+            wrapper.populate(&mut wrappers);
+        }
+    }
+    // Types protecting their inner data must not leak safe mutable references
+    // to it, so default reference traits are skipped and explicit ones are
+    // rejected
+    if wrappers.iter().any(T::protects_inner) {
+        if let Some(path) = mut_refs.first() {
+            return Err(attr_err!(
+                path.span(),
+                T::IDENT,
+                "mutable references to the inner data can't be derived together with `no_inner` \
+                 or `unsafe_inner`",
+                "#[wrapper_mut(unsafe_inner, AddAssign)]"
+            ));
         }
+        wrappers.push(T::NO_REFS);
     }
     if wrappers.contains(&T::NO_REFS) {
-        wrappers = wrappers.into_iter().filter(T::is_not_ref).collect();
+        wrappers.retain(|w| w.is_not_ref() && *w != T::NO_REFS);
     }
-    Ok(wrappers)
+    // Groups may overlap, like `MathOps` and `NumOps`
+    wrappers.sort();
+    wrappers.dedup();
+    Ok((wrappers, params))
 }
@@ -17,13 +17,13 @@ use std::collections::{HashMap, HashSet};
 use std::fmt::{self, Debug, Formatter};
 
 use syn::parse::Parser;
-use syn::parse_quote::ParseQuote;
+use syn::punctuated::Punctuated;
 use syn::{
-    Attribute, Lit, LitBool, LitByteStr, LitChar, LitFloat, LitInt, LitStr, Meta, MetaNameValue,
-    Path, Type,
+    Attribute, Expr, ExprLit, Lit, LitBool, LitByteStr, LitChar, LitFloat, LitInt, LitStr, Meta,
+    MetaNameValue, Path, Type,
 };
 
-use crate::{ArgValue, ArgValueReq, AttrReq, Error, MetaArg, MetaArgList, MetaArgNameValue};
+use crate::{ArgValue, ArgValueReq, AttrReq, Error, MetaArg, MetaArgNameValue};
 
 /// Internal structure representation of a proc macro attribute collected
 /// instances having some specific name (accessible via [`Attr::name()`]).
@@ -217,7 +217,7 @@ impl SingularAttr {
     /// [`Error::SingularAttrRequired`]
     pub fn with(name: impl ToString, attrs: &[Attribute]) -> Result<Self, Error> {
         let name = name.to_string();
-        let mut filtered_attrs = attrs.iter().filter(|attr| attr.path.is_ident(&name));
+        let mut filtered_attrs = attrs.iter().filter(|attr| attr.path().is_ident(&name));
         let res = if let Some(attr) = filtered_attrs.next() {
             SingularAttr::from_attribute(attr)
         } else {
@@ -255,17 +255,23 @@ impl SingularAttr {
     /// and [`Error::SingularAttrRequired`]
     pub fn from_attribute(attr: &Attribute) -> Result<Self, Error> {
         let ident = attr
-            .path
+            .path()
             .get_ident()
             .ok_or(Error::ArgNameMustBeIdent)?
             .to_string();
-        match attr.parse_meta()? {
+        match &attr.meta {
             // `#[attr::path]` - unreachable: filtered in the code above
             Meta::Path(_) => unreachable!(),
             // `#[ident = lit]`
-            Meta::NameValue(MetaNameValue { lit, .. }) => {
-                Ok(SingularAttr::with_literal(ident, lit))
-            }
+            Meta::NameValue(MetaNameValue {
+                value: Expr::Lit(ExprLit { lit, .. }),
+                ..
+            }) => Ok(SingularAttr::with_literal(ident, lit.clone())),
+            // `#[ident = expr]`
+            Meta::NameValue(MetaNameValue { value, .. }) => Ok(SingularAttr {
+                name: ident,
+                value: ArgValue::Expr(value.clone()),
+            }),
             // `#[ident(...)]`
             Meta::List(_) => Err(Error::SingularAttrRequired(ident)),
         }
@@ -373,7 +379,7 @@ impl ParametrizedAttr {
     /// attributes, selecting attributes matching the provided name.
     pub fn with(name: impl ToString + AsRef<str>, attrs: &[Attribute]) -> Result<Self, Error> {
         let mut me = ParametrizedAttr::new(name.to_string());
-        for attr in attrs.iter().filter(|attr| attr.path.is_ident(&name)) {
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident(&name)) {
             me.fuse(attr)?;
         }
         Ok(me)
@@ -382,7 +388,7 @@ impl ParametrizedAttr {
     /// Constructs [`ParametrizedAttr`] from a given [`syn::Attribute`]
     pub fn from_attribute(attr: &Attribute) -> Result<Self, Error> {
         let name = attr
-            .path
+            .path()
             .get_ident()
             .ok_or(Error::ArgNameMustBeIdent)?
             .to_string();
@@ -402,6 +408,21 @@ impl ParametrizedAttr {
             .and_then(|a| a.clone().try_into())
     }
 
+    /// Returns value for a given argument with name `name`, or `None` if the
+    /// argument is absent. Fails if the value can't be converted into `T`.
+    pub fn opt_arg_value<T>(&self, name: &str) -> Result<Option<T>, Error>
+    where T: TryFrom<ArgValue, Error = Error> {
+        self.args
+            .get(name)
+            .map(|a| a.clone().try_into())
+            .transpose()
+    }
+
+    /// Checks whether argument with name `name` is present, which is the way
+    /// to read flag arguments declared with [`AttrReq::flag`].
+    #[inline]
+    pub fn has_arg(&self, name: &str) -> bool { self.args.contains_key(name) }
+
     /// Returns value for a given argument with name `name`, if it is defined,
     /// or panics otherwise.
     pub fn unwrap_arg_value<T>(&self, name: &str) -> T
@@ -538,8 +559,9 @@ impl ParametrizedAttr {
     /// fusion takes a nested meta data.
     #[inline]
     pub fn fuse(&mut self, attr: &Attribute) -> Result<(), Error> {
-        let args = MetaArgList::parse.parse2(attr.tokens.clone().into())?;
-        for arg in args.list {
+        let tokens = attr.meta.require_list()?.tokens.clone();
+        let args = Punctuated::<MetaArg, Token![,]>::parse_terminated.parse2(tokens)?;
+        for arg in args {
             match arg {
                 // `#[ident("literal", ...)]`
                 MetaArg::Literal(Lit::Str(s)) => {
@@ -722,7 +744,7 @@ where T: IntoIterator<Item = &'a Attribute>
 
         let filtered = self
             .into_iter()
-            .filter(|attr| attr.path.is_ident(&name))
+            .filter(|attr| attr.path().is_ident(&name))
             .collect::<Vec<_>>();
 
         if filtered.is_empty() {
@@ -748,7 +770,7 @@ where T: IntoIterator<Item = &'a Attribute>
 
         let filtered = self
             .into_iter()
-            .filter(|attr| attr.path.is_ident(&name))
+            .filter(|attr| attr.path().is_ident(&name))
             .collect::<Vec<_>>();
 
         if filtered.is_empty() {
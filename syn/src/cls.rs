@@ -13,9 +13,12 @@
 // along with this software.
 // If not, see <https://opensource.org/licenses/MIT>.
 
+use std::convert::TryFrom;
+use std::ops::RangeInclusive;
+
 use syn::{Lit, Type};
 
-use crate::{ArgValue, Error};
+use crate::{ArgValue, Error, LitValue};
 
 /// Constrains for attribute value type
 #[derive(Clone, Copy, Ord, PartialOrd, Eq, PartialEq, Hash, Debug)]
@@ -30,6 +33,10 @@ pub enum ValueClass {
 
     /// The value must be of a native rust expression.
     Expr,
+
+    /// The value must be an integer literal lying within the provided
+    /// inclusive range (`min`, `max`)
+    IntRange(i128, i128),
 }
 
 impl From<Lit> for ValueClass {
@@ -84,6 +91,12 @@ impl ValueClass {
     /// Convenience constructor creating
     /// `ValueClass::Literal(LiteralClass::Bool)`
     pub fn bool() -> ValueClass { ValueClass::Literal(LiteralClass::Bool) }
+
+    /// Convenience constructor creating [`ValueClass::IntRange`] from a
+    /// range of allowed values
+    pub fn int_range(range: RangeInclusive<i128>) -> ValueClass {
+        ValueClass::IntRange(*range.start(), *range.end())
+    }
 }
 
 impl ValueClass {
@@ -101,6 +114,29 @@ impl ValueClass {
             (ValueClass::Expr, ArgValue::Type(_) | ArgValue::Expr(_) | ArgValue::Literal(_)) => {
                 Ok(())
             }
+            // Negative numbers are parsed as expressions
+            (ValueClass::Literal(lit @ (LiteralClass::Int | LiteralClass::Float)), value) => {
+                match LitValue::try_from(value) {
+                    Ok(val) if val.class() == lit => Ok(()),
+                    _ => Err(Error::ArgValueTypeMismatch {
+                        attr: attr.to_string(),
+                        arg: arg.to_string(),
+                    }),
+                }
+            }
+            (ValueClass::IntRange(min, max), value) => match LitValue::try_from(value) {
+                Ok(LitValue::Int(value)) if value >= min && value <= max => Ok(()),
+                Ok(LitValue::Int(_)) => Err(Error::ArgValueOutOfRange {
+                    attr: attr.to_string(),
+                    arg: arg.to_string(),
+                    min,
+                    max,
+                }),
+                _ => Err(Error::ArgValueTypeMismatch {
+                    attr: attr.to_string(),
+                    arg: arg.to_string(),
+                }),
+            },
             _ => Err(Error::ArgValueTypeMismatch {
                 attr: attr.to_string(),
                 arg: arg.to_string(),
@@ -152,7 +188,7 @@ impl From<&Lit> for LiteralClass {
             Lit::Int(_) => LiteralClass::Int,
             Lit::Float(_) => LiteralClass::Float,
             Lit::Bool(_) => LiteralClass::Bool,
-            Lit::Verbatim(_) => LiteralClass::Any,
+            _ => LiteralClass::Any,
         }
     }
 }
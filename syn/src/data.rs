@@ -236,7 +236,6 @@ impl From<syn::Visibility> for Vis {
     fn from(vis: syn::Visibility) -> Self {
         match vis {
             syn::Visibility::Public(_) => Vis::Public,
-            syn::Visibility::Crate(_) => Vis::Scoped(Scope::Crate),
             syn::Visibility::Restricted(scope) => Vis::Scoped(scope.into()),
             syn::Visibility::Inherited => Vis::Inherited,
         }
@@ -245,7 +244,9 @@ impl From<syn::Visibility> for Vis {
 
 impl From<syn::VisRestricted> for Scope {
     fn from(scope: syn::VisRestricted) -> Self {
-        if scope.in_token.is_none() {
+        if scope.in_token.is_none() && scope.path.is_ident("crate") {
+            Scope::Crate
+        } else if scope.in_token.is_none() {
             debug_assert_eq!(scope.path.get_ident().unwrap(), &ident!(super));
             Scope::Super
         } else {
@@ -272,16 +273,13 @@ impl DataType {
             DataInner::Enum(variants) => attr.derive_enum_inner(variants),
             DataInner::Union(_) => Err(syn::Error::new(
                 Span::call_site(),
-                format!(
-                    "deriving `{}` is not supported in unions",
-                    trait_name.to_token_stream().to_string()
-                ),
+                format!("deriving `{}` is not supported in unions", trait_name.to_token_stream()),
             )),
             DataInner::Uninhabited => Err(syn::Error::new(
                 Span::call_site(),
                 format!(
                     "deriving `{}` is not supported for uninhabited enums",
-                    trait_name.to_token_stream().to_string()
+                    trait_name.to_token_stream()
                 ),
             )),
         }?;
@@ -0,0 +1,111 @@
+// Rust language amplification derive library providing multiple generic trait
+// implementations, type wrappers, derive macros and other language enhancements
+//
+// Written in 2019-2021 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the MIT License
+// along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+use std::fmt::Display;
+
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::ToTokens;
+
+/// Place in the source code a diagnostic message refers to
+#[derive(Clone, Debug)]
+enum Location {
+    Span(Span),
+    Tokens(TokenStream2),
+}
+
+impl Location {
+    fn error(&self, message: String) -> syn::Error {
+        match self {
+            Location::Span(span) => syn::Error::new(*span, message),
+            Location::Tokens(tokens) => syn::Error::new_spanned(tokens, message),
+        }
+    }
+}
+
+/// Compilation error which, in addition to the primary message, may point at
+/// secondary places in the source code (like "first defined here") and
+/// provide `help:` suggestions.
+///
+/// The diagnostic is emitted as a [`syn::Error`] combining errors for each of
+/// the spans with [`syn::Error::combine`].
+#[derive(Clone, Debug)]
+pub struct Diagnostic {
+    location: Location,
+    message: String,
+    notes: Vec<(Location, String)>,
+    help: Vec<String>,
+}
+
+impl Diagnostic {
+    /// Constructs diagnostic with the primary `message` at the given `span`
+    pub fn new(span: Span, message: impl Display) -> Diagnostic {
+        Diagnostic {
+            location: Location::Span(span),
+            message: message.to_string(),
+            notes: vec![],
+            help: vec![],
+        }
+    }
+
+    /// Constructs diagnostic with the primary `message` spanning all of the
+    /// provided `tokens`, like [`syn::Error::new_spanned`] does
+    pub fn spanned(tokens: impl ToTokens, message: impl Display) -> Diagnostic {
+        Diagnostic {
+            location: Location::Tokens(tokens.into_token_stream()),
+            message: message.to_string(),
+            notes: vec![],
+            help: vec![],
+        }
+    }
+
+    /// Adds secondary `message` pointing at the given `span`
+    pub fn note(mut self, span: Span, message: impl Display) -> Diagnostic {
+        self.notes.push((Location::Span(span), message.to_string()));
+        self
+    }
+
+    /// Adds secondary `message` spanning all of the provided `tokens`
+    pub fn note_spanned(mut self, tokens: impl ToTokens, message: impl Display) -> Diagnostic {
+        self.notes
+            .push((Location::Tokens(tokens.into_token_stream()), message.to_string()));
+        self
+    }
+
+    /// Adds `help:` suggestion to the primary message
+    pub fn help(mut self, message: impl Display) -> Diagnostic {
+        self.help.push(message.to_string());
+        self
+    }
+
+    /// Converts diagnostic into [`syn::Error`], which can be emitted with
+    /// [`syn::Error::to_compile_error`]
+    pub fn into_syn_error(self) -> syn::Error {
+        let mut message = self.message;
+        for help in self.help {
+            message.push_str("\nhelp: ");
+            message.push_str(&help);
+        }
+        let mut err = self.location.error(message);
+        for (location, note) in self.notes {
+            err.combine(location.error(format!("note: {}", note)));
+        }
+        err
+    }
+}
+
+impl From<Diagnostic> for syn::Error {
+    #[inline]
+    fn from(diag: Diagnostic) -> Self { diag.into_syn_error() }
+}
@@ -0,0 +1,87 @@
+// Rust language amplification derive library providing multiple generic trait
+// implementations, type wrappers, derive macros and other language enhancements
+//
+// Written in 2019-2021 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the MIT License
+// along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+use syn::{Attribute, Expr, ExprLit, Lit, Meta, MetaNameValue};
+
+/// Extracts lines of documentation from `#[doc = "..."]` attributes (i.e. doc
+/// comments), in the order of their appearance.
+///
+/// Each line is stripped from a single leading space, which is added by the
+/// `///` syntax, and from trailing whitespace. Multi-line doc strings (like
+/// ones produced by `/** */` comments) are split into separate lines.
+pub fn doc_lines<'a>(attrs: impl IntoIterator<Item = &'a Attribute>) -> Vec<String> {
+    attrs
+        .into_iter()
+        .filter(|attr| attr.path().is_ident("doc"))
+        .filter_map(|attr| match &attr.meta {
+            Meta::NameValue(MetaNameValue {
+                value:
+                    Expr::Lit(ExprLit {
+                        lit: Lit::Str(s), ..
+                    }),
+                ..
+            }) => Some(s.value()),
+            _ => None,
+        })
+        .flat_map(|doc| {
+            doc.split('\n')
+                .map(|line| line.strip_prefix(' ').unwrap_or(line).trim_end().to_owned())
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// Joins documentation from `#[doc = "..."]` attributes into a text.
+///
+/// Lines of the same paragraph are joined with a single space; each empty
+/// line is replaced with a line break. Escaped `\n` sequences inside the
+/// comment also produce line breaks, such that the text may be formatted
+/// without the use of empty lines.
+pub fn doc_text<'a>(attrs: impl IntoIterator<Item = &'a Attribute>) -> String {
+    let mut text = String::new();
+    for line in doc_lines(attrs) {
+        let line = line.trim().replace("\\n", "\n");
+        if line.is_empty() || line == "\n" {
+            text.push('\n');
+        } else {
+            text.push_str(&line);
+            text.push(' ');
+        }
+    }
+    text.trim().replace(" \n", "\n")
+}
+
+/// Returns first paragraph of the documentation from `#[doc = "..."]`
+/// attributes with its lines joined, or `None` if there is no documentation.
+pub fn doc_summary<'a>(attrs: impl IntoIterator<Item = &'a Attribute>) -> Option<String> {
+    doc_lines(attrs)
+        .iter()
+        .map(|line| line.trim())
+        .skip_while(|line| line.is_empty())
+        .take_while(|line| !line.is_empty())
+        .fold(None, |summary: Option<String>, line| match summary {
+            Some(summary) => Some(format!("{} {}", summary, line)),
+            None => Some(line.to_owned()),
+        })
+}
+
+/// Returns first non-empty line of the documentation from `#[doc = "..."]`
+/// attributes, or `None` if there is no documentation.
+pub fn doc_first_line<'a>(attrs: impl IntoIterator<Item = &'a Attribute>) -> Option<String> {
+    doc_lines(attrs)
+        .into_iter()
+        .map(|line| line.trim().to_owned())
+        .find(|line| !line.is_empty())
+}
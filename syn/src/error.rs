@@ -20,6 +20,8 @@ use std::fmt::{self, Display, Formatter};
 
 use proc_macro2::Span;
 
+use crate::LiteralClass;
+
 /// Errors representing inconsistency in proc macro attribute structure
 #[derive(Clone, Debug)]
 pub enum Error {
@@ -121,6 +123,35 @@ pub enum Error {
         arg: String,
     },
 
+    /// Integer attribute value lies outside of the range allowed by
+    /// [`crate::ValueClass::IntRange`]
+    ArgValueOutOfRange {
+        /// Attribute name
+        attr: String,
+        /// Argument name
+        arg: String,
+        /// Minimal allowed value
+        min: i128,
+        /// Maximal allowed value
+        max: i128,
+    },
+
+    /// Literal has a class different from the one which is expected
+    LiteralClassMismatch {
+        /// Expected literal class
+        expected: LiteralClass,
+        /// Literal class which was found
+        found: LiteralClass,
+    },
+
+    /// Literal value does not fit into the target type
+    LiteralOutOfRange {
+        /// Literal value
+        value: String,
+        /// Name of the target type
+        ty: String,
+    },
+
     /// Parametrized attribute argument must have a literal value (string,
     /// integer etc): `#[attr(arg = "value")]` or `#[arg = 4]`
     ArgValueMustBeLiteral,
@@ -246,6 +277,22 @@ impl Display for Error {
             Error::ArgValueTypeMismatch { attr, arg } => {
                 write!(f, "Type mismatch in attribute `{}` argument `{}`", attr, arg)
             }
+            Error::ArgValueOutOfRange {
+                attr,
+                arg,
+                min,
+                max,
+            } => write!(
+                f,
+                "Value of attribute `{}` argument `{}` must be an integer in range {}..={}",
+                attr, arg, min, max
+            ),
+            Error::LiteralClassMismatch { expected, found } => {
+                write!(f, "Expected {:?} literal, while {:?} literal is found", expected, found)
+            }
+            Error::LiteralOutOfRange { value, ty } => {
+                write!(f, "Literal value `{}` does not fit into `{}` type", value, ty)
+            }
         }
     }
 }
@@ -272,7 +319,10 @@ impl std::error::Error for Error {
             Error::UnsupportedLiteral(_) |
             Error::AttributeUnknownArgument { .. } |
             Error::ArgNumberExceedsMax { .. } |
-            Error::ArgValueTypeMismatch { .. } => None,
+            Error::ArgValueTypeMismatch { .. } |
+            Error::ArgValueOutOfRange { .. } |
+            Error::LiteralClassMismatch { .. } |
+            Error::LiteralOutOfRange { .. } => None,
             Error::NestedListsNotSupported(_) => None,
         }
     }
@@ -0,0 +1,212 @@
+// Rust language amplification derive library providing multiple generic trait
+// implementations, type wrappers, derive macros and other language enhancements
+//
+// Written in 2019-2021 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the MIT License
+// along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+use std::fmt::{self, Display, Formatter};
+use std::ops::Range;
+
+use proc_macro2::Span;
+use syn::LitStr;
+
+/// Piece of a parsed [`FormatString`].
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub enum FormatPiece {
+    /// Literal text, with `{{` and `}}` escapes resolved
+    Text(String),
+
+    /// Placeholder, like `{}`, `{0}` or `{name:#x}`
+    Placeholder(Placeholder),
+}
+
+/// Placeholder of a format string.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Placeholder {
+    /// Argument referenced by the placeholder as it is given in the format
+    /// string; empty for implicit positional placeholders like `{}` or `{:?}`
+    pub arg: String,
+
+    /// Format specification following the `:`, if any
+    pub spec: Option<String>,
+
+    /// Number of the implicit positional placeholder among all such
+    /// placeholders of the format string
+    pub position: Option<usize>,
+
+    /// Byte range of the placeholder (including the braces) within the
+    /// format string
+    pub range: Range<usize>,
+}
+
+impl Placeholder {
+    /// Returns index of the positional argument referenced by the
+    /// placeholder, either explicitly (like `{1}`) or implicitly (like `{}`).
+    pub fn index(&self) -> Option<usize> { self.position.or_else(|| self.arg.parse().ok()) }
+
+    /// Returns name of the named argument referenced by the placeholder.
+    pub fn name(&self) -> Option<&str> {
+        Some(self.arg.as_str()).filter(|arg| !arg.is_empty() && self.index().is_none())
+    }
+
+    /// Returns span of the placeholder inside the string literal containing
+    /// the format string, if the compiler supports this, or the span of the
+    /// whole literal otherwise.
+    pub fn span(&self, lit: &LitStr) -> Span { subspan(lit, self.range.clone()) }
+}
+
+/// Error parsing format string.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct FormatError {
+    /// Description of the error
+    pub message: String,
+
+    /// Byte range within the format string the error is related to
+    pub range: Range<usize>,
+}
+
+impl FormatError {
+    /// Converts into [`syn::Error`] pointing into the string literal
+    /// containing the format string
+    pub fn into_syn_error(self, lit: &LitStr) -> syn::Error {
+        syn::Error::new(subspan(lit, self.range), self.message)
+    }
+}
+
+impl Display for FormatError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result { f.write_str(&self.message) }
+}
+
+impl std::error::Error for FormatError {}
+
+/// Format string, like one used by [`format!`] macro, split into the literal
+/// text and placeholders.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct FormatString {
+    source: String,
+    pieces: Vec<FormatPiece>,
+}
+
+impl FormatString {
+    /// Parses format string, checking that all braces are either escaped or
+    /// enclose placeholders.
+    pub fn parse(fmt: &str) -> Result<FormatString, FormatError> {
+        let mut pieces = vec![];
+        let mut text = String::new();
+        let mut position = 0usize;
+        let mut chars = fmt.char_indices().peekable();
+        while let Some((start, c)) = chars.next() {
+            match (c, chars.peek().map(|(_, c)| *c)) {
+                ('{', Some('{')) | ('}', Some('}')) => {
+                    chars.next();
+                    text.push(c);
+                }
+                ('}', _) => {
+                    return Err(FormatError {
+                        message: "unmatched `}` in format string; use `}}` to escape it".to_owned(),
+                        range: start..start + 1,
+                    });
+                }
+                ('{', _) => {
+                    let end = chars
+                        .by_ref()
+                        .find(|(_, c)| *c == '}' || *c == '{')
+                        .filter(|(_, c)| *c == '}')
+                        .map(|(end, _)| end)
+                        .ok_or_else(|| FormatError {
+                            message: "unclosed placeholder in format string; use `{{` to escape \
+                                      the brace"
+                                .to_owned(),
+                            range: start..start + 1,
+                        })?;
+                    if !text.is_empty() {
+                        pieces.push(FormatPiece::Text(std::mem::take(&mut text)));
+                    }
+                    let placeholder = &fmt[start + 1..end];
+                    let (arg, spec) = match placeholder.split_once(':') {
+                        Some((arg, spec)) => (arg.trim(), Some(spec.to_owned())),
+                        None => (placeholder.trim(), None),
+                    };
+                    pieces.push(FormatPiece::Placeholder(Placeholder {
+                        arg: arg.to_owned(),
+                        spec,
+                        position: if arg.is_empty() {
+                            position += 1;
+                            Some(position - 1)
+                        } else {
+                            None
+                        },
+                        range: start..end + 1,
+                    }));
+                }
+                (c, _) => text.push(c),
+            }
+        }
+        if !text.is_empty() {
+            pieces.push(FormatPiece::Text(text));
+        }
+        Ok(FormatString {
+            source: fmt.to_owned(),
+            pieces,
+        })
+    }
+
+    /// Parses format string given by a string literal; errors point into the
+    /// literal.
+    pub fn parse_lit(lit: &LitStr) -> Result<FormatString, syn::Error> {
+        FormatString::parse(&lit.value()).map_err(|err| err.into_syn_error(lit))
+    }
+
+    /// Returns the original format string
+    pub fn source(&self) -> &str { &self.source }
+
+    /// Returns pieces the format string consists of
+    pub fn pieces(&self) -> &[FormatPiece] { &self.pieces }
+
+    /// Returns iterator over the placeholders of the format string
+    pub fn placeholders(&self) -> impl Iterator<Item = &Placeholder> {
+        self.pieces.iter().filter_map(|piece| match piece {
+            FormatPiece::Placeholder(placeholder) => Some(placeholder),
+            FormatPiece::Text(_) => None,
+        })
+    }
+
+    /// Produces new format string, replacing placeholders for which `f`
+    /// returns a value and keeping the rest of the string (including escapes)
+    /// intact.
+    pub fn rewrite(&self, mut f: impl FnMut(&Placeholder) -> Option<String>) -> String {
+        let mut res = String::with_capacity(self.source.len());
+        let mut pos = 0usize;
+        for placeholder in self.placeholders() {
+            if let Some(replacement) = f(placeholder) {
+                res.push_str(&self.source[pos..placeholder.range.start]);
+                res.push_str(&replacement);
+                pos = placeholder.range.end;
+            }
+        }
+        res.push_str(&self.source[pos..]);
+        res
+    }
+}
+
+/// Computes span of the byte range of the value of a string literal, if the
+/// compiler supports this and the literal has no escapes (which shift the
+/// positions); otherwise returns the span of the whole literal.
+fn subspan(lit: &LitStr, range: Range<usize>) -> Span {
+    let token = lit.token();
+    if token.to_string() != format!("\"{}\"", lit.value()) {
+        return lit.span();
+    }
+    token
+        .subspan(range.start + 1..range.end + 1)
+        .unwrap_or_else(|| lit.span())
+}
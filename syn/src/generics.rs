@@ -0,0 +1,107 @@
+// Rust language amplification derive library providing multiple generic trait
+// implementations, type wrappers, derive macros and other language enhancements
+//
+// Written in 2019-2021 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the MIT License
+// along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+use proc_macro2::{TokenStream as TokenStream2, TokenTree};
+use quote::ToTokens;
+use syn::punctuated::Punctuated;
+use syn::{Generics, Ident, PredicateType, Type, TypeParamBound, WherePredicate};
+
+/// Checks whether the tokens (usually a type) mention any of the generic type
+/// parameters.
+pub fn mentions_params(tokens: TokenStream2, params: &[&Ident]) -> bool {
+    tokens.into_iter().any(|tt| match tt {
+        TokenTree::Ident(ident) => params.contains(&&ident),
+        TokenTree::Group(group) => mentions_params(group.stream(), params),
+        _ => false,
+    })
+}
+
+/// Returns generics extended with the bounds for the types depending on the
+/// generic type parameters, as with [`add_bounds`]; bounds for the types not
+/// mentioning any of the parameters are skipped. If `predicates` are given
+/// (like with `bound = "..."` argument of a derive attribute), they are added
+/// to the where clause instead of the bounds.
+pub fn bounded_generics(
+    generics: &Generics,
+    predicates: Option<impl IntoIterator<Item = WherePredicate>>,
+    bounds: impl IntoIterator<Item = (Type, TypeParamBound)>,
+) -> Generics {
+    let mut bounded = generics.clone();
+    if let Some(predicates) = predicates {
+        bounded.make_where_clause().predicates.extend(predicates);
+        return bounded;
+    }
+    let params = generics
+        .type_params()
+        .map(|param| &param.ident)
+        .collect::<Vec<_>>();
+    let bounds = bounds
+        .into_iter()
+        .filter(|(ty, _)| mentions_params(ty.to_token_stream(), &params));
+    add_bounds(&mut bounded, bounds);
+    bounded
+}
+
+/// Extends generics with the bounds, each of which requires type (which may
+/// be a generic type parameter or any type depending on it) to implement a
+/// trait.
+///
+/// Bounds which are already present either in the declaration of the generic
+/// parameters or in the where clause are skipped; new bounds for the types
+/// already having a where clause predicate are added to that predicate.
+pub fn add_bounds(
+    generics: &mut Generics,
+    bounds: impl IntoIterator<Item = (Type, TypeParamBound)>,
+) {
+    let same = |a: &dyn ToTokens, b: &dyn ToTokens| {
+        a.to_token_stream().to_string() == b.to_token_stream().to_string()
+    };
+    for (ty, bound) in bounds {
+        let declared = generics.type_params().any(|param| {
+            same(&param.ident, &ty) && param.bounds.iter().any(|other| same(other, &bound))
+        });
+        if declared {
+            continue;
+        }
+        let where_clause = generics.make_where_clause();
+        let predicate = where_clause
+            .predicates
+            .iter_mut()
+            .find_map(|predicate| match predicate {
+                WherePredicate::Type(predicate)
+                    if predicate.lifetimes.is_none() && same(&predicate.bounded_ty, &ty) =>
+                {
+                    Some(predicate)
+                }
+                _ => None,
+            });
+        match predicate {
+            Some(predicate) if predicate.bounds.iter().any(|other| same(other, &bound)) => {}
+            Some(predicate) => predicate.bounds.push(bound),
+            None => {
+                let mut bounds = Punctuated::new();
+                bounds.push(bound);
+                where_clause
+                    .predicates
+                    .push(WherePredicate::Type(PredicateType {
+                        lifetimes: None,
+                        bounded_ty: ty,
+                        colon_token: Default::default(),
+                        bounds,
+                    }));
+            }
+        }
+    }
+}
@@ -0,0 +1,42 @@
+// Rust language amplification derive library providing multiple generic trait
+// implementations, type wrappers, derive macros and other language enhancements
+//
+// Written in 2019-2021 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the MIT License
+// along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+use proc_macro_crate::FoundCrate;
+use proc_macro2::Span;
+use syn::{Attribute, Ident, Path};
+
+/// Resolves path to the crate `name` which should be used in the generated
+/// code.
+///
+/// If the item has `#[<override_attr>(path)]` attribute, the provided path is
+/// used. Otherwise, the name under which the crate is specified in the
+/// dependencies of the crate being compiled is used, such that renamed
+/// dependencies (like `amp = { package = "amplify", ... }`) are supported. If
+/// the crate is not found among the dependencies (for instance, when it is
+/// accessed through a re-export) or if the crate being compiled is the crate
+/// `name` itself, the `name` is used as is.
+pub fn crate_path(attrs: &[Attribute], override_attr: &str, name: &str) -> syn::Result<Path> {
+    if let Some(attr) = attrs
+        .iter()
+        .find(|attr| attr.path().is_ident(override_attr))
+    {
+        return attr.parse_args::<Path>();
+    }
+    let name = match proc_macro_crate::crate_name(name) {
+        Ok(FoundCrate::Name(name)) => name,
+        Ok(FoundCrate::Itself) | Err(_) => name.to_owned(),
+    };
+    Ok(Path::from(Ident::new(&name, Span::call_site())))
+}
@@ -42,11 +42,20 @@ extern crate proc_macro;
 
 mod attr;
 mod cls;
+mod docs;
 mod error;
+mod fmt;
+mod generics;
+mod krate;
+mod lit;
+mod merge;
 mod parsers;
 mod req;
+mod select;
 mod val;
+mod warn;
 mod data;
+mod diag;
 
 pub use attr::{Attr, ExtractAttr, ParametrizedAttr, SingularAttr};
 pub use cls::{LiteralClass, TypeClass, ValueClass};
@@ -54,10 +63,19 @@ pub use data::{
     DataInner, DataType, DeriveInner, Element, EnumKind, Field, FieldKind, Fields, Items,
     NamedField, Scope, Variant, Vis,
 };
+pub use diag::Diagnostic;
+pub use docs::{doc_first_line, doc_lines, doc_summary, doc_text};
 pub use error::Error;
+pub use fmt::{FormatError, FormatPiece, FormatString, Placeholder};
+pub use generics::{add_bounds, bounded_generics, mentions_params};
+pub use krate::crate_path;
+pub use lit::LitValue;
+pub use merge::{DuplicatePolicy, merge_attr_args};
 pub use parsers::{MetaArg, MetaArgList, MetaArgNameValue};
 pub use req::{ArgValueReq, AttrReq, ListReq, ValueReq};
+pub use select::{SelectPolicy, SelectedField, select_field};
 pub use val::ArgValue;
+pub use warn::{Warning, Warnings};
 
 /// Convenience macro for constructing [`struct@syn::Ident`] from literals
 #[macro_export]
@@ -84,4 +102,354 @@ mod test {
     fn ident() {
         assert_eq!(ident!(u8), Ident::new("u8", Span::call_site()));
     }
+
+    #[test]
+    fn keyword_args() {
+        let attr: syn::Attribute = parse_quote!(#[attr(const, type, name = "value")]);
+        let attr = crate::ParametrizedAttr::from_attribute(&attr).unwrap();
+        assert!(attr.paths[0].is_ident("const"));
+        assert!(attr.paths[1].is_ident("type"));
+        assert!(attr.args.contains_key("name"));
+    }
+
+    #[test]
+    fn req_builder() {
+        use crate::{AttrReq, Error, ValueClass};
+
+        let attrs: Vec<syn::Attribute> = vec![
+            parse_quote!(#[attr(name = "value", flag)]),
+            parse_quote!(#[attr(level = 3)]),
+            parse_quote!(#[other(unknown)]),
+        ];
+        let req = AttrReq::new()
+            .required("name", ValueClass::str())
+            .optional("rename", ValueClass::str())
+            .with_default("level", 1usize)
+            .flag("flag");
+
+        let attr = req.clone().parse("attr", &attrs).unwrap();
+        assert!(attr.has_arg("flag"));
+        assert_eq!(attr.arg_value::<String>("name").unwrap(), "value");
+        assert!(attr.opt_arg_value::<String>("rename").unwrap().is_none());
+        assert_eq!(
+            attr.arg_value::<syn::LitInt>("level")
+                .unwrap()
+                .base10_digits(),
+            "3"
+        );
+
+        let attrs: Vec<syn::Attribute> = vec![parse_quote!(#[attr(flag)])];
+        assert!(matches!(
+            req.clone().parse("attr", &attrs),
+            Err(Error::ArgRequired { ref arg, .. }) if arg == "name"
+        ));
+
+        let attrs: Vec<syn::Attribute> = vec![parse_quote!(#[attr(name = "x", flag = 1)])];
+        assert!(matches!(
+            req.clone().parse("attr", &attrs),
+            Err(Error::ArgMustNotHaveValue { ref arg, .. }) if arg == "flag"
+        ));
+
+        let attrs: Vec<syn::Attribute> = vec![parse_quote!(#[attr(name = "x", extra = 1)])];
+        assert!(matches!(
+            req.parse("attr", &attrs),
+            Err(Error::AttributeUnknownArgument { ref arg, .. }) if arg == "extra"
+        ));
+    }
+
+    #[test]
+    fn diagnostic() {
+        use crate::Diagnostic;
+
+        let err = syn::Error::from(
+            Diagnostic::new(Span::call_site(), "duplicated value")
+                .note(Span::call_site(), "first defined here")
+                .help("remove one of the values"),
+        );
+        let messages = err.into_iter().map(|e| e.to_string()).collect::<Vec<_>>();
+        assert_eq!(messages, vec![
+            "duplicated value\nhelp: remove one of the values",
+            "note: first defined here"
+        ]);
+    }
+
+    #[test]
+    fn lit_value() {
+        use std::convert::TryFrom;
+
+        use crate::{ArgValue, Error, LitValue};
+
+        let lit = |value: ArgValue| LitValue::try_from(value).unwrap();
+        assert_eq!(lit(parse_quote!(-1.5)), LitValue::Float(-1.5));
+        assert_eq!(lit(parse_quote!(-42)), LitValue::Int(-42));
+        assert_eq!(lit(parse_quote!(',')), LitValue::Char(','));
+        assert_eq!(lit(parse_quote!(b"\x00ABC")), LitValue::ByteStr(b"\x00ABC".to_vec()));
+        assert_eq!(lit(parse_quote!(b'a')), LitValue::Byte(b'a'));
+        assert_eq!(lit(parse_quote!(true)), LitValue::Bool(true));
+        assert_eq!(
+            lit(parse_quote!(-170141183460469231731687303715884105728)),
+            LitValue::Int(i128::MIN)
+        );
+        let value: ArgValue = parse_quote!(-"str");
+        assert!(LitValue::try_from(value).is_err());
+
+        let value: ArgValue = parse_quote!(-129);
+        assert_eq!(i16::try_from(value.clone()).unwrap(), -129);
+        assert!(matches!(i8::try_from(value.clone()), Err(Error::LiteralOutOfRange { .. })));
+        assert!(matches!(u16::try_from(value), Err(Error::LiteralOutOfRange { .. })));
+        let value: ArgValue = parse_quote!(-2);
+        assert_eq!(f32::try_from(value).unwrap(), -2.0);
+        assert!(matches!(
+            char::try_from(LitValue::Str(String::from("a"))),
+            Err(Error::LiteralClassMismatch { .. })
+        ));
+
+        for value in
+            [LitValue::Int(-5), LitValue::Float(-0.25), LitValue::Byte(1), LitValue::Byte(b'\'')]
+        {
+            let tokens = quote! { #value };
+            assert_eq!(lit(syn::parse2(tokens).unwrap()), value);
+        }
+    }
+
+    #[test]
+    fn merge_attrs() {
+        use crate::{DuplicatePolicy, MetaArg, merge_attr_args};
+
+        let attrs: Vec<syn::Attribute> = vec![
+            parse_quote!(#[attr(Add, name = "first")]),
+            parse_quote!(#[other(Add)]),
+            parse_quote!(#[attr(Sub, name = "second", "literal")]),
+        ];
+        let names = |policy| {
+            merge_attr_args::<MetaArg, _>("attr", &attrs, policy, MetaArg::name).map(|args| {
+                args.iter()
+                    .map(|arg| quote! { #arg }.to_string())
+                    .collect::<Vec<_>>()
+            })
+        };
+
+        assert_eq!(names(DuplicatePolicy::Accumulate).unwrap(), vec![
+            "Add",
+            "name = \"first\"",
+            "Sub",
+            "name = \"second\"",
+            "\"literal\""
+        ]);
+        assert_eq!(names(DuplicatePolicy::LastWins).unwrap(), vec![
+            "Add",
+            "name = \"second\"",
+            "Sub",
+            "\"literal\""
+        ]);
+        let err = names(DuplicatePolicy::Error).unwrap_err();
+        let messages = err.into_iter().map(|e| e.to_string()).collect::<Vec<_>>();
+        assert_eq!(messages, vec![
+            "Attribute `#[attr]`: argument `name` is repeated\nhelp: keep only one occurrence of \
+             `name`",
+            "note: `name` is first given here"
+        ]);
+    }
+
+    #[test]
+    fn req_int_range() {
+        use crate::{AttrReq, Error, ValueClass};
+
+        let req = AttrReq::new().required("code", ValueClass::int_range(1..=100));
+
+        let attrs: Vec<syn::Attribute> = vec![parse_quote!(#[attr(code = 42)])];
+        assert!(req.clone().parse("attr", &attrs).is_ok());
+
+        let attrs: Vec<syn::Attribute> = vec![parse_quote!(#[attr(code = 420)])];
+        assert!(matches!(
+            req.clone().parse("attr", &attrs),
+            Err(Error::ArgValueOutOfRange {
+                min: 1,
+                max: 100,
+                ..
+            })
+        ));
+
+        let attrs: Vec<syn::Attribute> = vec![parse_quote!(#[attr(code = -4)])];
+        assert!(matches!(
+            req.clone().parse("attr", &attrs),
+            Err(Error::ArgValueOutOfRange {
+                min: 1,
+                max: 100,
+                ..
+            })
+        ));
+
+        let attrs: Vec<syn::Attribute> = vec![parse_quote!(#[attr(code = "42")])];
+        assert!(req.parse("attr", &attrs).is_err());
+    }
+
+    #[test]
+    fn crate_path() {
+        use crate::crate_path;
+
+        let path = |attrs: Vec<syn::Attribute>| {
+            crate_path(&attrs, "amplify_crate", "amplify").map(|path| quote! { #path }.to_string())
+        };
+        assert_eq!(path(vec![parse_quote!(#[other(::amp)])]).unwrap(), "amplify");
+        assert_eq!(
+            path(vec![parse_quote!(#[amplify_crate(::facade::amplify)])]).unwrap(),
+            ":: facade :: amplify"
+        );
+        assert!(path(vec![parse_quote!(#[amplify_crate("amplify")])]).is_err());
+    }
+
+    #[test]
+    fn doc_comments() {
+        use crate::{doc_first_line, doc_lines, doc_summary, doc_text};
+
+        let attrs: Vec<syn::Attribute> = vec![
+            parse_quote!(#[doc = " "]),
+            parse_quote!(#[doc = " First line"]),
+            parse_quote!(#[derive(Debug)]),
+            parse_quote!(#[doc = "  of summary.  "]),
+            parse_quote!(#[doc = ""]),
+            parse_quote!(#[doc = " Details\n    indented"]),
+            parse_quote!(#[doc = r" \nescaped"]),
+        ];
+        assert_eq!(doc_lines(&attrs), vec![
+            "",
+            "First line",
+            " of summary.",
+            "",
+            "Details",
+            "   indented",
+            r"\nescaped"
+        ]);
+        assert_eq!(doc_text(&attrs), "First line of summary.\nDetails indented\nescaped");
+        assert_eq!(doc_summary(&attrs).unwrap(), "First line of summary.");
+        assert_eq!(doc_first_line(&attrs).unwrap(), "First line");
+        assert_eq!(doc_summary(&attrs[2..3]), None);
+        assert_eq!(doc_first_line(&attrs[..1]), None);
+    }
+
+    #[test]
+    fn bounds() {
+        use crate::add_bounds;
+
+        let mut generics: syn::Generics = parse_quote!(<T: Clone, U>);
+        generics.where_clause = Some(parse_quote!(where U: Default));
+        add_bounds(&mut generics, [
+            (parse_quote!(T), parse_quote!(Clone)),
+            (parse_quote!(U), parse_quote!(Default)),
+            (parse_quote!(U), parse_quote!(::core::fmt::Debug)),
+            (parse_quote!(Vec<T>), parse_quote!(::core::fmt::Debug)),
+            (parse_quote!(Vec<T>), parse_quote!(::core::fmt::Debug)),
+        ]);
+        let where_clause = generics.where_clause;
+        assert_eq!(
+            quote! { #where_clause }.to_string(),
+            quote! { where U: Default + ::core::fmt::Debug, Vec<T>: ::core::fmt::Debug }
+                .to_string()
+        );
+    }
+
+    #[test]
+    fn param_bounds() {
+        use crate::bounded_generics;
+
+        let generics: syn::Generics = parse_quote!(<T, const N: usize>);
+        let bounded = bounded_generics(&generics, None::<Vec<syn::WherePredicate>>, [
+            (parse_quote!(Vec<T>), parse_quote!(::core::fmt::Debug)),
+            (parse_quote!([u8; N]), parse_quote!(::core::fmt::Debug)),
+            (parse_quote!(String), parse_quote!(::core::fmt::Display)),
+        ]);
+        let where_clause = bounded.where_clause;
+        assert_eq!(
+            quote! { #where_clause }.to_string(),
+            quote! { where Vec<T>: ::core::fmt::Debug }.to_string()
+        );
+
+        let bounded = bounded_generics(&generics, Some([parse_quote!(T: Clone)]), [(
+            parse_quote!(T),
+            parse_quote!(::core::fmt::Debug),
+        )]);
+        let where_clause = bounded.where_clause;
+        assert_eq!(quote! { #where_clause }.to_string(), quote! { where T: Clone }.to_string());
+    }
+
+    #[test]
+    fn field_selection() {
+        use crate::{SelectPolicy, select_field};
+
+        let policy = SelectPolicy { skip_phantom: true };
+        let fields: syn::FieldsUnnamed = parse_quote!((u8, #[wrap] u16, PhantomData<T>));
+        let field = select_field(&fields.into(), "wrap", policy).unwrap();
+        assert_eq!(field.index, 1);
+        assert!(field.member == parse_quote!(1));
+
+        let fields: syn::FieldsNamed = parse_quote!({ marker: PhantomData<T>, value: u8 });
+        let fields = syn::Fields::from(fields);
+        let field = select_field(&fields, "wrap", policy).unwrap();
+        assert!(field.member == parse_quote!(value));
+        assert!(select_field(&fields, "wrap", SelectPolicy::default()).is_err());
+
+        let fields: syn::FieldsUnnamed = parse_quote!((
+            #[wrap]
+            u8,
+            #[wrap]
+            u16
+        ));
+        let err = select_field(&fields.into(), "wrap", policy).err().unwrap();
+        assert_eq!(err.into_iter().count(), 2);
+    }
+
+    #[test]
+    fn warnings() {
+        use crate::{Warning, Warnings};
+
+        let mut warnings = Warnings::new();
+        assert!(warnings.is_empty());
+        assert!(quote! { #warnings }.is_empty());
+        warnings.warn(ident!(Add), "repeated");
+        warnings.push(Warning::new(proc_macro2::Span::call_site(), "unused"));
+        assert_eq!(warnings.iter().map(Warning::message).collect::<Vec<_>>(), vec![
+            "repeated", "unused"
+        ]);
+        let warning = Warning::spanned(ident!(Add), "repeated");
+        assert_eq!(
+            quote! { #warning }.to_string(),
+            quote! {
+                const _: () = {
+                    #[deprecated(note = "repeated")]
+                    #[allow(non_upper_case_globals)]
+                    const warning: () = ();
+                    warning
+                };
+            }
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn format_string() {
+        use crate::{FormatPiece, FormatString};
+
+        let fmt = FormatString::parse("{{{}}} {name:#x} {1} {:?}, {value.len()}}}").unwrap();
+        let placeholders = fmt.placeholders().collect::<Vec<_>>();
+        assert_eq!(placeholders.len(), 5);
+        assert_eq!(placeholders[0].index(), Some(0));
+        assert_eq!(placeholders[0].range, 2..4);
+        assert_eq!(placeholders[1].name(), Some("name"));
+        assert_eq!(placeholders[1].spec.as_deref(), Some("#x"));
+        assert_eq!(placeholders[2].index(), Some(1));
+        assert_eq!(placeholders[2].position, None);
+        assert_eq!(placeholders[3].position, Some(1));
+        assert_eq!(placeholders[4].name(), Some("value.len()"));
+        assert_eq!(fmt.pieces()[0], FormatPiece::Text("{".to_owned()));
+        assert_eq!(fmt.pieces().last(), Some(&FormatPiece::Text("}".to_owned())));
+        assert_eq!(
+            fmt.rewrite(|placeholder| placeholder.name().map(|_| "{}".to_owned())),
+            "{{{}}} {} {1} {:?}, {}}}"
+        );
+
+        assert_eq!(FormatString::parse("a } b").unwrap_err().range, 2..3);
+        assert_eq!(FormatString::parse("a {b").unwrap_err().range, 2..3);
+        assert!(FormatString::parse("{a{b}").is_err());
+    }
 }
@@ -0,0 +1,319 @@
+// Rust language amplification derive library providing multiple generic trait
+// implementations, type wrappers, derive macros and other language enhancements
+//
+// Written in 2019-2021 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the MIT License
+// along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+use std::convert::{TryFrom, TryInto};
+
+use proc_macro2::{Literal, Span, TokenStream};
+use quote::ToTokens;
+use syn::{Expr, ExprLit, ExprUnary, Lit, UnOp};
+
+use crate::{ArgValue, Error, LiteralClass};
+
+/// Decoded value of a literal given to an attribute argument, like
+/// `#[attr(arg = -1.5)]`, `#[attr(arg = ',')]` or `#[attr(arg = b"\x00ABC")]`.
+///
+/// Unlike [`Lit`], negative numbers (which are parsed by `syn` as unary
+/// expressions) are also represented as literal values.
+#[derive(Clone, PartialEq, Debug)]
+pub enum LitValue {
+    /// String literal: `"value"`
+    Str(String),
+
+    /// Byte string literal: `b"value"`
+    ByteStr(Vec<u8>),
+
+    /// Byte literal: `b'v'`
+    Byte(u8),
+
+    /// Character literal: `'v'`
+    Char(char),
+
+    /// Integer literal, possibly negative: `-42`
+    Int(i128),
+
+    /// Float literal, possibly negative: `-1.5`
+    Float(f64),
+
+    /// Boolean literal: `true` or `false`
+    Bool(bool),
+}
+
+impl LitValue {
+    /// Returns class of the literal
+    pub fn class(&self) -> LiteralClass {
+        match self {
+            LitValue::Str(_) => LiteralClass::Str,
+            LitValue::ByteStr(_) => LiteralClass::ByteStr,
+            LitValue::Byte(_) => LiteralClass::Byte,
+            LitValue::Char(_) => LiteralClass::Char,
+            LitValue::Int(_) => LiteralClass::Int,
+            LitValue::Float(_) => LiteralClass::Float,
+            LitValue::Bool(_) => LiteralClass::Bool,
+        }
+    }
+
+    fn mismatch(&self, expected: LiteralClass) -> Error {
+        Error::LiteralClassMismatch {
+            expected,
+            found: self.class(),
+        }
+    }
+
+    fn negated(self) -> Result<LitValue, Error> {
+        match self {
+            LitValue::Int(value) => Ok(LitValue::Int(-value)),
+            LitValue::Float(value) => Ok(LitValue::Float(-value)),
+            _ => Err(Error::ArgValueMustBeLiteral),
+        }
+    }
+}
+
+impl TryFrom<&Lit> for LitValue {
+    type Error = Error;
+
+    fn try_from(lit: &Lit) -> Result<Self, Self::Error> {
+        Ok(match lit {
+            Lit::Str(s) => LitValue::Str(s.value()),
+            Lit::ByteStr(s) => LitValue::ByteStr(s.value()),
+            Lit::Byte(b) => LitValue::Byte(b.value()),
+            Lit::Char(c) => LitValue::Char(c.value()),
+            Lit::Int(i) => {
+                LitValue::Int(i.base10_parse().map_err(|_| Error::LiteralOutOfRange {
+                    value: i.base10_digits().to_owned(),
+                    ty: "i128".to_owned(),
+                })?)
+            }
+            Lit::Float(f) => match f.base10_parse::<f64>() {
+                Ok(value) if value.is_finite() => LitValue::Float(value),
+                _ => {
+                    return Err(Error::LiteralOutOfRange {
+                        value: f.base10_digits().to_owned(),
+                        ty: "f64".to_owned(),
+                    });
+                }
+            },
+            Lit::Bool(b) => LitValue::Bool(b.value),
+            _ => return Err(Error::ArgValueMustBeLiteral),
+        })
+    }
+}
+
+impl TryFrom<Lit> for LitValue {
+    type Error = Error;
+
+    #[inline]
+    fn try_from(lit: Lit) -> Result<Self, Self::Error> { LitValue::try_from(&lit) }
+}
+
+impl TryFrom<&Expr> for LitValue {
+    type Error = Error;
+
+    fn try_from(expr: &Expr) -> Result<Self, Self::Error> {
+        match expr {
+            Expr::Lit(ExprLit { lit, .. }) => LitValue::try_from(lit),
+            Expr::Unary(ExprUnary {
+                op: UnOp::Neg(_),
+                expr,
+                ..
+            }) => match expr.as_ref() {
+                // Negation of the minimal integer value does not fit into
+                // positive `i128`, so we parse it with the sign
+                Expr::Lit(ExprLit {
+                    lit: Lit::Int(i), ..
+                }) => {
+                    let digits = format!("-{}", i.base10_digits());
+                    digits
+                        .parse()
+                        .map(LitValue::Int)
+                        .map_err(|_| Error::LiteralOutOfRange {
+                            value: digits,
+                            ty: "i128".to_owned(),
+                        })
+                }
+                Expr::Lit(ExprLit { lit, .. }) => LitValue::try_from(lit)?.negated(),
+                _ => Err(Error::ArgValueMustBeLiteral),
+            },
+            _ => Err(Error::ArgValueMustBeLiteral),
+        }
+    }
+}
+
+impl TryFrom<&ArgValue> for LitValue {
+    type Error = Error;
+
+    fn try_from(value: &ArgValue) -> Result<Self, Self::Error> {
+        match value {
+            ArgValue::Literal(lit) => LitValue::try_from(lit),
+            ArgValue::Expr(expr) => LitValue::try_from(expr),
+            ArgValue::Type(_) | ArgValue::None => Err(Error::ArgValueMustBeLiteral),
+        }
+    }
+}
+
+impl TryFrom<ArgValue> for LitValue {
+    type Error = Error;
+
+    #[inline]
+    fn try_from(value: ArgValue) -> Result<Self, Self::Error> { LitValue::try_from(&value) }
+}
+
+impl ToTokens for LitValue {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let span = Span::call_site();
+        match self {
+            LitValue::Str(s) => Literal::string(s).to_tokens(tokens),
+            LitValue::ByteStr(s) => Literal::byte_string(s).to_tokens(tokens),
+            LitValue::Byte(b) => {
+                // `syn::LitByte::new` produces `u8` integer literal instead
+                format!("b'{}'", std::ascii::escape_default(*b))
+                    .parse::<Literal>()
+                    .expect("escaped byte literal")
+                    .to_tokens(tokens)
+            }
+            LitValue::Char(c) => Literal::character(*c).to_tokens(tokens),
+            LitValue::Int(i) if *i < 0 => {
+                let abs = Literal::u128_unsuffixed(i.unsigned_abs());
+                quote! { -#abs }.to_tokens(tokens)
+            }
+            LitValue::Int(i) => Literal::i128_unsuffixed(*i).to_tokens(tokens),
+            LitValue::Float(f) if f.is_sign_negative() => {
+                let abs = Literal::f64_unsuffixed(-*f);
+                quote! { -#abs }.to_tokens(tokens)
+            }
+            LitValue::Float(f) => Literal::f64_unsuffixed(*f).to_tokens(tokens),
+            LitValue::Bool(b) => syn::LitBool::new(*b, span).to_tokens(tokens),
+        }
+    }
+}
+
+macro_rules! impl_int {
+    ($($ty:ty),+) => { $(
+        impl TryFrom<LitValue> for $ty {
+            type Error = Error;
+
+            fn try_from(value: LitValue) -> Result<Self, Self::Error> {
+                match value {
+                    LitValue::Int(i) => i.try_into().map_err(|_| Error::LiteralOutOfRange {
+                        value: i.to_string(),
+                        ty: stringify!($ty).to_owned(),
+                    }),
+                    other => Err(other.mismatch(LiteralClass::Int)),
+                }
+            }
+        }
+
+        impl TryFrom<ArgValue> for $ty {
+            type Error = Error;
+
+            #[inline]
+            fn try_from(value: ArgValue) -> Result<Self, Self::Error> {
+                LitValue::try_from(value)?.try_into()
+            }
+        }
+    )+ };
+}
+
+impl_int!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+impl TryFrom<LitValue> for f64 {
+    type Error = Error;
+
+    fn try_from(value: LitValue) -> Result<Self, Self::Error> {
+        match value {
+            LitValue::Float(f) => Ok(f),
+            // Integer literals are accepted for the float arguments, like in
+            // `#[attr(scale = 2)]`
+            LitValue::Int(i) => Ok(i as f64),
+            other => Err(other.mismatch(LiteralClass::Float)),
+        }
+    }
+}
+
+impl TryFrom<LitValue> for f32 {
+    type Error = Error;
+
+    fn try_from(value: LitValue) -> Result<Self, Self::Error> {
+        let f = f64::try_from(value)?;
+        if f.abs() > f32::MAX as f64 {
+            return Err(Error::LiteralOutOfRange {
+                value: f.to_string(),
+                ty: "f32".to_owned(),
+            });
+        }
+        Ok(f as f32)
+    }
+}
+
+impl TryFrom<ArgValue> for f64 {
+    type Error = Error;
+
+    #[inline]
+    fn try_from(value: ArgValue) -> Result<Self, Self::Error> {
+        LitValue::try_from(value)?.try_into()
+    }
+}
+
+impl TryFrom<ArgValue> for f32 {
+    type Error = Error;
+
+    #[inline]
+    fn try_from(value: ArgValue) -> Result<Self, Self::Error> {
+        LitValue::try_from(value)?.try_into()
+    }
+}
+
+impl TryFrom<LitValue> for String {
+    type Error = Error;
+
+    fn try_from(value: LitValue) -> Result<Self, Self::Error> {
+        match value {
+            LitValue::Str(s) => Ok(s),
+            other => Err(other.mismatch(LiteralClass::Str)),
+        }
+    }
+}
+
+impl TryFrom<LitValue> for Vec<u8> {
+    type Error = Error;
+
+    fn try_from(value: LitValue) -> Result<Self, Self::Error> {
+        match value {
+            LitValue::ByteStr(s) => Ok(s),
+            other => Err(other.mismatch(LiteralClass::ByteStr)),
+        }
+    }
+}
+
+impl TryFrom<LitValue> for char {
+    type Error = Error;
+
+    fn try_from(value: LitValue) -> Result<Self, Self::Error> {
+        match value {
+            LitValue::Char(c) => Ok(c),
+            other => Err(other.mismatch(LiteralClass::Char)),
+        }
+    }
+}
+
+impl TryFrom<LitValue> for bool {
+    type Error = Error;
+
+    fn try_from(value: LitValue) -> Result<Self, Self::Error> {
+        match value {
+            LitValue::Bool(b) => Ok(b),
+            other => Err(other.mismatch(LiteralClass::Bool)),
+        }
+    }
+}
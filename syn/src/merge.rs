@@ -0,0 +1,82 @@
+// Rust language amplification derive library providing multiple generic trait
+// implementations, type wrappers, derive macros and other language enhancements
+//
+// Written in 2019-2021 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the MIT License
+// along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+use std::fmt::Display;
+
+use quote::ToTokens;
+use syn::Attribute;
+use syn::parse::Parse;
+use syn::punctuated::Punctuated;
+
+use crate::Diagnostic;
+
+/// Defines how repeated arguments are handled when merging multiple
+/// occurrences of the same attribute with [`merge_attr_args`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum DuplicatePolicy {
+    /// Repeated argument is an error pointing at both of its occurrences
+    Error,
+
+    /// Repeated argument replaces the previous one, keeping its position in
+    /// the list
+    LastWins,
+
+    /// All occurrences of the argument are kept
+    Accumulate,
+}
+
+/// Parses comma-separated arguments from all occurrences of the attribute
+/// with the given `name` (like `#[wrapper(Add)] #[wrapper(Sub)]`) and merges
+/// them into a single list, in the order of their appearance.
+///
+/// Arguments are checked for repetitions using the `key` function, and
+/// repeated arguments are processed according to the `policy`. Arguments for
+/// which `key` returns `None` are never treated as repeated.
+pub fn merge_attr_args<T, K>(
+    name: &str,
+    attrs: &[Attribute],
+    policy: DuplicatePolicy,
+    key: impl Fn(&T) -> Option<K>,
+) -> Result<Vec<T>, syn::Error>
+where
+    T: Parse + ToTokens,
+    K: Eq + Display,
+{
+    let mut merged: Vec<(Option<K>, T)> = vec![];
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident(name)) {
+        let args = attr.parse_args_with(Punctuated::<T, Token![,]>::parse_terminated)?;
+        for arg in args {
+            let key = key(&arg);
+            let pos = key
+                .as_ref()
+                .and_then(|key| merged.iter().position(|(k, _)| k.as_ref() == Some(key)));
+            match (pos, policy) {
+                (Some(pos), DuplicatePolicy::Error) => {
+                    let key = key.expect("position is found only for keyed arguments");
+                    return Err(Diagnostic::spanned(
+                        &arg,
+                        format!("Attribute `#[{}]`: argument `{}` is repeated", name, key),
+                    )
+                    .note_spanned(&merged[pos].1, format!("`{}` is first given here", key))
+                    .help(format!("keep only one occurrence of `{}`", key))
+                    .into_syn_error());
+                }
+                (Some(pos), DuplicatePolicy::LastWins) => merged[pos] = (key, arg),
+                _ => merged.push((key, arg)),
+            }
+        }
+    }
+    Ok(merged.into_iter().map(|(_, arg)| arg).collect())
+}
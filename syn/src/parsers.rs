@@ -22,7 +22,7 @@ use syn::{Lit, Path};
 
 use crate::{ArgValue, Error};
 
-/// Drop-in replacement for [`syn::NestedMeta`], which allows to parse
+/// Drop-in replacement for `syn::NestedMeta` from `syn` v1, which allows to parse
 /// attributes which can have arguments made of either literal, path or
 /// [`MetaArgNameValue`] expressions.
 pub struct MetaArgList {
@@ -43,7 +43,7 @@ impl ToTokens for MetaArgList {
     fn to_tokens(&self, tokens: &mut TokenStream) { (quote! { ( list ) }).to_tokens(tokens); }
 }
 
-/// Drop-in replacement for [`syn::NestedMeta`], which allows to parse
+/// Drop-in replacement for `syn::NestedMeta` from `syn` v1, which allows to parse
 /// attributes which can have arguments made of either literal, path or
 /// [`MetaArgNameValue`] expressions.
 pub enum MetaArg {
@@ -62,6 +62,12 @@ impl Parse for MetaArg {
     fn parse(input: &ParseBuffer) -> Result<Self> {
         if input.peek2(Token![=]) {
             input.parse().map(MetaArg::NameValue)
+        } else if input.peek(Ident::peek_any) && !input.peek2(Token![::]) {
+            // Single identifiers may be keywords, like `const` or `type`
+            input
+                .call(Ident::parse_any)
+                .map(Path::from)
+                .map(MetaArg::Path)
         } else if input.peek(Ident::peek_any) ||
             input.peek(Token![::]) && input.peek3(Ident::peek_any)
         {
@@ -72,6 +78,19 @@ impl Parse for MetaArg {
     }
 }
 
+impl MetaArg {
+    /// Returns name of the argument for the name-value arguments (`name =
+    /// value`) and single-identifier path arguments (`name`), which can be
+    /// used as a key for [`crate::merge_attr_args`].
+    pub fn name(&self) -> Option<String> {
+        match self {
+            MetaArg::Literal(_) => None,
+            MetaArg::Path(path) => path.get_ident().map(Ident::to_string),
+            MetaArg::NameValue(meta) => Some(meta.name.to_string()),
+        }
+    }
+}
+
 impl ToTokens for MetaArg {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         match self {
@@ -17,9 +17,9 @@ use std::collections::HashMap;
 use std::convert::TryInto;
 
 use quote::ToTokens;
-use syn::{LitChar, LitFloat, LitInt, Path};
+use syn::{Attribute, LitChar, LitFloat, LitInt, Path};
 
-use crate::{ArgValue, Error, ValueClass};
+use crate::{ArgValue, Error, ParametrizedAttr, ValueClass};
 
 /// Structure requirements for parametrized attribute
 #[derive(Clone)]
@@ -76,6 +76,66 @@ impl AttrReq {
             bool_req: ValueReq::Prohibited,
         }
     }
+
+    /// Constructs [`AttrReq`] which does not accept any arguments. Use it as a
+    /// starting point for the builder methods like [`AttrReq::required`],
+    /// [`AttrReq::optional`] and [`AttrReq::flag`].
+    #[inline]
+    pub fn new() -> AttrReq { AttrReq::with(HashMap::new()) }
+
+    /// Adds named argument `name` with the provided requirements
+    pub fn arg(mut self, name: impl ToString, req: ArgValueReq) -> AttrReq {
+        self.arg_req.insert(name.to_string(), req);
+        self
+    }
+
+    /// Adds named argument `name` which must be present and hold a value of
+    /// the provided class
+    #[inline]
+    pub fn required(self, name: impl ToString, class: impl Into<ValueClass>) -> AttrReq {
+        self.arg(name, ArgValueReq::required(class))
+    }
+
+    /// Adds named argument `name` which may be absent, but if present must
+    /// hold a value of the provided class
+    #[inline]
+    pub fn optional(self, name: impl ToString, class: impl Into<ValueClass>) -> AttrReq {
+        self.arg(name, ArgValueReq::optional(class))
+    }
+
+    /// Adds named argument `name` which uses `default` value when it is not
+    /// assigned a value explicitly
+    #[inline]
+    pub fn with_default(self, name: impl ToString, default: impl Into<ArgValue>) -> AttrReq {
+        self.arg(name, ArgValueReq::with_default(default))
+    }
+
+    /// Adds flag argument `name` which must not hold a value: `#[attr(name)]`
+    #[inline]
+    pub fn flag(self, name: impl ToString) -> AttrReq { self.arg(name, ArgValueReq::Prohibited) }
+
+    /// Sets requirements for the path arguments
+    #[inline]
+    pub fn paths(mut self, req: ListReq<Path>) -> AttrReq {
+        self.path_req = req;
+        self
+    }
+
+    /// Collects all attributes with the given `name` into a single
+    /// [`ParametrizedAttr`] and checks it against the requirements, returning
+    /// the attribute with all arguments validated and defaults filled in.
+    pub fn parse(
+        self,
+        name: impl ToString + AsRef<str>,
+        attrs: &[Attribute],
+    ) -> Result<ParametrizedAttr, Error> {
+        ParametrizedAttr::with(name, attrs)?.checked(self)
+    }
+}
+
+impl Default for AttrReq {
+    #[inline]
+    fn default() -> Self { AttrReq::new() }
 }
 
 /// Requirements for attribute or named argument value presence
@@ -0,0 +1,125 @@
+// Rust language amplification derive library providing multiple generic trait
+// implementations, type wrappers, derive macros and other language enhancements
+//
+// Written in 2019-2021 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the MIT License
+// along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+use syn::spanned::Spanned;
+use syn::{Attribute, Fields, Index, Member, Type};
+
+use crate::Diagnostic;
+
+/// Policies applied by [`select_field`] when a field is not marked explicitly.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct SelectPolicy {
+    /// Fields of `PhantomData` type are not considered, such that the only
+    /// non-phantom field of the structure gets selected
+    pub skip_phantom: bool,
+}
+
+/// Field selected with [`select_field`].
+#[derive(Clone)]
+pub struct SelectedField {
+    /// Position of the field within the structure
+    pub index: usize,
+
+    /// Member used to access the field, i.e. its name or index
+    pub member: Member,
+
+    /// Type of the field
+    pub ty: Type,
+}
+
+/// Selects a field marked with `#[<marker>]` attribute, or the only field of
+/// the structure if none of the fields are marked.
+///
+/// Fails if more than one field is marked, if no fields are marked while the
+/// structure has multiple fields (not counting `PhantomData` fields when
+/// [`SelectPolicy::skip_phantom`] is set), or if there are no fields.
+pub fn select_field(
+    fields: &Fields,
+    marker: &str,
+    policy: SelectPolicy,
+) -> Result<SelectedField, syn::Error> {
+    let mut marked: Option<(usize, &Attribute)> = None;
+    for (index, field) in fields.iter().enumerate() {
+        for attr in field
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident(marker))
+        {
+            if let Some((_, first)) = marked {
+                return Err(Diagnostic::spanned(
+                    attr,
+                    format!("Only a single field may be marked with `#[{}]`", marker),
+                )
+                .note_spanned(first, format!("`#[{}]` is first used here", marker))
+                .help(format!("remove `#[{}]` from all fields except one", marker))
+                .into_syn_error());
+            }
+            marked = Some((index, attr));
+        }
+    }
+
+    let index = match marked {
+        Some((index, _)) => index,
+        None => {
+            let mut candidates = fields
+                .iter()
+                .enumerate()
+                .filter(|(_, field)| !(policy.skip_phantom && is_phantom(&field.ty)))
+                .map(|(index, _)| index);
+            match (candidates.next(), candidates.next()) {
+                (Some(index), None) => index,
+                (None, _) if fields.is_empty() => {
+                    return Err(syn::Error::new(fields.span(), "Structure has no fields"));
+                }
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        fields,
+                        format!(
+                            "When the structure has multiple fields you must point out one of \
+                             them with `#[{}]` attribute",
+                            marker
+                        ),
+                    ));
+                }
+            }
+        }
+    };
+
+    let field = fields
+        .iter()
+        .nth(index)
+        .expect("index is taken from the fields");
+    let member = match field.ident {
+        Some(ref ident) => Member::Named(ident.clone()),
+        None => Member::Unnamed(Index::from(index)),
+    };
+    Ok(SelectedField {
+        index,
+        member,
+        ty: field.ty.clone(),
+    })
+}
+
+/// Detects `PhantomData` type, given by any path ending with it
+fn is_phantom(ty: &Type) -> bool {
+    match ty {
+        Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .map_or(false, |segment| segment.ident == "PhantomData"),
+        _ => false,
+    }
+}
@@ -0,0 +1,96 @@
+// Rust language amplification derive library providing multiple generic trait
+// implementations, type wrappers, derive macros and other language enhancements
+//
+// Written in 2019-2021 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the MIT License
+// along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::ToTokens;
+use syn::Ident;
+use syn::spanned::Spanned;
+
+/// Non-fatal warning reported by a derive macro.
+///
+/// Since custom warnings are not available for proc macros on stable, the
+/// warning is emitted as a use of deprecated constant carrying the message,
+/// which is reported by the compiler at the span of the warning. Thus, the
+/// warning must be added to the derive output (for instance, with
+/// [`quote::quote!`]), and it may be silenced by the user with `#[allow(deprecated)]`.
+#[derive(Clone, Debug)]
+pub struct Warning {
+    span: Span,
+    message: String,
+}
+
+impl Warning {
+    /// Constructs warning pointing at the given span
+    pub fn new(span: Span, message: impl ToString) -> Warning {
+        Warning {
+            span,
+            message: message.to_string(),
+        }
+    }
+
+    /// Constructs warning pointing at the given tokens
+    pub fn spanned(tokens: impl Spanned, message: impl ToString) -> Warning {
+        Warning::new(tokens.span(), message)
+    }
+
+    /// Returns message of the warning
+    pub fn message(&self) -> &str { &self.message }
+}
+
+impl ToTokens for Warning {
+    fn to_tokens(&self, tokens: &mut TokenStream2) {
+        let message = &self.message;
+        let usage = Ident::new("warning", self.span);
+        tokens.extend(quote! {
+            const _: () = {
+                #[deprecated(note = #message)]
+                #[allow(non_upper_case_globals)]
+                const warning: () = ();
+                #usage
+            };
+        });
+    }
+}
+
+/// Collection of warnings accumulated during the processing of derive input.
+#[derive(Clone, Debug, Default)]
+pub struct Warnings(Vec<Warning>);
+
+impl Warnings {
+    /// Constructs empty collection of warnings
+    pub fn new() -> Warnings { Warnings::default() }
+
+    /// Adds warning pointing at the given tokens
+    pub fn warn(&mut self, tokens: impl Spanned, message: impl ToString) {
+        self.0.push(Warning::spanned(tokens, message))
+    }
+
+    /// Adds warning
+    pub fn push(&mut self, warning: Warning) { self.0.push(warning) }
+
+    /// Detects if there are no warnings
+    pub fn is_empty(&self) -> bool { self.0.is_empty() }
+
+    /// Returns iterator over the warnings
+    pub fn iter(&self) -> impl Iterator<Item = &Warning> { self.0.iter() }
+}
+
+impl ToTokens for Warnings {
+    fn to_tokens(&self, tokens: &mut TokenStream2) {
+        for warning in &self.0 {
+            warning.to_tokens(tokens)
+        }
+    }
+}
@@ -0,0 +1,123 @@
+//! Checks that the code generated by `From`, `TryFrom`, `SmartDefault`,
+//! `EnumIter`, `FromRepr`, `FromStr`, `Error`, `AsAny`, `AsAnyMut` and
+//! `Wrapper` derives compiles in `no_std` crates.
+
+#![no_std]
+#![allow(dead_code)]
+
+extern crate alloc;
+#[macro_use]
+extern crate amplify_derive;
+
+/// Traits provided by `amplify`, which is not a dependency of this crate
+mod shim {
+    use alloc::boxed::Box;
+    use core::any::Any;
+
+    pub trait AsAny {
+        fn as_any(&self) -> &dyn Any;
+    }
+
+    pub trait AsAnyMut {
+        fn as_any_mut(&mut self) -> &mut dyn Any;
+        fn into_any(self: Box<Self>) -> Box<dyn Any>;
+    }
+
+    pub trait Wrapper {
+        type Inner;
+        fn from_inner(inner: Self::Inner) -> Self;
+        fn as_inner(&self) -> &Self::Inner;
+        fn into_inner(self) -> Self::Inner;
+    }
+}
+
+#[derive(From)]
+#[from(u8)]
+pub struct Unit;
+
+#[derive(From)]
+pub struct Newtype(u32);
+
+#[derive(Default, From)]
+pub struct Named {
+    #[from]
+    #[from(u16)]
+    value: u32,
+    flag: bool,
+}
+
+#[derive(From)]
+#[from(tuple)]
+pub struct Pair(u8, u16);
+
+#[derive(From)]
+#[from(generic)]
+pub struct Generic(u64);
+
+#[derive(From)]
+#[from(infallible)]
+pub enum Value {
+    #[from(ref)]
+    Int(i64),
+    Tuple(u8, #[from] i8, u8),
+    Struct {
+        #[from]
+        #[into]
+        text: &'static str,
+        #[from(default = 1)]
+        count: usize,
+    },
+}
+
+#[derive(TryFrom)]
+#[try_from(u32)]
+pub struct Port(u16);
+
+#[derive(SmartDefault)]
+pub struct Defaults<T> {
+    #[default(7)]
+    count: usize,
+    #[default("none")]
+    text: &'static str,
+    value: Option<T>,
+}
+
+#[derive(SmartDefault)]
+pub enum Mode {
+    Off,
+    #[default]
+    On(#[default(1)] u8),
+}
+
+#[derive(Copy, Clone, EnumIter, FromRepr)]
+#[amplify_crate(no_std)]
+#[repr(u16)]
+pub enum Flag {
+    Read,
+    Write,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Display, FromStr)]
+#[display(lowercase)]
+#[amplify_crate(no_std)]
+pub enum Color {
+    Red,
+    Green,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Display, Error)]
+#[display(doc_comments)]
+#[amplify_crate(no_std)]
+pub enum Failure {
+    /// value {0} is out of range
+    OutOfRange(u8),
+}
+
+#[derive(AsAny, AsAnyMut)]
+#[amplify_crate(crate::shim, no_std)]
+pub struct Item(u8);
+
+#[derive(Clone, Wrapper, From)]
+#[wrapper(Display, FromStr)]
+#[amplify_crate(crate::shim, no_std)]
+pub struct Count(u32);